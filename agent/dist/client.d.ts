@@ -5,9 +5,10 @@
  *
  * Built by Turbinete 🚀
  */
-import { Connection, Keypair, PublicKey } from '@solana/web3.js';
+import { AccountMeta, Connection, Keypair, PublicKey } from '@solana/web3.js';
 import { ProtocolId } from './config.js';
 export interface OracleState {
+    version: number;
     isInitialized: boolean;
     authority: PublicKey;
     bestProtocol: number;
@@ -18,6 +19,21 @@ export interface OracleState {
     decisionsCount: bigint;
     cumulativePnl: bigint;
 }
+/**
+ * Raydium CPMM pool accounts for an on-chain swap
+ */
+export interface RaydiumCpmmPool {
+    poolAuthority: PublicKey;
+    ammConfig: PublicKey;
+    poolState: PublicKey;
+    inputVault: PublicKey;
+    outputVault: PublicKey;
+    inputTokenProgram: PublicKey;
+    outputTokenProgram: PublicKey;
+    inputMint: PublicKey;
+    outputMint: PublicKey;
+    observationState: PublicKey;
+}
 export declare class OracleClient {
     private connection;
     private payer;
@@ -26,24 +42,54 @@ export declare class OracleClient {
      * Derive the oracle PDA address
      */
     static deriveOracleAddress(authority: PublicKey): [PublicKey, number];
+    /**
+     * Pool accounts of a Raydium CPMM swap, in the order the program expects
+     * them after the destination token account
+     */
+    static raydiumPoolAccounts(pool: RaydiumCpmmPool): AccountMeta[];
     /**
      * Get oracle state from account data
      */
     getOracleState(oracleAddress: PublicKey): Promise<OracleState | null>;
     /**
-     * Initialize a new oracle account
+     * Whether raw oracle account data is initialized
+     *
+     * Version 1 accounts are initialized by existing; later versions carry
+     * an explicit status flag. Anything else is not an oracle.
      */
-    initialize(oracleKeypair: Keypair): Promise<string>;
+    static isInitialized(data: Buffer): boolean;
+    /**
+     * Initialize the payer's oracle
+     *
+     * The oracle is the PDA derived from the payer (see `deriveOracleAddress`);
+     * the program creates it, funded by the payer, and initializes it in the
+     * same instruction.
+     */
+    initialize(): Promise<string>;
     /**
      * Update oracle with new yield data
+     *
+     * Each observation carries a nonce above the last one the oracle
+     * accepted, so a relayed copy can't be replayed; the default, the current
+     * time in milliseconds, grows from one call to the next. Oracles migrated
+     * from before nonces take the layout without one until `RequireNonces`.
+     */
+    monitorYields(oracleAddress: PublicKey, protocol: ProtocolId, apyBps: number, riskScore: number, nonce?: bigint): Promise<string>;
+    /**
+     * Whether the oracle still takes observations without a nonce
      */
-    monitorYields(oracleAddress: PublicKey, protocol: ProtocolId, apyBps: number, riskScore: number): Promise<string>;
+    private takesLegacyObservations;
     /**
      * Publish strategy recommendation
      */
     publishStrategy(oracleAddress: PublicKey, protocol: ProtocolId, expectedApyBps: number, riskScore: number): Promise<string>;
     /**
-     * Execute a swap decision (records decision on-chain)
+     * Execute a swap decision
+     *
+     * Raydium CPMM swaps run on-chain, signed by the oracle PDA: pass the
+     * pool's accounts as `venueAccounts` (see `raydiumPoolAccounts`), with
+     * `sourceToken` owned by the oracle. Jupiter swaps executed off-chain
+     * need none and are only recorded.
      */
-    executeSwap(oracleAddress: PublicKey, sourceToken: PublicKey, destToken: PublicKey, amountIn: bigint, minAmountOut: bigint, protocol: ProtocolId): Promise<string>;
+    executeSwap(oracleAddress: PublicKey, sourceToken: PublicKey, destToken: PublicKey, amountIn: bigint, minAmountOut: bigint, protocol: ProtocolId, venueAccounts?: AccountMeta[]): Promise<string>;
 }
@@ -5,8 +5,8 @@
  *
  * Built by Turbinete 🚀
  */
-import { PublicKey, Transaction, TransactionInstruction, sendAndConfirmTransaction, SystemProgram, } from '@solana/web3.js';
-import { PROGRAM_ID, DISCRIMINATOR, ORACLE_STATE_SIZE } from './config.js';
+import { PublicKey, Transaction, TransactionInstruction, sendAndConfirmTransaction, SystemProgram, SYSVAR_CLOCK_PUBKEY, } from '@solana/web3.js';
+import { PROGRAM_ID, RAYDIUM_CPMM_PROGRAM_ID, DISCRIMINATOR, ORACLE_STATE_SIZE, STATUS_FLAGS_VERSION, STATUS_FLAGS_OFFSET, STATUS_INITIALIZED, STATUS_LEGACY_OBSERVATIONS, } from './config.js';
 export class OracleClient {
     connection;
     payer;
@@ -20,6 +20,27 @@ export class OracleClient {
     static deriveOracleAddress(authority) {
         return PublicKey.findProgramAddressSync([Buffer.from('oracle'), authority.toBuffer()], PROGRAM_ID);
     }
+    /**
+     * Pool accounts of a Raydium CPMM swap, in the order the program expects
+     * them after the destination token account
+     */
+    static raydiumPoolAccounts(pool) {
+        const readonly = (pubkey) => ({ pubkey, isSigner: false, isWritable: false });
+        const writable = (pubkey) => ({ pubkey, isSigner: false, isWritable: true });
+        return [
+            readonly(RAYDIUM_CPMM_PROGRAM_ID),
+            readonly(pool.poolAuthority),
+            readonly(pool.ammConfig),
+            writable(pool.poolState),
+            writable(pool.inputVault),
+            writable(pool.outputVault),
+            readonly(pool.inputTokenProgram),
+            readonly(pool.outputTokenProgram),
+            readonly(pool.inputMint),
+            readonly(pool.outputMint),
+            writable(pool.observationState),
+        ];
+    }
     /**
      * Get oracle state from account data
      */
@@ -29,8 +50,10 @@ export class OracleClient {
             return null;
         }
         const data = accountInfo.data;
+        const version = data[0];
         return {
-            isInitialized: data[0] === 1,
+            version,
+            isInitialized: OracleClient.isInitialized(data),
             authority: new PublicKey(data.slice(1, 33)),
             bestProtocol: data[33],
             currentApyBps: data.readUInt16LE(34),
@@ -42,51 +65,71 @@ export class OracleClient {
         };
     }
     /**
-     * Initialize a new oracle account
+     * Whether raw oracle account data is initialized
+     *
+     * Version 1 accounts are initialized by existing; later versions carry
+     * an explicit status flag. Anything else is not an oracle.
      */
-    async initialize(oracleKeypair) {
-        const space = ORACLE_STATE_SIZE;
-        const lamports = await this.connection.getMinimumBalanceForRentExemption(space);
-        // Create account instruction
-        const createAccountIx = SystemProgram.createAccount({
-            fromPubkey: this.payer.publicKey,
-            newAccountPubkey: oracleKeypair.publicKey,
-            lamports,
-            space,
-            programId: PROGRAM_ID,
-        });
-        // Initialize instruction
+    static isInitialized(data) {
+        const version = data[0];
+        if (version === 1) {
+            return true;
+        }
+        return version === STATUS_FLAGS_VERSION
+            && data.length > STATUS_FLAGS_OFFSET
+            && (data[STATUS_FLAGS_OFFSET] & STATUS_INITIALIZED) !== 0;
+    }
+    /**
+     * Initialize the payer's oracle
+     *
+     * The oracle is the PDA derived from the payer (see `deriveOracleAddress`);
+     * the program creates it, funded by the payer, and initializes it in the
+     * same instruction.
+     */
+    async initialize() {
+        const [oracleAddress] = OracleClient.deriveOracleAddress(this.payer.publicKey);
         const initializeIx = new TransactionInstruction({
             programId: PROGRAM_ID,
             keys: [
-                { pubkey: oracleKeypair.publicKey, isSigner: false, isWritable: true },
-                { pubkey: this.payer.publicKey, isSigner: true, isWritable: false },
+                { pubkey: oracleAddress, isSigner: false, isWritable: true },
+                { pubkey: this.payer.publicKey, isSigner: true, isWritable: true },
                 { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
             ],
             data: Buffer.from([DISCRIMINATOR.INITIALIZE]),
         });
-        const tx = new Transaction().add(createAccountIx, initializeIx);
-        const sig = await sendAndConfirmTransaction(this.connection, tx, [this.payer, oracleKeypair]);
-        console.log(`✅ Oracle initialized: ${oracleKeypair.publicKey.toBase58()}`);
+        const tx = new Transaction().add(initializeIx);
+        const sig = await sendAndConfirmTransaction(this.connection, tx, [this.payer]);
+        console.log(`✅ Oracle initialized: ${oracleAddress.toBase58()}`);
         return sig;
     }
     /**
      * Update oracle with new yield data
+     *
+     * Each observation carries a nonce above the last one the oracle
+     * accepted, so a relayed copy can't be replayed; the default, the current
+     * time in milliseconds, grows from one call to the next. Oracles migrated
+     * from before nonces take the layout without one until `RequireNonces`.
      */
-    async monitorYields(oracleAddress, protocol, apyBps, riskScore) {
+    async monitorYields(oracleAddress, protocol, apyBps, riskScore, nonce = BigInt(Date.now())) {
         const timestamp = BigInt(Math.floor(Date.now() / 1000));
-        // Build instruction data: discriminator + protocol + apy_bps + risk_score + timestamp
-        const data = Buffer.alloc(13);
+        const legacy = await this.takesLegacyObservations(oracleAddress);
+        // Build instruction data: discriminator + protocol + apy_bps + risk_score + timestamp + nonce
+        const data = Buffer.alloc(legacy ? 13 : 21);
         data.writeUInt8(DISCRIMINATOR.MONITOR_YIELDS, 0);
         data.writeUInt8(protocol, 1);
         data.writeUInt16LE(apyBps, 2);
         data.writeUInt8(riskScore, 4);
         data.writeBigInt64LE(timestamp, 5);
+        if (!legacy) {
+            data.writeBigUInt64LE(nonce, 13);
+        }
+        // The clock lets the program refuse observations from the future
         const ix = new TransactionInstruction({
             programId: PROGRAM_ID,
             keys: [
                 { pubkey: oracleAddress, isSigner: false, isWritable: true },
                 { pubkey: this.payer.publicKey, isSigner: true, isWritable: false },
+                { pubkey: SYSVAR_CLOCK_PUBKEY, isSigner: false, isWritable: false },
             ],
             data,
         });
@@ -94,6 +137,18 @@ export class OracleClient {
         const sig = await sendAndConfirmTransaction(this.connection, tx, [this.payer]);
         return sig;
     }
+    /**
+     * Whether the oracle still takes observations without a nonce
+     */
+    async takesLegacyObservations(oracleAddress) {
+        const accountInfo = await this.connection.getAccountInfo(oracleAddress);
+        if (!accountInfo || accountInfo.data.length <= STATUS_FLAGS_OFFSET) {
+            return false;
+        }
+        const data = accountInfo.data;
+        return data[0] === STATUS_FLAGS_VERSION
+            && (data[STATUS_FLAGS_OFFSET] & STATUS_LEGACY_OBSERVATIONS) !== 0;
+    }
     /**
      * Publish strategy recommendation
      */
@@ -118,9 +173,15 @@ export class OracleClient {
         return sig;
     }
     /**
-     * Execute a swap decision (records decision on-chain)
+     * Execute a swap decision
+     *
+     * Raydium CPMM swaps run on-chain, signed by the oracle PDA: pass the
+     * pool's accounts as `venueAccounts` (see `raydiumPoolAccounts`), with
+     * `sourceToken` owned by the oracle. Jupiter swaps executed off-chain
+     * need none and are only recorded.
      */
-    async executeSwap(oracleAddress, sourceToken, destToken, amountIn, minAmountOut, protocol) {
+    async executeSwap(oracleAddress, sourceToken, destToken, amountIn, minAmountOut, protocol, venueAccounts = []) {
+        // Build instruction data: discriminator + amount_in + min_amount_out + protocol
         const data = Buffer.alloc(18);
         data.writeUInt8(DISCRIMINATOR.EXECUTE_SWAP, 0);
         data.writeBigUInt64LE(amountIn, 1);
@@ -133,6 +194,7 @@ export class OracleClient {
                 { pubkey: this.payer.publicKey, isSigner: true, isWritable: false },
                 { pubkey: sourceToken, isSigner: false, isWritable: true },
                 { pubkey: destToken, isSigner: false, isWritable: true },
+                ...venueAccounts,
             ],
             data,
         });
@@ -5,6 +5,7 @@
  */
 import { PublicKey, Cluster } from '@solana/web3.js';
 export declare const PROGRAM_ID: PublicKey;
+export declare const RAYDIUM_CPMM_PROGRAM_ID: PublicKey;
 export declare const PROTOCOL: {
     readonly RAYDIUM_CPMM: 0;
     readonly JUPITER_ROUTE: 1;
@@ -23,6 +24,10 @@ export declare const DISCRIMINATOR: {
     readonly EMERGENCY_WITHDRAW: 5;
 };
 export declare const ORACLE_STATE_SIZE = 69;
+export declare const STATUS_FLAGS_VERSION = 2;
+export declare const STATUS_FLAGS_OFFSET = 71;
+export declare const STATUS_INITIALIZED: number;
+export declare const STATUS_LEGACY_OBSERVATIONS: number;
 export declare const DEFAULT_CONFIG: {
     cluster: Cluster;
     rpcUrl: string;
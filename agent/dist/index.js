@@ -157,9 +157,8 @@ class YieldOracleAgent {
         }
         // Create new oracle
         this.log('INFO', '📝 Creating new oracle account...');
-        const oracleKeypair = Keypair.generate();
-        await this.client.initialize(oracleKeypair);
-        this.oracleAddress = oracleKeypair.publicKey;
+        await this.client.initialize();
+        [this.oracleAddress] = OracleClient.deriveOracleAddress(this.payer.publicKey);
         this.log('INFO', `✅ Oracle created: ${this.oracleAddress.toBase58()}`);
     }
     /**
@@ -18,20 +18,20 @@ async function main() {
     const connection = new Connection('https://api.mainnet-beta.solana.com', 'confirmed');
     const balance = await connection.getBalance(payer.publicKey);
     console.log(`💵 Balance: ${balance / 1e9} SOL\n`);
-    // Create oracle account (new keypair for mainnet)
-    const oracleKeypair = Keypair.generate();
-    console.log(`📊 Oracle Address: ${oracleKeypair.publicKey.toBase58()}`);
+    // The oracle is the PDA derived from the wallet
+    const [oracleAddress] = OracleClient.deriveOracleAddress(payer.publicKey);
+    console.log(`📊 Oracle Address: ${oracleAddress.toBase58()}`);
     // Initialize oracle
     const client = new OracleClient(connection, payer);
     try {
-        const sig = await client.initialize(oracleKeypair);
+        const sig = await client.initialize();
         console.log(`\n✅ MAINNET ORACLE INITIALIZED!`);
         console.log(`📝 Signature: ${sig}`);
         console.log(`\n🔗 Explorer: https://explorer.solana.com/tx/${sig}`);
-        console.log(`🔗 Oracle: https://explorer.solana.com/address/${oracleKeypair.publicKey.toBase58()}`);
+        console.log(`🔗 Oracle: https://explorer.solana.com/address/${oracleAddress.toBase58()}`);
         // Save oracle address for future reference
         const oracleInfo = {
-            address: oracleKeypair.publicKey.toBase58(),
+            address: oracleAddress.toBase58(),
             initSignature: sig,
             network: 'mainnet-beta',
             timestamp: new Date().toISOString(),
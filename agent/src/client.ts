@@ -7,6 +7,7 @@
  */
 
 import {
+  AccountMeta,
   Connection,
   Keypair,
   PublicKey,
@@ -14,10 +15,22 @@ import {
   TransactionInstruction,
   sendAndConfirmTransaction,
   SystemProgram,
+  SYSVAR_CLOCK_PUBKEY,
 } from '@solana/web3.js';
-import { PROGRAM_ID, DISCRIMINATOR, ORACLE_STATE_SIZE, ProtocolId } from './config.js';
+import {
+  PROGRAM_ID,
+  RAYDIUM_CPMM_PROGRAM_ID,
+  DISCRIMINATOR,
+  ORACLE_STATE_SIZE,
+  STATUS_FLAGS_VERSION,
+  STATUS_FLAGS_OFFSET,
+  STATUS_INITIALIZED,
+  STATUS_LEGACY_OBSERVATIONS,
+  ProtocolId,
+} from './config.js';
 
 export interface OracleState {
+  version: number;
   isInitialized: boolean;
   authority: PublicKey;
   bestProtocol: number;
@@ -29,6 +42,22 @@ export interface OracleState {
   cumulativePnl: bigint;
 }
 
+/**
+ * Raydium CPMM pool accounts for an on-chain swap
+ */
+export interface RaydiumCpmmPool {
+  poolAuthority: PublicKey;
+  ammConfig: PublicKey;
+  poolState: PublicKey;
+  inputVault: PublicKey;
+  outputVault: PublicKey;
+  inputTokenProgram: PublicKey;
+  outputTokenProgram: PublicKey;
+  inputMint: PublicKey;
+  outputMint: PublicKey;
+  observationState: PublicKey;
+}
+
 export class OracleClient {
   private connection: Connection;
   private payer: Keypair;
@@ -48,6 +77,28 @@ export class OracleClient {
     );
   }
 
+  /**
+   * Pool accounts of a Raydium CPMM swap, in the order the program expects
+   * them after the destination token account
+   */
+  static raydiumPoolAccounts(pool: RaydiumCpmmPool): AccountMeta[] {
+    const readonly = (pubkey: PublicKey) => ({ pubkey, isSigner: false, isWritable: false });
+    const writable = (pubkey: PublicKey) => ({ pubkey, isSigner: false, isWritable: true });
+    return [
+      readonly(RAYDIUM_CPMM_PROGRAM_ID),
+      readonly(pool.poolAuthority),
+      readonly(pool.ammConfig),
+      writable(pool.poolState),
+      writable(pool.inputVault),
+      writable(pool.outputVault),
+      readonly(pool.inputTokenProgram),
+      readonly(pool.outputTokenProgram),
+      readonly(pool.inputMint),
+      readonly(pool.outputMint),
+      writable(pool.observationState),
+    ];
+  }
+
   /**
    * Get oracle state from account data
    */
@@ -58,8 +109,10 @@ export class OracleClient {
     }
 
     const data = accountInfo.data;
+    const version = data[0];
     return {
-      isInitialized: data[0] === 1,
+      version,
+      isInitialized: OracleClient.isInitialized(data),
       authority: new PublicKey(data.slice(1, 33)),
       bestProtocol: data[33],
       currentApyBps: data.readUInt16LE(34),
@@ -72,67 +125,84 @@ export class OracleClient {
   }
 
   /**
-   * Initialize a new oracle account
+   * Whether raw oracle account data is initialized
+   *
+   * Version 1 accounts are initialized by existing; later versions carry
+   * an explicit status flag. Anything else is not an oracle.
    */
-  async initialize(oracleKeypair: Keypair): Promise<string> {
-    const space = ORACLE_STATE_SIZE;
-    const lamports = await this.connection.getMinimumBalanceForRentExemption(space);
-
-    // Create account instruction
-    const createAccountIx = SystemProgram.createAccount({
-      fromPubkey: this.payer.publicKey,
-      newAccountPubkey: oracleKeypair.publicKey,
-      lamports,
-      space,
-      programId: PROGRAM_ID,
-    });
+  static isInitialized(data: Buffer): boolean {
+    const version = data[0];
+    if (version === 1) {
+      return true;
+    }
+    return version === STATUS_FLAGS_VERSION
+      && data.length > STATUS_FLAGS_OFFSET
+      && (data[STATUS_FLAGS_OFFSET] & STATUS_INITIALIZED) !== 0;
+  }
+
+  /**
+   * Initialize the payer's oracle
+   *
+   * The oracle is the PDA derived from the payer (see `deriveOracleAddress`);
+   * the program creates it, funded by the payer, and initializes it in the
+   * same instruction.
+   */
+  async initialize(): Promise<string> {
+    const [oracleAddress] = OracleClient.deriveOracleAddress(this.payer.publicKey);
 
-    // Initialize instruction
     const initializeIx = new TransactionInstruction({
       programId: PROGRAM_ID,
       keys: [
-        { pubkey: oracleKeypair.publicKey, isSigner: false, isWritable: true },
-        { pubkey: this.payer.publicKey, isSigner: true, isWritable: false },
+        { pubkey: oracleAddress, isSigner: false, isWritable: true },
+        { pubkey: this.payer.publicKey, isSigner: true, isWritable: true },
         { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
       ],
       data: Buffer.from([DISCRIMINATOR.INITIALIZE]),
     });
 
-    const tx = new Transaction().add(createAccountIx, initializeIx);
-    const sig = await sendAndConfirmTransaction(
-      this.connection,
-      tx,
-      [this.payer, oracleKeypair]
-    );
+    const tx = new Transaction().add(initializeIx);
+    const sig = await sendAndConfirmTransaction(this.connection, tx, [this.payer]);
 
-    console.log(`✅ Oracle initialized: ${oracleKeypair.publicKey.toBase58()}`);
+    console.log(`✅ Oracle initialized: ${oracleAddress.toBase58()}`);
     return sig;
   }
 
   /**
    * Update oracle with new yield data
+   *
+   * Each observation carries a nonce above the last one the oracle
+   * accepted, so a relayed copy can't be replayed; the default, the current
+   * time in milliseconds, grows from one call to the next. Oracles migrated
+   * from before nonces take the layout without one until `RequireNonces`.
    */
   async monitorYields(
     oracleAddress: PublicKey,
     protocol: ProtocolId,
     apyBps: number,
     riskScore: number,
+    nonce: bigint = BigInt(Date.now()),
   ): Promise<string> {
     const timestamp = BigInt(Math.floor(Date.now() / 1000));
+    const legacy = await this.takesLegacyObservations(oracleAddress);
 
-    // Build instruction data: discriminator + protocol + apy_bps + risk_score + timestamp
-    const data = Buffer.alloc(13);
+    // Build instruction data: discriminator + protocol + apy_bps + risk_score + timestamp + nonce
+    const data = Buffer.alloc(legacy ? 13 : 21);
     data.writeUInt8(DISCRIMINATOR.MONITOR_YIELDS, 0);
     data.writeUInt8(protocol, 1);
     data.writeUInt16LE(apyBps, 2);
     data.writeUInt8(riskScore, 4);
     data.writeBigInt64LE(timestamp, 5);
+    if (!legacy) {
+      data.writeBigUInt64LE(nonce, 13);
+    }
 
+    // The clock lets the program refuse observations from the future
     const ix = new TransactionInstruction({
       programId: PROGRAM_ID,
       keys: [
         { pubkey: oracleAddress, isSigner: false, isWritable: true },
         { pubkey: this.payer.publicKey, isSigner: true, isWritable: false },
+        { pubkey: SYSVAR_CLOCK_PUBKEY, isSigner: false, isWritable: false },
       ],
       data,
     });
@@ -143,6 +213,20 @@ export class OracleClient {
     return sig;
   }
 
+  /**
+   * Whether the oracle still takes observations without a nonce
+   */
+  private async takesLegacyObservations(oracleAddress: PublicKey): Promise<boolean> {
+    const accountInfo = await this.connection.getAccountInfo(oracleAddress);
+    if (!accountInfo || accountInfo.data.length <= STATUS_FLAGS_OFFSET) {
+      return false;
+    }
+
+    const data = accountInfo.data;
+    return data[0] === STATUS_FLAGS_VERSION
+      && (data[STATUS_FLAGS_OFFSET] & STATUS_LEGACY_OBSERVATIONS) !== 0;
+  }
+
   /**
    * Publish strategy recommendation
    */
@@ -177,7 +261,12 @@ export class OracleClient {
   }
 
   /**
-   * Execute a swap decision (records decision on-chain)
+   * Execute a swap decision
+   *
+   * Raydium CPMM swaps run on-chain, signed by the oracle PDA: pass the
+   * pool's accounts as `venueAccounts` (see `raydiumPoolAccounts`), with
+   * `sourceToken` owned by the oracle. Jupiter swaps executed off-chain
+   * need none and are only recorded.
    */
   async executeSwap(
     oracleAddress: PublicKey,
@@ -186,7 +275,9 @@ export class OracleClient {
     amountIn: bigint,
     minAmountOut: bigint,
     protocol: ProtocolId,
+    venueAccounts: AccountMeta[] = [],
   ): Promise<string> {
+    // Build instruction data: discriminator + amount_in + min_amount_out + protocol
     const data = Buffer.alloc(18);
     data.writeUInt8(DISCRIMINATOR.EXECUTE_SWAP, 0);
     data.writeBigUInt64LE(amountIn, 1);
@@ -200,6 +291,7 @@ export class OracleClient {
         { pubkey: this.payer.publicKey, isSigner: true, isWritable: false },
         { pubkey: sourceToken, isSigner: false, isWritable: true },
         { pubkey: destToken, isSigner: false, isWritable: true },
+        ...venueAccounts,
       ],
       data,
     });
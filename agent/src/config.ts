@@ -9,6 +9,9 @@ import { PublicKey, Cluster } from '@solana/web3.js';
 // Program ID on devnet (and eventually mainnet)
 export const PROGRAM_ID = new PublicKey('E7nfxrs1We4muQNAbqnyJwVGFA5WvJPAtvUbt8BoCeRq');
 
+// Raydium CPMM, which the program swaps through on-chain
+export const RAYDIUM_CPMM_PROGRAM_ID = new PublicKey('CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C');
+
 // Protocol identifiers (must match on-chain program)
 export const PROTOCOL = {
   RAYDIUM_CPMM: 0,
@@ -40,7 +43,14 @@ export const DISCRIMINATOR = {
 } as const;
 
 // Oracle state layout
-export const ORACLE_STATE_SIZE = 69; // bytes
+export const ORACLE_STATE_SIZE = 69; // bytes, of the original version 1 layout
+
+// Byte 0 is the layout version; from version 2 on, initialization is a
+// status flag rather than implied by the version
+export const STATUS_FLAGS_VERSION = 2;
+export const STATUS_FLAGS_OFFSET = 71;
+export const STATUS_INITIALIZED = 1 << 1;
+export const STATUS_LEGACY_OBSERVATIONS = 1 << 4;
 
 // Default configuration
 export const DEFAULT_CONFIG = {
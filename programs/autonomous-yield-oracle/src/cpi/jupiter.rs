@@ -0,0 +1,67 @@
+//! Jupiter route CPI
+//!
+//! The route instruction is built off-chain by the agent (Jupiter's quote
+//! and swap-instructions API) and forwarded as is: its data verbatim, its
+//! accounts in their given order. The oracle PDA is the route's user
+//! transfer authority and signs the CPI.
+
+use pinocchio::{
+    cpi::{slice_invoke_signed, Signer},
+    instruction::{InstructionAccount, InstructionView},
+    AccountView, Address, ProgramResult,
+};
+use solana_program_error::ProgramError;
+
+/// Jupiter aggregator v6 program (JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4)
+pub const JUPITER_PROGRAM_ID: Address = Address::new_from_array([
+    0x04, 0x79, 0xd5, 0x5b, 0xf2, 0x31, 0xc0, 0x6e,
+    0xee, 0x74, 0xc5, 0x6e, 0xce, 0x68, 0x15, 0x07,
+    0xfd, 0xb1, 0xb2, 0xde, 0xa3, 0xf4, 0x8e, 0x51,
+    0x02, 0xb1, 0xcd, 0xa2, 0x56, 0xbc, 0x13, 0x8f,
+]);
+
+/// Most accounts a forwarded route may use
+pub const MAX_ROUTE_ACCOUNTS: usize = 64;
+
+/// Invoke a pre-built Jupiter route, signed by `authority`
+///
+/// `accounts` starts with the Jupiter program, followed by the route's
+/// accounts. Each keeps the writability it has in this transaction;
+/// `authority` is additionally marked as a signer.
+pub fn route(
+    authority: &Address,
+    accounts: &[AccountView],
+    route_data: &[u8],
+    signers: &[Signer],
+) -> ProgramResult {
+    let [program, route_accounts @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if *program.address() != JUPITER_PROGRAM_ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if route_accounts.len() > MAX_ROUTE_ACCOUNTS {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let metas: Vec<InstructionAccount> = route_accounts
+        .iter()
+        .map(|account| {
+            InstructionAccount::new(
+                account.address(),
+                account.is_writable(),
+                account.is_signer() || account.address() == authority,
+            )
+        })
+        .collect();
+    let views: Vec<&AccountView> = route_accounts.iter().collect();
+
+    let instruction = InstructionView {
+        program_id: &JUPITER_PROGRAM_ID,
+        accounts: &metas,
+        data: route_data,
+    };
+
+    slice_invoke_signed(&instruction, &views, signers)
+}
@@ -0,0 +1,92 @@
+//! BPF upgradeable loader account helpers
+//!
+//! Reads the upgrade authority out of this program's program data account,
+//! so instructions can ask for it as a co-signer.
+
+use pinocchio::{AccountView, Address};
+use solana_program_error::ProgramError;
+
+/// BPF upgradeable loader (BPFLoaderUpgradeab1e11111111111111111111111)
+pub const BPF_LOADER_UPGRADEABLE_ID: Address = Address::new_from_array([
+    0x02, 0xa8, 0xf6, 0x91, 0x4e, 0x88, 0xa1, 0xb0,
+    0xe2, 0x10, 0x15, 0x3e, 0xf7, 0x63, 0xae, 0x2b,
+    0x00, 0xc2, 0xb9, 0x3d, 0x16, 0xc1, 0x24, 0xd2,
+    0xc0, 0x53, 0x7a, 0x10, 0x04, 0x80, 0x00, 0x00,
+]);
+
+/// `UpgradeableLoaderState::ProgramData` tag (4 bytes as le)
+const PROGRAM_DATA: u32 = 3;
+
+/// Program data header: tag (4) + slot (8) + authority option (1) +
+/// authority (32) = 45 bytes
+const PROGRAM_DATA_HEADER_LEN: usize = 45;
+
+/// Address of the program data account of `program_id`
+pub fn program_data_address(program_id: &Address) -> Address {
+    Address::find_program_address(&[program_id.as_ref()], &BPF_LOADER_UPGRADEABLE_ID).0
+}
+
+/// Parse the upgrade authority from program data account data
+///
+/// `None` for an immutable program. Fails with `InvalidAccountData` for
+/// anything but a program data account.
+pub fn parse_upgrade_authority(data: &[u8]) -> Result<Option<[u8; 32]>, ProgramError> {
+    let Some(header) = data.get(..PROGRAM_DATA_HEADER_LEN) else {
+        return Err(ProgramError::InvalidAccountData);
+    };
+    if u32::from_le_bytes(header[0..4].try_into().unwrap()) != PROGRAM_DATA {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    match header[12] {
+        0 => Ok(None),
+        1 => Ok(Some(header[13..45].try_into().unwrap())),
+        _ => Err(ProgramError::InvalidAccountData),
+    }
+}
+
+/// Read the upgrade authority of `program_id` from its program data account
+///
+/// Fails with `InvalidSeeds` if `program_data` isn't that account, and with
+/// `InvalidAccountOwner` unless the loader owns it.
+pub fn upgrade_authority(
+    program_data: &AccountView,
+    program_id: &Address,
+) -> Result<Option<[u8; 32]>, ProgramError> {
+    if *program_data.address() != program_data_address(program_id) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if !program_data.owned_by(&BPF_LOADER_UPGRADEABLE_ID) {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+    let data = program_data.try_borrow()?;
+    parse_upgrade_authority(&data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_upgrade_authority() {
+        let mut data = [0u8; PROGRAM_DATA_HEADER_LEN + 16];
+        data[0..4].copy_from_slice(&PROGRAM_DATA.to_le_bytes());
+        data[4..12].copy_from_slice(&123_456u64.to_le_bytes());
+        data[12] = 1;
+        data[13..45].copy_from_slice(&[7u8; 32]);
+        assert_eq!(parse_upgrade_authority(&data), Ok(Some([7u8; 32])));
+        assert_eq!(parse_upgrade_authority(&data[..45]), Ok(Some([7u8; 32])));
+
+        // Immutable
+        data[12] = 0;
+        assert_eq!(parse_upgrade_authority(&data), Ok(None));
+
+        // Not program data
+        assert_eq!(parse_upgrade_authority(&data[..44]), Err(ProgramError::InvalidAccountData));
+        data[12] = 2;
+        assert_eq!(parse_upgrade_authority(&data), Err(ProgramError::InvalidAccountData));
+        data[12] = 1;
+        data[0..4].copy_from_slice(&2u32.to_le_bytes());
+        assert_eq!(parse_upgrade_authority(&data), Err(ProgramError::InvalidAccountData));
+    }
+}
@@ -0,0 +1,10 @@
+//! CPI module
+//!
+//! Hand-built cross-program invocations into the venues the oracle trades on.
+//! Instructions are assembled manually since the published CPI crates target
+//! older pinocchio `AccountInfo` types.
+
+pub mod jupiter;
+pub mod loader;
+pub mod raydium;
+pub mod token;
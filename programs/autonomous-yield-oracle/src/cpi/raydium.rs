@@ -0,0 +1,146 @@
+//! Raydium CPMM swap CPI
+//!
+//! Builds `swap_base_input` by hand with the oracle PDA as the payer, so
+//! the source/destination token accounts must be owned by the oracle.
+
+use pinocchio::{
+    cpi::{invoke_signed, Signer},
+    instruction::{InstructionAccount, InstructionView},
+    AccountView, Address, ProgramResult,
+};
+use solana_program_error::ProgramError;
+
+use super::token::require_token_program;
+
+/// Raydium CPMM program (CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C)
+pub const RAYDIUM_CPMM_PROGRAM_ID: Address = Address::new_from_array([
+    0xa9, 0x2a, 0x5a, 0x8b, 0x4f, 0x29, 0x59, 0x52,
+    0x84, 0x25, 0x50, 0xaa, 0x93, 0xfd, 0x5b, 0x95,
+    0xb5, 0xac, 0xe6, 0xa8, 0xeb, 0x92, 0x0c, 0x93,
+    0x94, 0x2e, 0x43, 0x69, 0x0c, 0x20, 0xec, 0x73,
+]);
+
+/// Anchor discriminator of `swap_base_input`
+const SWAP_BASE_INPUT: [u8; 8] = [143, 190, 90, 218, 196, 30, 51, 222];
+
+/// Number of `RaydiumSwapAccounts`
+pub const RAYDIUM_SWAP_ACCOUNTS: usize = 11;
+
+/// Pool accounts for a Raydium CPMM swap
+///
+/// Passed after `dest_token` in this order.
+pub struct RaydiumSwapAccounts<'a> {
+    /// Raydium CPMM program
+    pub program: &'a AccountView,
+    /// Pool vault authority
+    pub pool_authority: &'a AccountView,
+    /// AMM config
+    pub amm_config: &'a AccountView,
+    /// Pool state
+    pub pool_state: &'a AccountView,
+    /// Vault receiving the input token
+    pub input_vault: &'a AccountView,
+    /// Vault paying out the output token
+    pub output_vault: &'a AccountView,
+    /// Token program of the input mint (SPL Token or Token-2022)
+    pub input_token_program: &'a AccountView,
+    /// Token program of the output mint (SPL Token or Token-2022)
+    pub output_token_program: &'a AccountView,
+    /// Input mint
+    pub input_mint: &'a AccountView,
+    /// Output mint
+    pub output_mint: &'a AccountView,
+    /// Pool observation state
+    pub observation_state: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for RaydiumSwapAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [program, pool_authority, amm_config, pool_state, input_vault, output_vault, input_token_program, output_token_program, input_mint, output_mint, observation_state, ..] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if *program.address() != RAYDIUM_CPMM_PROGRAM_ID {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        require_token_program(input_token_program)?;
+        require_token_program(output_token_program)?;
+
+        Ok(Self {
+            program,
+            pool_authority,
+            amm_config,
+            pool_state,
+            input_vault,
+            output_vault,
+            input_token_program,
+            output_token_program,
+            input_mint,
+            output_mint,
+            observation_state,
+        })
+    }
+}
+
+/// Swap `amount_in` of the source token for at least `min_amount_out`
+#[allow(clippy::too_many_arguments)]
+pub fn swap_base_input(
+    payer: &AccountView,
+    source_token: &AccountView,
+    dest_token: &AccountView,
+    pool: &RaydiumSwapAccounts,
+    amount_in: u64,
+    min_amount_out: u64,
+    signers: &[Signer],
+) -> ProgramResult {
+    let mut data = [0u8; 24];
+    data[0..8].copy_from_slice(&SWAP_BASE_INPUT);
+    data[8..16].copy_from_slice(&amount_in.to_le_bytes());
+    data[16..24].copy_from_slice(&min_amount_out.to_le_bytes());
+
+    let accounts = [
+        InstructionAccount::readonly_signer(payer.address()),
+        InstructionAccount::readonly(pool.pool_authority.address()),
+        InstructionAccount::readonly(pool.amm_config.address()),
+        InstructionAccount::writable(pool.pool_state.address()),
+        InstructionAccount::writable(source_token.address()),
+        InstructionAccount::writable(dest_token.address()),
+        InstructionAccount::writable(pool.input_vault.address()),
+        InstructionAccount::writable(pool.output_vault.address()),
+        InstructionAccount::readonly(pool.input_token_program.address()),
+        InstructionAccount::readonly(pool.output_token_program.address()),
+        InstructionAccount::readonly(pool.input_mint.address()),
+        InstructionAccount::readonly(pool.output_mint.address()),
+        InstructionAccount::writable(pool.observation_state.address()),
+    ];
+
+    let instruction = InstructionView {
+        program_id: &RAYDIUM_CPMM_PROGRAM_ID,
+        accounts: &accounts,
+        data: &data,
+    };
+
+    invoke_signed(
+        &instruction,
+        &[
+            payer,
+            pool.pool_authority,
+            pool.amm_config,
+            pool.pool_state,
+            source_token,
+            dest_token,
+            pool.input_vault,
+            pool.output_vault,
+            pool.input_token_program,
+            pool.output_token_program,
+            pool.input_mint,
+            pool.output_mint,
+            pool.observation_state,
+        ],
+        signers,
+    )
+}
@@ -0,0 +1,107 @@
+//! SPL Token account helpers
+
+use pinocchio::{
+    cpi::{invoke_signed, Signer},
+    instruction::{InstructionAccount, InstructionView},
+    AccountView, Address, ProgramResult,
+};
+use solana_program_error::ProgramError;
+
+/// SPL Token program (TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA)
+pub const TOKEN_PROGRAM_ID: Address = Address::new_from_array([
+    0x06, 0xdd, 0xf6, 0xe1, 0xd7, 0x65, 0xa1, 0x93,
+    0xd9, 0xcb, 0xe1, 0x46, 0xce, 0xeb, 0x79, 0xac,
+    0x1c, 0xb4, 0x85, 0xed, 0x5f, 0x5b, 0x37, 0x91,
+    0x3a, 0x8c, 0xf5, 0x85, 0x7e, 0xff, 0x00, 0xa9,
+]);
+
+/// SPL Token-2022 program (TokenzQdBNbLqP5VEhdkAS6EPFLC1PEnBqCXEpPxuEb)
+pub const TOKEN_2022_PROGRAM_ID: Address = Address::new_from_array([
+    0x06, 0xdd, 0xf6, 0xe1, 0xee, 0x75, 0x8f, 0xde,
+    0x18, 0x42, 0x5d, 0xbc, 0xe4, 0x6c, 0xcd, 0xda,
+    0xb6, 0x1a, 0xfc, 0x4d, 0x83, 0xb9, 0x0c, 0x3c,
+    0x4e, 0x9d, 0xc7, 0x2d, 0x24, 0x7c, 0x5b, 0xfc,
+]);
+
+/// Byte range of `amount` in an SPL token account
+const AMOUNT_OFFSET: usize = 64;
+
+/// SPL Token `Transfer` instruction tag
+const TRANSFER: u8 = 3;
+
+/// Parse the `amount` field from SPL token account data
+pub fn parse_token_amount(data: &[u8]) -> Result<u64, ProgramError> {
+    let Some(amount) = data.get(AMOUNT_OFFSET..AMOUNT_OFFSET + 8) else {
+        return Err(ProgramError::InvalidAccountData);
+    };
+    Ok(u64::from_le_bytes(amount.try_into().unwrap()))
+}
+
+/// Fail with `IllegalOwner` unless the SPL Token program owns `account`
+pub fn require_token_account(account: &AccountView) -> Result<(), ProgramError> {
+    if !account.owned_by(&TOKEN_PROGRAM_ID) {
+        return Err(ProgramError::IllegalOwner);
+    }
+    Ok(())
+}
+
+/// Fail with `IncorrectProgramId` unless `program` is SPL Token or
+/// Token-2022
+pub fn require_token_program(program: &AccountView) -> Result<(), ProgramError> {
+    let address = program.address();
+    if *address != TOKEN_PROGRAM_ID && *address != TOKEN_2022_PROGRAM_ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(())
+}
+
+/// Read the token balance of an SPL token account
+pub fn token_amount(account: &AccountView) -> Result<u64, ProgramError> {
+    require_token_account(account)?;
+    let data = account.try_borrow()?;
+    parse_token_amount(&data)
+}
+
+/// Transfer `amount` tokens from `source`, signed by its owner `authority`
+pub fn transfer(
+    source: &AccountView,
+    destination: &AccountView,
+    authority: &AccountView,
+    amount: u64,
+    signers: &[Signer],
+) -> ProgramResult {
+    let mut data = [0u8; 9];
+    data[0] = TRANSFER;
+    data[1..9].copy_from_slice(&amount.to_le_bytes());
+
+    let accounts = [
+        InstructionAccount::writable(source.address()),
+        InstructionAccount::writable(destination.address()),
+        InstructionAccount::readonly_signer(authority.address()),
+    ];
+
+    let instruction = InstructionView {
+        program_id: &TOKEN_PROGRAM_ID,
+        accounts: &accounts,
+        data: &data,
+    };
+
+    invoke_signed(&instruction, &[source, destination, authority], signers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_token_amount() {
+        // mint (32) + owner (32) + amount (8) + the rest of a token account
+        let mut data = [0u8; 165];
+        data[..64].fill(0xff);
+        data[64..72].copy_from_slice(&1_234_567_890u64.to_le_bytes());
+        assert_eq!(parse_token_amount(&data), Ok(1_234_567_890));
+
+        assert_eq!(parse_token_amount(&data[..72]), Ok(1_234_567_890));
+        assert_eq!(parse_token_amount(&data[..71]), Err(ProgramError::InvalidAccountData));
+    }
+}
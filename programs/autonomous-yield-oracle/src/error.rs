@@ -21,6 +21,54 @@ pub enum OracleError {
     SlippageExceeded,
     /// Emergency mode is active
     EmergencyModeActive,
+    /// Oracle state does not match the expected snapshot
+    StateMismatch,
+    /// Not enough history (elapsed time or value) to compute a metric
+    InsufficientHistory,
+    /// Timestamp is ahead of on-chain time or before the last update
+    InvalidTimestamp,
+    /// Settings are locked
+    ConfigLocked,
+    /// Internal accounting invariant violated
+    InvariantViolation,
+    /// A two-phase rebalance is already underway
+    RebalanceInProgress,
+    /// Lifetime metrics were already seeded or decisions already recorded
+    HistoryAlreadySeeded,
+    /// Update arrived before the minimum update interval elapsed
+    UpdateTooFrequent,
+    /// Settings are out of range or conflict with each other
+    InvalidConfig,
+    /// Value is still managed by the oracle
+    PositionsStillOpen,
+    /// Priority fee above the configured maximum
+    GasPriceTooHigh,
+    /// Protocol is paused until a later time
+    ProtocolPaused,
+    /// A timelocked change was accepted before its delay elapsed
+    TimelockNotElapsed,
+    /// Tracked value would overflow
+    ValueOverflow,
+    /// Account uses an older state layout; run `Migrate` first
+    MigrationRequired,
+    /// Target allocation does not sum to 10000 bps
+    InvalidAllocation,
+    /// Instruction data is shorter than its layout
+    DataTooShort,
+    /// Fee treasury account doesn't match the stored fee destination
+    InvalidTreasury,
+    /// `max_decisions_per_day` decisions were already made in this window
+    DecisionLimitReached,
+    /// Observed APY is above `max_apy_bps`
+    ImplausibleApy,
+    /// Deposit would take the value managed past `max_value_managed`
+    ValueCapExceeded,
+    /// `MonitorYields` nonce is not above the last accepted one
+    StaleNonce,
+    /// More accounts were passed than the instruction takes
+    TooManyAccounts,
+    /// Booking PnL would overflow the cumulative total
+    PnlOverflow,
 }
 
 impl From<OracleError> for ProgramError {
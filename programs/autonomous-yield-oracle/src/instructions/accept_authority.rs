@@ -0,0 +1,63 @@
+//! Accept Authority instruction
+//!
+//! Second step of the authority handoff: the pending authority signs to
+//! take control of the oracle.
+
+use pinocchio::{AccountView, Address, ProgramResult};
+use solana_program_error::ProgramError;
+
+use crate::state::OracleState;
+use super::load_oracle;
+
+/// Accounts required for accepting authority
+pub struct AcceptAuthorityAccounts<'a> {
+    /// The oracle account
+    pub oracle: &'a AccountView,
+    /// The pending authority (must sign)
+    pub pending_authority: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for AcceptAuthorityAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [oracle, pending_authority, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !pending_authority.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self {
+            oracle,
+            pending_authority,
+        })
+    }
+}
+
+/// Accept Authority instruction
+pub struct AcceptAuthority<'a> {
+    pub accounts: AcceptAuthorityAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for AcceptAuthority<'a> {
+    type Error = ProgramError;
+
+    fn try_from((_data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = AcceptAuthorityAccounts::try_from(accounts)?;
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> AcceptAuthority<'a> {
+    pub fn process(&self, program_id: &Address) -> ProgramResult {
+        // Program-owned, initialized oracle only
+        load_oracle(self.accounts.oracle, program_id)?;
+
+        let mut oracle_data = self.accounts.oracle.try_borrow_mut()?;
+        let state = OracleState::from_bytes_mut(&mut oracle_data)?;
+
+        state.accept_authority(self.accounts.pending_authority.address().as_ref())
+    }
+}
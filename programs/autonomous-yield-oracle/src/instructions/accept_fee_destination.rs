@@ -0,0 +1,66 @@
+//! Accept Fee Destination instruction
+//!
+//! Second step of rotating the fee destination: makes the proposed account
+//! current. Rejected until the proposal's timelock has elapsed.
+
+use pinocchio::{
+    sysvars::{clock::Clock, Sysvar},
+    AccountView, Address, ProgramResult,
+};
+use solana_program_error::ProgramError;
+
+use crate::state::OracleState;
+use super::load_oracle;
+
+/// Accounts required for accepting a fee destination
+pub struct AcceptFeeDestinationAccounts<'a> {
+    /// The oracle account
+    pub oracle: &'a AccountView,
+    /// The authority (must sign)
+    pub authority: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for AcceptFeeDestinationAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [oracle, authority, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !authority.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self { oracle, authority })
+    }
+}
+
+/// Accept Fee Destination instruction
+pub struct AcceptFeeDestination<'a> {
+    pub accounts: AcceptFeeDestinationAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for AcceptFeeDestination<'a> {
+    type Error = ProgramError;
+
+    fn try_from((_data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = AcceptFeeDestinationAccounts::try_from(accounts)?;
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> AcceptFeeDestination<'a> {
+    pub fn process(&self, program_id: &Address) -> ProgramResult {
+        // Program-owned, initialized oracle only
+        load_oracle(self.accounts.oracle, program_id)?;
+
+        let mut oracle_data = self.accounts.oracle.try_borrow_mut()?;
+        let state = OracleState::from_bytes_mut(&mut oracle_data)?;
+
+        // Verify initialized, then authority
+        state.require_authority(self.accounts.authority.address().as_ref())?;
+
+        state.accept_fee_destination(Clock::get()?.unix_timestamp)
+    }
+}
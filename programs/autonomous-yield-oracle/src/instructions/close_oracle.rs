@@ -0,0 +1,138 @@
+//! Close Oracle instruction
+//!
+//! Shuts an oracle down and returns its rent to the destination. Only
+//! allowed once no value is managed, so funds are never stranded. The data
+//! is zeroed and the lamports drained, leaving the account to be garbage
+//! collected at the end of the transaction.
+
+use pinocchio::{AccountView, Address, ProgramResult};
+use solana_program_error::ProgramError;
+
+use crate::state::OracleState;
+use crate::error::OracleError;
+use super::load_oracle;
+
+/// Accounts required for closing the oracle
+pub struct CloseOracleAccounts<'a> {
+    /// The oracle account
+    pub oracle: &'a AccountView,
+    /// The authority (must sign)
+    pub authority: &'a AccountView,
+    /// Receives the oracle's lamports
+    pub destination: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for CloseOracleAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [oracle, authority, destination, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !authority.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        // Draining into itself would burn the rent
+        if oracle.address() == destination.address() {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        Ok(Self {
+            oracle,
+            authority,
+            destination,
+        })
+    }
+}
+
+/// Wipe the oracle's data once it holds no value
+///
+/// Zeroing clears the discriminator, so the account can't be read as an
+/// oracle again within the transaction.
+pub fn close_oracle_data(data: &mut [u8]) -> ProgramResult {
+    let state = OracleState::from_bytes(data)?;
+    if state.total_value_managed() > 0 {
+        return Err(OracleError::PositionsStillOpen.into());
+    }
+
+    data.fill(0);
+    Ok(())
+}
+
+/// Close Oracle instruction
+pub struct CloseOracle<'a> {
+    pub accounts: CloseOracleAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for CloseOracle<'a> {
+    type Error = ProgramError;
+
+    fn try_from((_data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = CloseOracleAccounts::try_from(accounts)?;
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> CloseOracle<'a> {
+    pub fn process(&self, program_id: &Address) -> ProgramResult {
+        let oracle = self.accounts.oracle;
+        let destination = self.accounts.destination;
+
+        // Program-owned, initialized oracle only
+        load_oracle(oracle, program_id)?;
+
+        {
+            let mut oracle_data = oracle.try_borrow_mut()?;
+            let state = OracleState::from_bytes(&oracle_data)?;
+
+            // Verify initialized, then authority
+            state.require_authority(self.accounts.authority.address().as_ref())?;
+
+            close_oracle_data(&mut oracle_data)?;
+        }
+
+        // Move every lamport directly; the oracle is program-owned
+        let new_destination = destination
+            .lamports()
+            .checked_add(oracle.lamports())
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        oracle.set_lamports(0);
+        destination.set_lamports(new_destination);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_close_oracle_data() {
+        let mut state = OracleState::test_state();
+        state.set_current_apy_bps(1500);
+        state.add_pnl(42).unwrap();
+        let mut buf = [0u8; OracleState::LEN];
+        buf.copy_from_slice(state.as_bytes());
+
+        close_oracle_data(&mut buf).unwrap();
+        assert!(buf.iter().all(|&b| b == 0));
+        assert!(OracleState::from_bytes(&buf).is_err());
+    }
+
+    #[test]
+    fn test_close_blocked_while_value_managed() {
+        let mut state = OracleState::test_state();
+        state.set_total_value_managed(1);
+        let mut buf = [0u8; OracleState::LEN];
+        buf.copy_from_slice(state.as_bytes());
+
+        assert_eq!(
+            close_oracle_data(&mut buf),
+            Err(OracleError::PositionsStillOpen.into())
+        );
+        assert_eq!(buf[0], OracleState::DISCRIMINATOR);
+    }
+}
@@ -0,0 +1,135 @@
+//! Compare Benchmark instruction
+//!
+//! Reports whether the realized APY beats a passive benchmark (e.g. the
+//! staking yield of just holding SOL) through return data. Read-only; no
+//! signer required.
+//!
+//! Return data layout: status (1, see `benchmark_status`) +
+//! realized_apy_bps (4, le; 0 without enough history) = 5 bytes
+
+use pinocchio::{
+    cpi::set_return_data,
+    sysvars::{clock::Clock, Sysvar},
+    AccountView, Address, ProgramResult,
+};
+use solana_program_error::ProgramError;
+
+use crate::state::OracleState;
+use super::load_oracle_any_version;
+
+/// Size of the benchmark report in return data
+pub const BENCHMARK_REPORT_LEN: usize = 5;
+
+/// Values of the report's status byte
+pub mod benchmark_status {
+    pub const UNDERPERFORMS: u8 = 0;
+    pub const OUTPERFORMS: u8 = 1;
+    pub const INSUFFICIENT_HISTORY: u8 = 2;
+}
+
+/// Accounts required for comparing against a benchmark
+pub struct CompareBenchmarkAccounts<'a> {
+    /// The oracle account
+    pub oracle: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for CompareBenchmarkAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [oracle, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self { oracle })
+    }
+}
+
+/// Instruction data for comparing against a benchmark
+/// Layout: benchmark_apy_bps (2) = 2 bytes
+pub struct CompareBenchmarkData {
+    /// APY (bps) of the passive alternative
+    pub benchmark_apy_bps: u16,
+}
+
+impl TryFrom<&[u8]> for CompareBenchmarkData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() < 2 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            benchmark_apy_bps: u16::from_le_bytes([data[0], data[1]]),
+        })
+    }
+}
+
+/// Encode the comparison against `benchmark_apy_bps` as of `now`
+pub fn benchmark_report(
+    state: &OracleState,
+    benchmark_apy_bps: u16,
+    now: i64,
+) -> [u8; BENCHMARK_REPORT_LEN] {
+    let mut report = [0u8; BENCHMARK_REPORT_LEN];
+    report[0] = match state.beats_benchmark(benchmark_apy_bps, now) {
+        Some(true) => benchmark_status::OUTPERFORMS,
+        Some(false) => benchmark_status::UNDERPERFORMS,
+        None => benchmark_status::INSUFFICIENT_HISTORY,
+    };
+    let realized = state.compute_realized_apy_bps(now).unwrap_or(0);
+    report[1..5].copy_from_slice(&realized.to_le_bytes());
+    report
+}
+
+/// Compare Benchmark instruction
+pub struct CompareBenchmark<'a> {
+    pub accounts: CompareBenchmarkAccounts<'a>,
+    pub data: CompareBenchmarkData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for CompareBenchmark<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = CompareBenchmarkAccounts::try_from(accounts)?;
+        let data = CompareBenchmarkData::try_from(data)?;
+        Ok(Self { accounts, data })
+    }
+}
+
+impl<'a> CompareBenchmark<'a> {
+    pub fn process(&self, program_id: &Address) -> ProgramResult {
+        // Program-owned, initialized oracle of any version
+        let state = load_oracle_any_version(self.accounts.oracle, program_id)?;
+
+        let now = Clock::get()?.unix_timestamp;
+        set_return_data(&benchmark_report(&state, self.data.benchmark_apy_bps, now));
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_benchmark_report() {
+        let state = &mut OracleState::test_state();
+        let half_year = OracleState::SECONDS_PER_YEAR / 2;
+
+        let report = benchmark_report(state, 700, half_year);
+        assert_eq!(report, [benchmark_status::INSUFFICIENT_HISTORY, 0, 0, 0, 0]);
+
+        state.set_total_value_managed(10_000_000_000);
+        state.add_pnl(1_000_000_000).unwrap();
+        let report = benchmark_report(state, 700, half_year);
+        assert_eq!(report[0], benchmark_status::OUTPERFORMS);
+        assert_eq!(i32::from_le_bytes(report[1..5].try_into().unwrap()), 2000);
+
+        let report = benchmark_report(state, 2500, half_year);
+        assert_eq!(report[0], benchmark_status::UNDERPERFORMS);
+    }
+}
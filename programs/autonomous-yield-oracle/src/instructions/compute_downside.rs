@@ -0,0 +1,69 @@
+//! Compute Downside instruction
+//!
+//! Stores the downside deviation of the booked PnL history: the
+//! Sortino-style spread of losses only, so gains don't count as risk.
+
+use pinocchio::{AccountView, Address, ProgramResult};
+use solana_program_error::ProgramError;
+
+use crate::state::OracleState;
+use crate::error::OracleError;
+use super::load_oracle;
+
+/// Accounts required for computing the downside deviation
+pub struct ComputeDownsideAccounts<'a> {
+    /// The oracle account
+    pub oracle: &'a AccountView,
+    /// The authority
+    pub authority: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for ComputeDownsideAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [oracle, authority, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !authority.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self { oracle, authority })
+    }
+}
+
+/// Compute Downside instruction
+pub struct ComputeDownside<'a> {
+    pub accounts: ComputeDownsideAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for ComputeDownside<'a> {
+    type Error = ProgramError;
+
+    fn try_from((_data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = ComputeDownsideAccounts::try_from(accounts)?;
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> ComputeDownside<'a> {
+    pub fn process(&self, program_id: &Address) -> ProgramResult {
+        // Program-owned, initialized oracle only
+        load_oracle(self.accounts.oracle, program_id)?;
+
+        let mut oracle_data = self.accounts.oracle.try_borrow_mut()?;
+        let state = OracleState::from_bytes_mut(&mut oracle_data)?;
+
+        // Verify initialized, then authority
+        state.require_authority(self.accounts.authority.address().as_ref())?;
+
+        let downside = state
+            .compute_downside_dev_lamports()
+            .ok_or(OracleError::InsufficientHistory)?;
+        state.set_downside_dev_lamports(downside);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,73 @@
+//! Compute Realized APY instruction
+//!
+//! Annualizes booked PnL against the value managed since the oracle was
+//! created, so the delivered APY can be compared to the advertised one.
+
+use pinocchio::{
+    sysvars::{clock::Clock, Sysvar},
+    AccountView, Address, ProgramResult,
+};
+use solana_program_error::ProgramError;
+
+use crate::state::OracleState;
+use crate::error::OracleError;
+use super::load_oracle;
+
+/// Accounts required for computing realized APY
+pub struct ComputeRealizedApyAccounts<'a> {
+    /// The oracle account
+    pub oracle: &'a AccountView,
+    /// The authority
+    pub authority: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for ComputeRealizedApyAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [oracle, authority, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !authority.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self { oracle, authority })
+    }
+}
+
+/// Compute Realized APY instruction
+pub struct ComputeRealizedApy<'a> {
+    pub accounts: ComputeRealizedApyAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for ComputeRealizedApy<'a> {
+    type Error = ProgramError;
+
+    fn try_from((_data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = ComputeRealizedApyAccounts::try_from(accounts)?;
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> ComputeRealizedApy<'a> {
+    pub fn process(&self, program_id: &Address) -> ProgramResult {
+        // Program-owned, initialized oracle only
+        load_oracle(self.accounts.oracle, program_id)?;
+
+        let mut oracle_data = self.accounts.oracle.try_borrow_mut()?;
+        let state = OracleState::from_bytes_mut(&mut oracle_data)?;
+
+        // Verify initialized, then authority
+        state.require_authority(self.accounts.authority.address().as_ref())?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let realized = state
+            .compute_realized_apy_bps(now)
+            .ok_or(OracleError::InsufficientHistory)?;
+        state.set_realized_apy_bps(realized);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,608 @@
+//! Configure instruction
+//!
+//! Changes any subset of the settings of a live oracle in one step. A
+//! bitmask (see `config_field`) selects the settings to write; the others
+//! are left as they are. The merged settings are validated as a whole, so a
+//! partial update can't leave them conflicting.
+
+use pinocchio::{AccountView, Address, ProgramResult};
+use solana_program_error::ProgramError;
+
+use crate::state::{OracleConfig, OracleState};
+use crate::error::OracleError;
+use super::{load_oracle, protocol};
+
+/// Bits of `ConfigureData::mask`, one per setting
+///
+/// The low 16 bits lead the instruction data; the high ones follow
+/// `drift_threshold_bps`.
+pub mod config_field {
+    pub const MAX_ACCEPTABLE_RISK: u32 = 1 << 0;
+    pub const MIN_UPDATE_INTERVAL_SECS: u32 = 1 << 1;
+    pub const LOT_SIZE_BPS: u32 = 1 << 2;
+    pub const MIN_ACTIONABLE_ADJUSTED_APY_BPS: u32 = 1 << 3;
+    pub const MAX_DRAWDOWN_LAMPORTS: u32 = 1 << 4;
+    pub const PHASE_ONE_FRACTION_BPS: u32 = 1 << 5;
+    pub const APY_PRECISION: u32 = 1 << 6;
+    pub const STALE_OVERRIDE_FLOOR_BPS: u32 = 1 << 7;
+    pub const RISK_CURVES: u32 = 1 << 8;
+    pub const STALE_AFTER_SECS: u32 = 1 << 9;
+    pub const MAX_DECISIONS_PER_DAY: u32 = 1 << 10;
+    pub const MAX_APY_BPS: u32 = 1 << 11;
+    pub const SWITCH_MARGIN_BPS: u32 = 1 << 12;
+    pub const EMA_ALPHA_BPS: u32 = 1 << 13;
+    pub const EMERGENCY_DELAY_SECS: u32 = 1 << 14;
+    pub const DRIFT_THRESHOLD_BPS: u32 = 1 << 15;
+    pub const PROTOCOL_FEE_BPS: u32 = 1 << 16;
+    pub const MIN_LIQUIDITY: u32 = 1 << 17;
+    pub const PROTOCOL_ALLOWLIST: u32 = 1 << 18;
+
+    /// Every defined bit
+    pub const ALL: u32 = (1 << 19) - 1;
+}
+
+/// Accounts required for configuring the oracle
+pub struct ConfigureAccounts<'a> {
+    /// The oracle account
+    pub oracle: &'a AccountView,
+    /// The authority (must sign)
+    pub authority: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for ConfigureAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [oracle, authority, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !authority.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self { oracle, authority })
+    }
+}
+
+/// Instruction data for configuring the oracle
+/// Layout: mask (2) + config (`OracleConfig::LEN`) + stale_after_secs (4) +
+/// max_decisions_per_day (2) + max_apy_bps (2) + switch_margin_bps (2) +
+/// ema_alpha_bps (2) + emergency_delay_secs (4) + drift_threshold_bps (2) +
+/// mask_high (2) + protocol_fee_bps (5 x 2) + min_liquidity (8) +
+/// protocol_allowlist (1) = 66 bytes;
+/// values of unselected settings are ignored, and trailing ones may be left
+/// out
+pub struct ConfigureData {
+    /// Settings to write (see `config_field`)
+    pub mask: u32,
+    /// New values of the `OracleConfig` settings
+    pub config: OracleConfig,
+    /// New staleness window in seconds (0 = never stale)
+    pub stale_after_secs: u32,
+    /// New cap on decisions per day (0 = no cap)
+    pub max_decisions_per_day: u16,
+    /// New highest plausible APY (0 = `OracleState::MAX_APY_BPS`)
+    pub max_apy_bps: u16,
+    /// New margin a protocol must beat the best one by to replace it
+    pub switch_margin_bps: u16,
+    /// New weight of a new APY in the EMA (0 = `OracleState::EMA_ALPHA_BPS`)
+    pub ema_alpha_bps: u16,
+    /// New delay between arming and executing `EmergencyWithdraw`
+    /// (0 = immediate)
+    pub emergency_delay_secs: u32,
+    /// New drift from the rebalance target that flags a rebalance
+    /// (0 = never flag)
+    pub drift_threshold_bps: u16,
+    /// New fee drag per protocol, taken off observed APYs (bps)
+    pub protocol_fee_bps: [u16; protocol::COUNT],
+    /// New liquidity (lamports) a monitored protocol must report to be
+    /// selected (0 = no minimum)
+    pub min_liquidity: u64,
+    /// New protocols the oracle may act on, bit i for protocol i (0 = all)
+    pub protocol_allowlist: u8,
+}
+
+impl TryFrom<&[u8]> for ConfigureData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() < 2 + OracleConfig::LEN + 4 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let stale_at = 2 + OracleConfig::LEN;
+        let max_decisions_at = stale_at + 4;
+        let max_apy_at = max_decisions_at + 2;
+        let switch_margin_at = max_apy_at + 2;
+        let ema_alpha_at = switch_margin_at + 2;
+        let emergency_delay_at = ema_alpha_at + 2;
+        let drift_threshold_at = emergency_delay_at + 4;
+        let mask_high_at = drift_threshold_at + 2;
+        let protocol_fee_at = mask_high_at + 2;
+        let min_liquidity_at = protocol_fee_at + 2 * protocol::COUNT;
+        let protocol_allowlist_at = min_liquidity_at + 8;
+
+        // Clients that predate the high bits leave them out
+        let mask_high = match data.get(mask_high_at..mask_high_at + 2) {
+            Some(value) => u16::from_le_bytes([value[0], value[1]]),
+            None => 0,
+        };
+        let mask = u16::from_le_bytes([data[0], data[1]]) as u32 | (mask_high as u32) << 16;
+        if mask & !config_field::ALL != 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        // Trailing settings are only required when selected
+        let optional_u8 = |offset: usize, field: u32| match data.get(offset) {
+            Some(&value) => Ok(value),
+            None if mask & field != 0 => Err(ProgramError::InvalidInstructionData),
+            None => Ok(0),
+        };
+        let optional_u16 = |offset: usize, field: u32| match data.get(offset..offset + 2) {
+            Some(value) => Ok(u16::from_le_bytes([value[0], value[1]])),
+            None if mask & field != 0 => Err(ProgramError::InvalidInstructionData),
+            None => Ok(0),
+        };
+        let optional_u32 = |offset: usize, field: u32| match data.get(offset..offset + 4) {
+            Some(value) => Ok(u32::from_le_bytes(value.try_into().unwrap())),
+            None if mask & field != 0 => Err(ProgramError::InvalidInstructionData),
+            None => Ok(0),
+        };
+        let optional_u64 = |offset: usize, field: u32| match data.get(offset..offset + 8) {
+            Some(value) => Ok(u64::from_le_bytes(value.try_into().unwrap())),
+            None if mask & field != 0 => Err(ProgramError::InvalidInstructionData),
+            None => Ok(0),
+        };
+        let mut protocol_fee_bps = [0u16; protocol::COUNT];
+        for (i, fee) in protocol_fee_bps.iter_mut().enumerate() {
+            *fee = optional_u16(protocol_fee_at + 2 * i, config_field::PROTOCOL_FEE_BPS)?;
+        }
+
+        Ok(Self {
+            mask,
+            config: OracleConfig::try_from(&data[2..stale_at])?,
+            stale_after_secs: u32::from_le_bytes(data[stale_at..stale_at + 4].try_into().unwrap()),
+            max_decisions_per_day: optional_u16(
+                max_decisions_at,
+                config_field::MAX_DECISIONS_PER_DAY,
+            )?,
+            max_apy_bps: optional_u16(max_apy_at, config_field::MAX_APY_BPS)?,
+            switch_margin_bps: optional_u16(switch_margin_at, config_field::SWITCH_MARGIN_BPS)?,
+            ema_alpha_bps: optional_u16(ema_alpha_at, config_field::EMA_ALPHA_BPS)?,
+            emergency_delay_secs: optional_u32(
+                emergency_delay_at,
+                config_field::EMERGENCY_DELAY_SECS,
+            )?,
+            drift_threshold_bps: optional_u16(
+                drift_threshold_at,
+                config_field::DRIFT_THRESHOLD_BPS,
+            )?,
+            protocol_fee_bps,
+            min_liquidity: optional_u64(min_liquidity_at, config_field::MIN_LIQUIDITY)?,
+            protocol_allowlist: optional_u8(
+                protocol_allowlist_at,
+                config_field::PROTOCOL_ALLOWLIST,
+            )?,
+        })
+    }
+}
+
+impl ConfigureData {
+    fn selects(&self, field: u32) -> bool {
+        self.mask & field != 0
+    }
+
+    fn pick<T>(&self, field: u32, new: T, current: T) -> T {
+        if self.selects(field) {
+            new
+        } else {
+            current
+        }
+    }
+
+    /// The oracle's settings with the selected ones replaced
+    pub fn merge(&self, current: OracleConfig) -> OracleConfig {
+        let new = &self.config;
+        OracleConfig {
+            max_acceptable_risk: self.pick(
+                config_field::MAX_ACCEPTABLE_RISK,
+                new.max_acceptable_risk,
+                current.max_acceptable_risk,
+            ),
+            min_update_interval_secs: self.pick(
+                config_field::MIN_UPDATE_INTERVAL_SECS,
+                new.min_update_interval_secs,
+                current.min_update_interval_secs,
+            ),
+            lot_size_bps: self.pick(config_field::LOT_SIZE_BPS, new.lot_size_bps, current.lot_size_bps),
+            min_actionable_adjusted_apy_bps: self.pick(
+                config_field::MIN_ACTIONABLE_ADJUSTED_APY_BPS,
+                new.min_actionable_adjusted_apy_bps,
+                current.min_actionable_adjusted_apy_bps,
+            ),
+            max_drawdown_lamports: self.pick(
+                config_field::MAX_DRAWDOWN_LAMPORTS,
+                new.max_drawdown_lamports,
+                current.max_drawdown_lamports,
+            ),
+            phase_one_fraction_bps: self.pick(
+                config_field::PHASE_ONE_FRACTION_BPS,
+                new.phase_one_fraction_bps,
+                current.phase_one_fraction_bps,
+            ),
+            apy_precision: self.pick(
+                config_field::APY_PRECISION,
+                new.apy_precision,
+                current.apy_precision,
+            ),
+            stale_override_floor_bps: self.pick(
+                config_field::STALE_OVERRIDE_FLOOR_BPS,
+                new.stale_override_floor_bps,
+                current.stale_override_floor_bps,
+            ),
+            risk_curves: self.pick(config_field::RISK_CURVES, new.risk_curves, current.risk_curves),
+        }
+    }
+
+    /// Validate and write the selected settings
+    ///
+    /// Nothing is written unless every check passes.
+    pub fn apply(&self, state: &mut OracleState) -> ProgramResult {
+        let config = self.merge(OracleConfig::from_state(state));
+        config.validate()?;
+
+        // A zero interval can only be had from initialization
+        if self.selects(config_field::MIN_UPDATE_INTERVAL_SECS)
+            && config.min_update_interval_secs == 0
+        {
+            return Err(OracleError::InvalidConfig.into());
+        }
+        if self.selects(config_field::EMA_ALPHA_BPS) && self.ema_alpha_bps > 10000 {
+            return Err(OracleError::InvalidConfig.into());
+        }
+        if self.selects(config_field::DRIFT_THRESHOLD_BPS) && self.drift_threshold_bps > 10000 {
+            return Err(OracleError::InvalidConfig.into());
+        }
+        if self.selects(config_field::PROTOCOL_FEE_BPS)
+            && self.protocol_fee_bps.iter().any(|&fee| fee > 10000)
+        {
+            return Err(OracleError::InvalidConfig.into());
+        }
+
+        config.apply(state);
+        if self.selects(config_field::STALE_AFTER_SECS) {
+            state.set_stale_after_secs(self.stale_after_secs);
+        }
+        if self.selects(config_field::MAX_DECISIONS_PER_DAY) {
+            state.set_max_decisions_per_day(self.max_decisions_per_day);
+        }
+        if self.selects(config_field::MAX_APY_BPS) {
+            state.set_max_apy_bps(self.max_apy_bps);
+        }
+        if self.selects(config_field::SWITCH_MARGIN_BPS) {
+            state.set_switch_margin_bps(self.switch_margin_bps);
+        }
+        if self.selects(config_field::EMA_ALPHA_BPS) {
+            state.set_ema_alpha_bps(self.ema_alpha_bps);
+        }
+        if self.selects(config_field::EMERGENCY_DELAY_SECS) {
+            state.set_emergency_delay_secs(self.emergency_delay_secs);
+        }
+        if self.selects(config_field::DRIFT_THRESHOLD_BPS) {
+            state.set_drift_threshold_bps(self.drift_threshold_bps);
+        }
+        if self.selects(config_field::PROTOCOL_FEE_BPS) {
+            state.set_protocol_fee_bps(self.protocol_fee_bps);
+        }
+        if self.selects(config_field::MIN_LIQUIDITY) {
+            state.set_min_liquidity(self.min_liquidity);
+        }
+        if self.selects(config_field::PROTOCOL_ALLOWLIST) {
+            state.set_protocol_allowlist(self.protocol_allowlist);
+        }
+
+        Ok(())
+    }
+}
+
+/// Configure instruction
+pub struct Configure<'a> {
+    pub accounts: ConfigureAccounts<'a>,
+    pub data: ConfigureData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for Configure<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = ConfigureAccounts::try_from(accounts)?;
+        let data = ConfigureData::try_from(data)?;
+        Ok(Self { accounts, data })
+    }
+}
+
+impl<'a> Configure<'a> {
+    pub fn process(&self, program_id: &Address) -> ProgramResult {
+        // Program-owned, initialized oracle only
+        load_oracle(self.accounts.oracle, program_id)?;
+
+        let mut oracle_data = self.accounts.oracle.try_borrow_mut()?;
+        let state = OracleState::from_bytes_mut(&mut oracle_data)?;
+
+        // Verify initialized, then authority
+        state.require_authority(self.accounts.authority.address().as_ref())?;
+
+        state.require_config_unlocked()?;
+        self.data.apply(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::RiskCurve;
+    use crate::state::ApyPrecision;
+
+    fn configure(mask: u32, config: OracleConfig, stale_after_secs: u32) -> ConfigureData {
+        ConfigureData {
+            mask,
+            config,
+            stale_after_secs,
+            max_decisions_per_day: 48,
+            max_apy_bps: 30_000,
+            switch_margin_bps: 15,
+            ema_alpha_bps: 3000,
+            emergency_delay_secs: 86_400,
+            drift_threshold_bps: 500,
+            protocol_fee_bps: [30, 0, 10, 5, 5],
+            min_liquidity: 1_000_000_000,
+            protocol_allowlist: 0b11000,
+        }
+    }
+
+    fn values() -> OracleConfig {
+        OracleConfig {
+            max_acceptable_risk: 40,
+            min_update_interval_secs: 120,
+            lot_size_bps: 250,
+            min_actionable_adjusted_apy_bps: 100,
+            max_drawdown_lamports: 5_000,
+            phase_one_fraction_bps: 2500,
+            apy_precision: ApyPrecision::CentiBps,
+            stale_override_floor_bps: 200,
+            risk_curves: [RiskCurve::Quadratic; 3],
+        }
+    }
+
+    #[test]
+    fn test_configure_only_risk_threshold() {
+        let state = &mut OracleState::test_state();
+        state.set_max_acceptable_risk(100);
+        state.set_min_update_interval_secs(30);
+        state.set_stale_after_secs(OracleState::STALE_AFTER_SECS as u32);
+
+        configure(config_field::MAX_ACCEPTABLE_RISK, values(), 900).apply(state).unwrap();
+
+        assert_eq!(state.max_acceptable_risk(), 40);
+        assert_eq!(state.min_update_interval_secs(), 30);
+        assert_eq!(state.lot_size_bps(), 0);
+        assert_eq!(state.apy_precision(), ApyPrecision::Bps);
+        assert_eq!(state.stale_after_secs(), OracleState::STALE_AFTER_SECS);
+    }
+
+    #[test]
+    fn test_configure_interval_and_stale_window() {
+        let state = &mut OracleState::test_state();
+
+        let mask = config_field::MIN_UPDATE_INTERVAL_SECS | config_field::STALE_AFTER_SECS;
+        configure(mask, values(), 900).apply(state).unwrap();
+        assert_eq!(state.min_update_interval_secs(), 120);
+        assert_eq!(state.stale_after_secs(), 900);
+        assert_eq!(state.max_acceptable_risk(), 0);
+
+        // No window at all: never stale
+        configure(config_field::STALE_AFTER_SECS, values(), 0).apply(state).unwrap();
+        assert_eq!(state.stale_after_secs(), 0);
+    }
+
+    #[test]
+    fn test_configure_rejects_bad_values() {
+        let state = &mut OracleState::test_state();
+        let before = *state;
+
+        let risky = OracleConfig { max_acceptable_risk: 101, ..values() };
+        assert_eq!(
+            configure(config_field::ALL, risky, 900).apply(state),
+            Err(OracleError::InvalidRiskScore.into())
+        );
+
+        let unthrottled = OracleConfig { min_update_interval_secs: 0, ..values() };
+        assert_eq!(
+            configure(config_field::ALL, unthrottled, 900).apply(state),
+            Err(OracleError::InvalidConfig.into())
+        );
+
+        // A bad value that isn't selected doesn't matter
+        configure(config_field::LOT_SIZE_BPS, risky, 0).apply(state).unwrap();
+        assert_eq!(state.lot_size_bps(), 250);
+
+        assert_eq!(state.max_acceptable_risk(), before.max_acceptable_risk());
+        assert_eq!(state.min_update_interval_secs(), before.min_update_interval_secs());
+    }
+
+    #[test]
+    fn test_configure_max_decisions_per_day() {
+        let state = &mut OracleState::test_state();
+
+        configure(config_field::LOT_SIZE_BPS, values(), 900).apply(state).unwrap();
+        assert_eq!(state.max_decisions_per_day(), 0);
+
+        configure(config_field::MAX_DECISIONS_PER_DAY, values(), 900).apply(state).unwrap();
+        assert_eq!(state.max_decisions_per_day(), 48);
+
+        let mut data = [0u8; 2 + OracleConfig::LEN + 6];
+        data[0..2].copy_from_slice(&(config_field::MAX_DECISIONS_PER_DAY as u16).to_le_bytes());
+        data[2 + OracleConfig::LEN + 4..].copy_from_slice(&100u16.to_le_bytes());
+        assert_eq!(ConfigureData::try_from(&data[..]).unwrap().max_decisions_per_day, 100);
+        assert_eq!(
+            ConfigureData::try_from(&data[..data.len() - 2]).err(),
+            Some(ProgramError::InvalidInstructionData)
+        );
+
+        // The cap may be left out when it isn't selected
+        data[0..2].copy_from_slice(&(config_field::LOT_SIZE_BPS as u16).to_le_bytes());
+        assert!(ConfigureData::try_from(&data[..data.len() - 2]).is_ok());
+    }
+
+    #[test]
+    fn test_configure_max_apy_bps() {
+        let state = &mut OracleState::test_state();
+        assert_eq!(state.max_apy_bps(), OracleState::MAX_APY_BPS);
+
+        configure(config_field::MAX_APY_BPS, values(), 900).apply(state).unwrap();
+        assert_eq!(state.max_apy_bps(), 30_000);
+        assert_eq!(state.max_decisions_per_day(), 0);
+
+        let mut data = [0u8; 2 + OracleConfig::LEN + 8];
+        data[0..2].copy_from_slice(&(config_field::MAX_APY_BPS as u16).to_le_bytes());
+        data[2 + OracleConfig::LEN + 6..].copy_from_slice(&40_000u16.to_le_bytes());
+        assert_eq!(ConfigureData::try_from(&data[..]).unwrap().max_apy_bps, 40_000);
+        assert_eq!(
+            ConfigureData::try_from(&data[..data.len() - 2]).err(),
+            Some(ProgramError::InvalidInstructionData)
+        );
+    }
+
+    #[test]
+    fn test_configure_switch_margin() {
+        let state = &mut OracleState::test_state();
+
+        configure(config_field::SWITCH_MARGIN_BPS, values(), 900).apply(state).unwrap();
+        assert_eq!(state.switch_margin_bps(), 15);
+        assert_eq!(state.max_apy_bps(), OracleState::MAX_APY_BPS);
+    }
+
+    #[test]
+    fn test_configure_ema_alpha() {
+        let state = &mut OracleState::test_state();
+
+        configure(config_field::EMA_ALPHA_BPS, values(), 900).apply(state).unwrap();
+        assert_eq!(state.ema_alpha_bps(), 3000);
+
+        let mut data = configure(config_field::EMA_ALPHA_BPS, values(), 900);
+        data.ema_alpha_bps = 10001;
+        assert_eq!(data.apply(state), Err(OracleError::InvalidConfig.into()));
+        assert_eq!(state.ema_alpha_bps(), 3000);
+    }
+
+    #[test]
+    fn test_configure_emergency_delay() {
+        let state = &mut OracleState::test_state();
+
+        configure(config_field::EMERGENCY_DELAY_SECS, values(), 900).apply(state).unwrap();
+        assert_eq!(state.emergency_delay_secs(), 86_400);
+        assert_eq!(state.ema_alpha_bps(), OracleState::EMA_ALPHA_BPS);
+
+        let mut data = [0u8; 2 + OracleConfig::LEN + 16];
+        data[0..2].copy_from_slice(&(config_field::EMERGENCY_DELAY_SECS as u16).to_le_bytes());
+        data[2 + OracleConfig::LEN + 12..].copy_from_slice(&3_600u32.to_le_bytes());
+        assert_eq!(ConfigureData::try_from(&data[..]).unwrap().emergency_delay_secs, 3_600);
+        assert_eq!(
+            ConfigureData::try_from(&data[..data.len() - 1]).err(),
+            Some(ProgramError::InvalidInstructionData)
+        );
+    }
+
+    #[test]
+    fn test_configure_drift_threshold() {
+        let state = &mut OracleState::test_state();
+
+        configure(config_field::DRIFT_THRESHOLD_BPS, values(), 900).apply(state).unwrap();
+        assert_eq!(state.drift_threshold_bps(), 500);
+        assert_eq!(state.emergency_delay_secs(), 0);
+
+        let mut data = configure(config_field::DRIFT_THRESHOLD_BPS, values(), 900);
+        data.drift_threshold_bps = 10001;
+        assert_eq!(data.apply(state), Err(OracleError::InvalidConfig.into()));
+        assert_eq!(state.drift_threshold_bps(), 500);
+    }
+
+    #[test]
+    fn test_configure_protocol_fees() {
+        let state = &mut OracleState::test_state();
+
+        configure(config_field::PROTOCOL_FEE_BPS, values(), 900).apply(state).unwrap();
+        assert_eq!(state.protocol_fee_bps(), [30, 0, 10, 5, 5]);
+        assert_eq!(state.drift_threshold_bps(), 0);
+
+        let mut data = configure(config_field::PROTOCOL_FEE_BPS, values(), 900);
+        data.protocol_fee_bps[4] = 10001;
+        assert_eq!(data.apply(state), Err(OracleError::InvalidConfig.into()));
+        assert_eq!(state.protocol_fee_bps(), [30, 0, 10, 5, 5]);
+
+        // Selected through the high half of the mask
+        let mut data = [0u8; 2 + OracleConfig::LEN + 30];
+        let mask_high_at = 2 + OracleConfig::LEN + 18;
+        data[mask_high_at..mask_high_at + 2].copy_from_slice(&1u16.to_le_bytes());
+        data[mask_high_at + 2..mask_high_at + 4].copy_from_slice(&25u16.to_le_bytes());
+        let parsed = ConfigureData::try_from(&data[..]).unwrap();
+        assert_eq!(parsed.mask, config_field::PROTOCOL_FEE_BPS);
+        assert_eq!(parsed.protocol_fee_bps, [25, 0, 0, 0, 0]);
+        assert_eq!(
+            ConfigureData::try_from(&data[..data.len() - 2]).err(),
+            Some(ProgramError::InvalidInstructionData)
+        );
+
+        // High bits with no setting behind them
+        data[mask_high_at..mask_high_at + 2].copy_from_slice(&4u16.to_le_bytes());
+        assert_eq!(
+            ConfigureData::try_from(&data[..]).err(),
+            Some(ProgramError::InvalidInstructionData)
+        );
+    }
+
+    #[test]
+    fn test_configure_min_liquidity() {
+        let state = &mut OracleState::test_state();
+
+        configure(config_field::MIN_LIQUIDITY, values(), 900).apply(state).unwrap();
+        assert_eq!(state.min_liquidity(), 1_000_000_000);
+        assert_eq!(state.protocol_fee_bps(), [0; protocol::COUNT]);
+
+        configure(config_field::MIN_LIQUIDITY | config_field::PROTOCOL_FEE_BPS, values(), 900)
+            .apply(state)
+            .unwrap();
+        assert_eq!(state.protocol_fee_bps(), [30, 0, 10, 5, 5]);
+    }
+
+    #[test]
+    fn test_configure_protocol_allowlist() {
+        let state = &mut OracleState::test_state();
+        assert_eq!(state.protocol_allowlist(), u8::MAX);
+
+        configure(config_field::PROTOCOL_ALLOWLIST, values(), 900).apply(state).unwrap();
+        assert_eq!(state.protocol_allowlist(), 0b11000);
+        assert_eq!(state.require_protocol_allowed(protocol::JITO), Ok(()));
+        assert_eq!(
+            state.require_protocol_allowed(protocol::RAYDIUM_CPMM),
+            Err(OracleError::InvalidProtocol.into())
+        );
+    }
+
+    #[test]
+    fn test_all_mask_bits_need_their_values() {
+        let mut data = [0u8; 2 + OracleConfig::LEN + 4 + 2 + 2 + 2 + 2 + 4 + 2 + 2 + 10 + 8 + 1];
+        let mask_high_at = 2 + OracleConfig::LEN + 18;
+        data[0..2].copy_from_slice(&(config_field::ALL as u16).to_le_bytes());
+        data[mask_high_at..mask_high_at + 2]
+            .copy_from_slice(&((config_field::ALL >> 16) as u16).to_le_bytes());
+        assert!(ConfigureData::try_from(&data[..]).is_ok());
+
+        // The last setting selected but left out
+        assert_eq!(
+            ConfigureData::try_from(&data[..data.len() - 1]).err(),
+            Some(ProgramError::InvalidInstructionData)
+        );
+    }
+}
@@ -0,0 +1,105 @@
+//! Deposit instruction
+//!
+//! Moves lamports from a depositor into the oracle and adds them to
+//! `total_value_managed`, so the agent no longer computes new totals
+//! off-chain. Anyone can deposit, but not while the oracle is paused, nor
+//! past `max_value_managed`. A depositor without the funds fails in the
+//! system transfer.
+
+use pinocchio::{AccountView, Address, ProgramResult};
+use pinocchio_system::instructions::Transfer;
+use solana_program_error::ProgramError;
+
+use crate::state::OracleState;
+use super::load_oracle;
+
+/// Accounts required for depositing
+pub struct DepositAccounts<'a> {
+    /// The oracle account
+    pub oracle: &'a AccountView,
+    /// Pays the deposit (must sign)
+    pub depositor: &'a AccountView,
+    /// System program
+    pub system_program: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for DepositAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [oracle, depositor, system_program, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !depositor.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self {
+            oracle,
+            depositor,
+            system_program,
+        })
+    }
+}
+
+/// Instruction data for depositing
+/// Layout: amount (8) = 8 bytes
+pub struct DepositData {
+    /// Lamports to deposit
+    pub amount: u64,
+}
+
+impl TryFrom<&[u8]> for DepositData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() < 8 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            amount: u64::from_le_bytes(data[0..8].try_into().unwrap()),
+        })
+    }
+}
+
+/// Deposit instruction
+pub struct Deposit<'a> {
+    pub accounts: DepositAccounts<'a>,
+    pub data: DepositData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for Deposit<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = DepositAccounts::try_from(accounts)?;
+        let data = DepositData::try_from(data)?;
+        Ok(Self { accounts, data })
+    }
+}
+
+impl<'a> Deposit<'a> {
+    pub fn process(&self, program_id: &Address) -> ProgramResult {
+        // Program-owned, initialized oracle only
+        load_oracle(self.accounts.oracle, program_id)?;
+
+        {
+            let mut oracle_data = self.accounts.oracle.try_borrow_mut()?;
+            let state = OracleState::from_bytes_mut(&mut oracle_data)?;
+
+            state.require_not_paused()?;
+
+            // Track first so an overflow fails before any lamports move
+            state.deposit_value(self.data.amount)?;
+        }
+
+        Transfer {
+            from: self.accounts.depositor,
+            to: self.accounts.oracle,
+            lamports: self.data.amount,
+        }
+        .invoke()
+    }
+}
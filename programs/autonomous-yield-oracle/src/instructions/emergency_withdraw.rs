@@ -1,12 +1,34 @@
 //! Emergency Withdraw instruction
 //!
 //! Safety feature for risk management - withdraws all funds to authority.
+//!
+//! Moves every lamport above the oracle's rent-exempt minimum to the
+//! destination and leaves the oracle paused. The oracle is never drained
+//! below that minimum, so its state outlives the emergency; with nothing
+//! above it, no lamports move and the call still succeeds.
+//!
+//! Open token positions are closed out by passing the SPL Token program
+//! after `destination`, then one `(token_account, pool_account)` pair per
+//! position: the oracle's token account and the account its whole balance
+//! is sent to. The optional notifier comes last. Without the token program
+//! only lamports move, which is refused while any value is deployed.
+//!
+//! With an `emergency_delay_secs` configured, the first call only arms the
+//! withdrawal and pauses the oracle; funds move on a call once the delay
+//! has passed, a speed bump should the authority key leak.
 
-use pinocchio::{AccountView, ProgramResult};
+use pinocchio::{
+    cpi::Signer,
+    sysvars::{clock::Clock, rent::Rent, Sysvar},
+    AccountView, Address, ProgramResult,
+};
 use solana_program_error::ProgramError;
 
-use crate::state::OracleState;
+use crate::cpi::token::{self, token_amount, TOKEN_PROGRAM_ID};
 use crate::error::OracleError;
+use crate::state::OracleState;
+use crate::notifier::{self, EventCode};
+use super::{load_oracle, protocol};
 
 /// Accounts required for emergency withdrawal
 pub struct EmergencyWithdrawAccounts<'a> {
@@ -16,14 +38,17 @@ pub struct EmergencyWithdrawAccounts<'a> {
     pub authority: &'a AccountView,
     /// Destination for withdrawn funds
     pub destination: &'a AccountView,
-    // Additional token accounts as needed
+    /// `(token_account, pool_account)` pairs of the positions to close out
+    pub positions: &'a [AccountView],
+    /// Optional notifier program to alert on the emergency
+    pub notifier: Option<&'a AccountView>,
 }
 
 impl<'a> TryFrom<&'a [AccountView]> for EmergencyWithdrawAccounts<'a> {
     type Error = ProgramError;
 
     fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
-        let [oracle, authority, destination, ..] = accounts else {
+        let [oracle, authority, destination, rest @ ..] = accounts else {
             return Err(ProgramError::NotEnoughAccountKeys);
         };
 
@@ -32,14 +57,48 @@ impl<'a> TryFrom<&'a [AccountView]> for EmergencyWithdrawAccounts<'a> {
             return Err(ProgramError::MissingRequiredSignature);
         }
 
+        let has_positions = rest
+            .first()
+            .is_some_and(|program| *program.address() == TOKEN_PROGRAM_ID);
+        let (positions, notifier) = split_positions(rest, has_positions)?;
+
         Ok(Self {
             oracle,
             authority,
             destination,
+            positions,
+            notifier,
         })
     }
 }
 
+/// Split the accounts after `destination` into position pairs and notifier
+///
+/// With `has_positions`, `rest` starts with the token program and the pairs
+/// follow it; a last unpaired account is the notifier. Otherwise the
+/// notifier is the only account allowed, so anything more fails with
+/// `TooManyAccounts` rather than being ignored.
+pub fn split_positions<T>(
+    rest: &[T],
+    has_positions: bool,
+) -> Result<(&[T], Option<&T>), ProgramError> {
+    if !has_positions {
+        if rest.len() > 1 {
+            return Err(OracleError::TooManyAccounts.into());
+        }
+        return Ok((&[], rest.first()));
+    }
+
+    let positions = &rest[1..];
+    let paired = positions.len() / 2 * 2;
+    Ok((&positions[..paired], positions.get(paired)))
+}
+
+/// Lamports that can leave the oracle without dropping below rent exemption
+pub fn withdrawable_lamports(balance: u64, rent_exempt_minimum: u64) -> u64 {
+    balance.saturating_sub(rent_exempt_minimum)
+}
+
 /// Emergency Withdraw instruction
 pub struct EmergencyWithdraw<'a> {
     pub accounts: EmergencyWithdrawAccounts<'a>,
@@ -55,28 +114,148 @@ impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for EmergencyWithdraw<'a> {
 }
 
 impl<'a> EmergencyWithdraw<'a> {
-    pub fn process(&self) -> ProgramResult {
-        let oracle_data = self.accounts.oracle.try_borrow()?;
-        let state = OracleState::from_bytes(&oracle_data)?;
+    pub fn process(&self, program_id: &Address) -> ProgramResult {
+        // Program-owned, initialized oracle only
+        load_oracle(self.accounts.oracle, program_id)?;
+
+        let now = Clock::get()?.unix_timestamp;
+
+        let (ready, total_value_managed, deployed, snapshot) = {
+            let mut oracle_data = self.accounts.oracle.try_borrow_mut()?;
+            let state = OracleState::from_bytes_mut(&mut oracle_data)?;
+
+            // Verify initialized, then authority - CRITICAL security check
+            state.require_authority(self.accounts.authority.address().as_ref())?;
+
+            let ready = state.arm_emergency(now)?;
+            let deployed = state.allocation_lamports()[..protocol::COUNT]
+                .iter()
+                .try_fold(0u64, |sum, &amount| sum.checked_add(amount))
+                .ok_or(OracleError::ValueOverflow)?;
+            (ready, state.total_value_managed(), deployed, *state)
+        };
+
+        // Withdrawing lamports alone would leave deployed value behind
+        if ready && deployed > 0 && self.accounts.positions.is_empty() {
+            return Err(OracleError::PositionsStillOpen.into());
+        }
+
+        // Alert operators before anything else can go wrong, including when
+        // only arming
+        notifier::notify(
+            self.accounts.notifier,
+            self.accounts.oracle,
+            EventCode::EmergencyTrip,
+            total_value_managed,
+        )?;
+
+        // Armed and paused; funds move once the delay has passed
+        if !ready {
+            return Ok(());
+        }
 
-        // Verify initialized
-        if state.is_initialized == 0 {
-            return Err(OracleError::NotInitialized.into());
+        self.close_positions(program_id, &snapshot)?;
+
+        // Move the excess lamports directly; the oracle is program-owned
+        let oracle = self.accounts.oracle;
+        let destination = self.accounts.destination;
+        // Never below what the current layout needs, so a later Migrate
+        // can't leave it short either
+        let rent_exempt =
+            Rent::get()?.try_minimum_balance(oracle.data_len().max(OracleState::LEN))?;
+        let amount = withdrawable_lamports(oracle.lamports(), rent_exempt);
+
+        if amount > 0 {
+            let new_destination = destination
+                .lamports()
+                .checked_add(amount)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            oracle.set_lamports(oracle.lamports() - amount);
+            destination.set_lamports(new_destination);
+        }
+
+        // Stay paused until the authority resumes
+        let mut oracle_data = oracle.try_borrow_mut()?;
+        let state = OracleState::from_bytes_mut(&mut oracle_data)?;
+        state.set_paused(true);
+
+        // Whatever was deployed has left with the positions
+        if !self.accounts.positions.is_empty() {
+            state.set_current_allocation_bps([0; protocol::COUNT])?;
+            state.withdraw_value(deployed)?;
         }
 
-        // Verify authority - CRITICAL security check
-        if state.authority != *self.accounts.authority.address().as_ref() {
-            return Err(OracleError::InvalidAuthority.into());
+        // Lamports sent to the oracle without a deposit leave too, but were
+        // never tracked
+        state.withdraw_value(amount.min(state.total_value_managed()))?;
+
+        Ok(())
+    }
+
+    /// Send each position's whole token balance to its pool account,
+    /// signed by the oracle PDA with the bump stored in `signer`
+    fn close_positions(&self, program_id: &Address, signer: &OracleState) -> ProgramResult {
+        if self.accounts.positions.is_empty() {
+            return Ok(());
         }
 
-        // TODO: Implement emergency withdrawal
-        // 1. Close all open positions
-        // 2. Swap all tokens back to SOL or USDC
-        // 3. Transfer all funds to destination
-        // 4. Update oracle state to reflect emergency mode
-        //
-        // This is a safety feature - must work even under adverse conditions
+        signer.verify_bump(self.accounts.oracle.address(), program_id)?;
+        let seeds = signer.signer_seeds();
+        let signers = [Signer::from(&seeds[..])];
+
+        for pair in self.accounts.positions.chunks_exact(2) {
+            let (token_account, pool_account) = (&pair[0], &pair[1]);
+            let amount = token_amount(token_account)?;
+            if amount > 0 {
+                token::transfer(token_account, pool_account, self.accounts.oracle, amount, &signers)?;
+            }
+        }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_positions() {
+        // Token program, two positions, notifier
+        let rest = [0u8, 11, 12, 21, 22, 9];
+
+        let (positions, notifier) = split_positions(&rest[..5], true).unwrap();
+        assert_eq!(positions, [11, 12, 21, 22]);
+        assert_eq!(positions.chunks_exact(2).count(), 2);
+        assert_eq!(notifier, None);
+
+        let (positions, notifier) = split_positions(&rest, true).unwrap();
+        assert_eq!(positions, [11, 12, 21, 22]);
+        assert_eq!(notifier, Some(&9));
+
+        // Lamports only
+        assert_eq!(split_positions(&rest[5..], false), Ok((&[][..], Some(&9))));
+        assert_eq!(split_positions::<u8>(&[], false), Ok((&[][..], None)));
+
+        // ...where anything past the notifier is a misordered list
+        assert_eq!(
+            split_positions(&rest[4..], false),
+            Err(OracleError::TooManyAccounts.into())
+        );
+    }
+
+    #[test]
+    fn test_withdrawable_lamports() {
+        let rent_exempt = 1_392_000;
+
+        // Destination gains everything above the rent-exempt minimum
+        let balance = rent_exempt + 2_000_000_000;
+        let amount = withdrawable_lamports(balance, rent_exempt);
+        assert_eq!(amount, 2_000_000_000);
+        assert_eq!(balance - amount, rent_exempt);
+
+        // Nothing to move at or below the minimum
+        assert_eq!(withdrawable_lamports(rent_exempt, rent_exempt), 0);
+        assert_eq!(withdrawable_lamports(rent_exempt - 1, rent_exempt), 0);
+    }
+}
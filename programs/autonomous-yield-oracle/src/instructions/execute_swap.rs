@@ -1,17 +1,36 @@
 //! Execute Swap instruction
 //!
 //! Executes a swap via Raydium CPMM or Jupiter aggregator.
-//! 
-//! Note: Full CPI integration pending - pinocchio-raydium-cpmm-cpi uses
-//! older pinocchio AccountInfo while we use 0.10 AccountView.
-//! For hackathon MVP: validate accounts and record decision, 
-//! actual swap executed via off-chain agent calling Raydium directly.
+//!
+//! Raydium swaps run on-chain through a CPI signed by the oracle PDA, with
+//! the Raydium pool accounts (see `RaydiumSwapAccounts`) following
+//! `dest_token`.
+//!
+//! Jupiter swaps run on-chain when a route is passed in `route_data`. The
+//! Jupiter program then follows `dest_token`, and after it every account
+//! of the route, in the route's order and including the oracle as its
+//! transfer authority. All of them are forwarded to Jupiter. Jupiter swaps
+//! without a route were executed off-chain by the agent and are only
+//! recorded.
+//!
+//! A swap that books profit pays the performance fee to the treasury. For
+//! Raydium it follows the pool accounts. For Jupiter it may go anywhere
+//! after the route's own accounts and is found by its address.
 
-use pinocchio::{AccountView, ProgramResult};
+use pinocchio::{
+    cpi::Signer,
+    sysvars::{clock::Clock, rent::Rent, Sysvar},
+    AccountView, Address, ProgramResult,
+};
 use solana_program_error::ProgramError;
 
+use crate::cpi::jupiter;
+use crate::cpi::raydium::{self, RaydiumSwapAccounts, RAYDIUM_SWAP_ACCOUNTS};
+use crate::cpi::token::{require_token_account, token_amount};
+use crate::logging;
 use crate::state::OracleState;
 use crate::error::OracleError;
+use super::{load_oracle, parse_priority_fee, withdrawable_lamports, ProtocolId};
 
 /// Accounts required for executing a swap
 pub struct ExecuteSwapAccounts<'a> {
@@ -23,13 +42,16 @@ pub struct ExecuteSwapAccounts<'a> {
     pub source_token: &'a AccountView,
     /// Destination token account
     pub dest_token: &'a AccountView,
+    /// Venue-specific accounts (e.g. the Raydium pool), then the fee
+    /// treasury when a performance fee is due
+    pub remaining: &'a [AccountView],
 }
 
 impl<'a> TryFrom<&'a [AccountView]> for ExecuteSwapAccounts<'a> {
     type Error = ProgramError;
 
     fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
-        let [oracle, authority, source_token, dest_token, ..] = accounts else {
+        let [oracle, authority, source_token, dest_token, remaining @ ..] = accounts else {
             return Err(ProgramError::NotEnoughAccountKeys);
         };
 
@@ -37,45 +59,195 @@ impl<'a> TryFrom<&'a [AccountView]> for ExecuteSwapAccounts<'a> {
             return Err(ProgramError::MissingRequiredSignature);
         }
 
+        // One account in two roles would alias the oracle's borrows
+        require_distinct(&[
+            oracle.address(),
+            authority.address(),
+            source_token.address(),
+            dest_token.address(),
+        ])?;
+
         Ok(Self {
             oracle,
             authority,
             source_token,
             dest_token,
+            remaining,
         })
     }
 }
 
+/// Fail with `InvalidArgument` if any address appears twice
+pub(crate) fn require_distinct(addresses: &[&Address]) -> Result<(), ProgramError> {
+    for (i, address) in addresses.iter().enumerate() {
+        if addresses[i + 1..].contains(address) {
+            return Err(ProgramError::InvalidArgument);
+        }
+    }
+    Ok(())
+}
+
+/// Fixed-point scale of `ExecuteSwapData::reference_price`
+pub const PRICE_SCALE: u64 = 1_000_000_000;
+
+/// Size of `ExecuteSwapData` up to the route
+const ROUTE_OFFSET: usize = 34;
+
 /// Instruction data for swap execution
-pub struct ExecuteSwapData {
+/// Layout: amount_in (8) + min_amount_out (8) + protocol (1) +
+/// reference_price (8, optional) + priority_fee_lamports (8, optional) +
+/// urgent (1, optional) = 34 bytes, then for Jupiter optionally
+/// route_len (2, le) + route_data (route_len)
+pub struct ExecuteSwapData<'a> {
     /// Amount to swap (in smallest units)
     pub amount_in: u64,
     /// Minimum amount out (slippage protection)
     pub min_amount_out: u64,
-    /// Protocol to use (0 = Raydium direct, 1 = Jupiter)
-    pub protocol: u8,
+    /// Protocol to use (only Raydium direct and Jupiter routes are swap
+    /// venues)
+    pub protocol: ProtocolId,
+    /// Source-token units per output-token unit, scaled by `PRICE_SCALE`
+    ///
+    /// Values the output in the source token (lamports for SOL/wSOL
+    /// sources) to book realized PnL. 0, or omitted by older clients,
+    /// books nothing.
+    pub reference_price: u64,
+    /// Priority fee the transaction was sent with (client-supplied)
+    pub priority_fee_lamports: u64,
+    /// Urgent exit; bypasses the priority fee cap
+    pub urgent: bool,
+    /// Jupiter route instruction data, forwarded as is (empty = executed
+    /// off-chain)
+    pub route_data: &'a [u8],
 }
 
-impl TryFrom<&[u8]> for ExecuteSwapData {
+impl<'a> TryFrom<&'a [u8]> for ExecuteSwapData<'a> {
     type Error = ProgramError;
 
-    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
         if data.len() < 17 {
+            return Err(OracleError::DataTooShort.into());
+        }
+
+        let protocol = ProtocolId::try_from(data[16])?;
+
+        let reference_price = match data.get(17..25) {
+            Some(price) => u64::from_le_bytes(price.try_into().unwrap()),
+            None => 0,
+        };
+
+        let (priority_fee_lamports, urgent) =
+            parse_priority_fee(data.get(25..ROUTE_OFFSET.min(data.len())).unwrap_or(&[]));
+
+        let route_data = parse_route(data.get(ROUTE_OFFSET..).unwrap_or(&[]))?;
+        if !route_data.is_empty() && protocol != ProtocolId::JupiterRoute {
             return Err(ProgramError::InvalidInstructionData);
         }
 
         Ok(Self {
             amount_in: u64::from_le_bytes(data[0..8].try_into().unwrap()),
             min_amount_out: u64::from_le_bytes(data[8..16].try_into().unwrap()),
-            protocol: data[16],
+            protocol,
+            reference_price,
+            priority_fee_lamports,
+            urgent,
+            route_data,
         })
     }
 }
 
+/// Parse the optional length-prefixed route tail
+///
+/// Bytes past the route are rejected, so a wrong length can't go unnoticed.
+fn parse_route(tail: &[u8]) -> Result<&[u8], ProgramError> {
+    if tail.is_empty() {
+        return Ok(&[]);
+    }
+
+    let Some((len, route)) = tail.split_first_chunk::<2>() else {
+        return Err(OracleError::DataTooShort.into());
+    };
+
+    let len = u16::from_le_bytes(*len) as usize;
+    if route.len() < len {
+        return Err(OracleError::DataTooShort.into());
+    }
+    if route.len() > len {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    Ok(route)
+}
+
+impl ExecuteSwapData<'_> {
+    /// Realized PnL of receiving `amount_out` for `amount_in`
+    ///
+    /// `amount_out` is valued at `reference_price`; the result is in
+    /// source-token units and saturates at the `i64` bounds.
+    pub fn realized_pnl(&self, amount_out: u64) -> i64 {
+        let value_out = amount_out as i128 * self.reference_price as i128 / PRICE_SCALE as i128;
+        let pnl = value_out - self.amount_in as i128;
+        pnl.clamp(i64::MIN as i128, i64::MAX as i128) as i64
+    }
+
+    /// Check `signer` may run this swap now, and count it as a swap
+    ///
+    /// Checks and count go through the same `&mut OracleState`, so nothing
+    /// can act on the oracle between them.
+    pub fn authorize(&self, state: &mut OracleState, signer: &[u8], now: i64) -> ProgramResult {
+        state.require_authority(signer)?;
+
+        // Refuse to trade while paused
+        state.require_not_paused()?;
+
+        // Skip non-urgent trades during fee spikes
+        state.check_priority_fee(self.priority_fee_lamports, self.urgent)?;
+
+        // Only Raydium and Jupiter can execute swaps
+        if !matches!(self.protocol, ProtocolId::RaydiumCpmm | ProtocolId::JupiterRoute) {
+            return Err(OracleError::InvalidProtocol.into());
+        }
+        state.require_protocol_allowed(self.protocol as u8)?;
+
+        state.roll_decision_window(now);
+        state.increment_swaps()?;
+        Ok(())
+    }
+
+    /// Record the outcome of an authorized swap in the oracle state
+    ///
+    /// The best protocol is left alone: it is what the scoring selected,
+    /// not whichever venue last traded.
+    ///
+    /// `amount_out` is the amount actually received, when known on-chain;
+    /// PnL is only booked for those swaps and with a reference price. It
+    /// moves the value managed too, as `RecordPnl` does, failing as
+    /// `OracleState::value_after_pnl` does, and with `PnlOverflow` past the
+    /// i64 range.
+    pub fn record(&self, state: &mut OracleState, amount_out: Option<u64>) -> ProgramResult {
+        if let Some(amount_out) = amount_out {
+            if self.reference_price > 0 {
+                let pnl = self.realized_pnl(amount_out);
+                state.book_pnl(pnl, state.value_after_pnl(pnl)?)?;
+            }
+        }
+
+        logging::format_swap(
+            self.protocol as u8,
+            self.amount_in,
+            self.min_amount_out,
+            amount_out.unwrap_or(0),
+        )
+        .log();
+
+        Ok(())
+    }
+}
+
 /// Execute Swap instruction
 pub struct ExecuteSwap<'a> {
     pub accounts: ExecuteSwapAccounts<'a>,
-    pub data: ExecuteSwapData,
+    pub data: ExecuteSwapData<'a>,
 }
 
 impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for ExecuteSwap<'a> {
@@ -89,42 +261,428 @@ impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for ExecuteSwap<'a> {
 }
 
 impl<'a> ExecuteSwap<'a> {
-    pub fn process(&self) -> ProgramResult {
-        // Verify oracle is initialized and authority matches
-        {
-            let oracle_data = self.accounts.oracle.try_borrow()?;
-            let state = OracleState::from_bytes(&oracle_data)?;
-
-            if state.is_initialized == 0 {
-                return Err(OracleError::NotInitialized.into());
-            }
+    pub fn process(&self, program_id: &Address) -> ProgramResult {
+        let now = Clock::get()?.unix_timestamp;
 
-            if state.authority != *self.accounts.authority.address().as_ref() {
-                return Err(OracleError::InvalidAuthority.into());
-            }
+        // Program-owned, initialized oracle only
+        load_oracle(self.accounts.oracle, program_id)?;
 
-            // Validate protocol choice
-            if self.data.protocol > 1 {
-                return Err(OracleError::InvalidProtocol.into());
-            }
-        }
+        // Validate and count the swap under a single mutable borrow. A read
+        // borrow here followed by a separate write after the swap would let
+        // anything reentering in between act on checks already passed. The
+        // borrow ends before the CPI, which needs the oracle as signer.
+        let snapshot = {
+            let mut oracle_data = self.accounts.oracle.try_borrow_mut()?;
+            let state = OracleState::from_bytes_mut(&mut oracle_data)?;
+            self.data.authorize(state, self.accounts.authority.address().as_ref(), now)?;
 
-        // Record the swap decision in oracle state
-        // Actual swap execution happens off-chain via agent calling Raydium/Jupiter directly
-        // This is the "Most Agentic" pattern: on-chain validation + off-chain execution
-        let mut oracle_data_mut = self.accounts.oracle.try_borrow_mut()?;
-        let state_mut = OracleState::from_bytes_mut(&mut oracle_data_mut)?;
-        
-        // Increment decisions counter
-        state_mut.increment_decisions();
-        
-        // Update best protocol based on this decision
-        state_mut.best_protocol = self.data.protocol;
-
-        // Log the decision (viewable in transaction logs)
-        // Format: SWAP|protocol|amount_in|min_out
-        // Agent can verify this matches its intent
+            *state
+        };
+
+        let amount_out = if self.data.protocol == ProtocolId::RaydiumCpmm {
+            Some(swap_raydium_signed(
+                program_id,
+                self.accounts.oracle,
+                &snapshot,
+                self.accounts.source_token,
+                self.accounts.dest_token,
+                self.accounts.remaining,
+                self.data.amount_in,
+                self.data.min_amount_out,
+            )?)
+        } else if !self.data.route_data.is_empty() {
+            Some(swap_jupiter_signed(
+                program_id,
+                self.accounts.oracle,
+                &snapshot,
+                self.accounts.source_token,
+                self.accounts.dest_token,
+                self.accounts.remaining,
+                self.data.route_data,
+                self.data.min_amount_out,
+            )?)
+        } else {
+            None
+        };
+
+        // Book the outcome; the decision itself is already counted
+        // Jupiter routes without route data execute off-chain via the
+        // agent, so their output (and PnL) isn't known here
+        let pnl = {
+            let mut oracle_data_mut = self.accounts.oracle.try_borrow_mut()?;
+            let state_mut = OracleState::from_bytes_mut(&mut oracle_data_mut)?;
+            self.data.record(state_mut, amount_out)?;
+
+            amount_out
+                .filter(|_| self.data.reference_price > 0)
+                .map(|amount_out| self.data.realized_pnl(amount_out))
+        };
+
+        if let Some(pnl) = pnl {
+            let treasury = match self.data.protocol {
+                ProtocolId::RaydiumCpmm => self.accounts.remaining.get(RAYDIUM_SWAP_ACCOUNTS),
+                _ => self
+                    .accounts
+                    .remaining
+                    .iter()
+                    .find(|account| account.address().as_ref() == snapshot.fee_destination),
+            };
+            collect_performance_fee(self.accounts.oracle, treasury, pnl)?;
+        }
 
         Ok(())
     }
 }
+
+/// Pay the performance fee on booked `pnl` from the oracle to `treasury`
+///
+/// `treasury` must be the stored fee destination whenever a fee is due. The
+/// fee is capped at what the oracle holds above rent exemption, and leaves
+/// `total_value_managed` along with the lamports.
+pub(crate) fn collect_performance_fee(
+    oracle: &AccountView,
+    treasury: Option<&AccountView>,
+    pnl: i64,
+) -> ProgramResult {
+    let mut oracle_data = oracle.try_borrow_mut()?;
+    let state = OracleState::from_bytes_mut(&mut oracle_data)?;
+
+    let fee = state.performance_fee(pnl);
+    if fee == 0 {
+        return Ok(());
+    }
+
+    let treasury = treasury.ok_or(ProgramError::NotEnoughAccountKeys)?;
+    if treasury.address().as_ref() != state.fee_destination.as_slice() {
+        return Err(OracleError::InvalidTreasury.into());
+    }
+
+    let rent_exempt = Rent::get()?.try_minimum_balance(oracle.data_len())?;
+    let fee = fee.min(withdrawable_lamports(oracle.lamports(), rent_exempt));
+    if fee == 0 {
+        return Ok(());
+    }
+
+    let new_treasury = treasury
+        .lamports()
+        .checked_add(fee)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    oracle.set_lamports(oracle.lamports() - fee);
+    treasury.set_lamports(new_treasury);
+
+    state.remove_value(fee.min(state.total_value_managed()))
+}
+
+/// Swap through Raydium CPMM, signed by the oracle PDA
+///
+/// `pool_accounts` start with the `RaydiumSwapAccounts`. See `swap_signed`
+/// for the checks. Returns the amount received.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn swap_raydium_signed(
+    program_id: &Address,
+    oracle: &AccountView,
+    signer: &OracleState,
+    source_token: &AccountView,
+    dest_token: &AccountView,
+    pool_accounts: &[AccountView],
+    amount_in: u64,
+    min_amount_out: u64,
+) -> Result<u64, ProgramError> {
+    let pool = RaydiumSwapAccounts::try_from(pool_accounts)?;
+
+    swap_signed(program_id, oracle, signer, source_token, dest_token, min_amount_out, |signers| {
+        raydium::swap_base_input(
+            oracle,
+            source_token,
+            dest_token,
+            &pool,
+            amount_in,
+            min_amount_out,
+            signers,
+        )
+    })
+}
+
+/// Swap along a pre-built Jupiter route, signed by the oracle PDA
+///
+/// `route_accounts` start with the Jupiter program (see `jupiter::route`).
+/// See `swap_signed` for the checks. Returns the amount received.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn swap_jupiter_signed(
+    program_id: &Address,
+    oracle: &AccountView,
+    signer: &OracleState,
+    source_token: &AccountView,
+    dest_token: &AccountView,
+    route_accounts: &[AccountView],
+    route_data: &[u8],
+    min_amount_out: u64,
+) -> Result<u64, ProgramError> {
+    swap_signed(program_id, oracle, signer, source_token, dest_token, min_amount_out, |signers| {
+        jupiter::route(oracle.address(), route_accounts, route_data, signers)
+    })
+}
+
+/// Run `venue` with the oracle PDA as signer, returning the amount received
+///
+/// `signer` is a copy of the oracle's state, taken before the borrow is
+/// released for the CPI; its stored bump signs. Enforces `min_amount_out`
+/// against the destination balance change rather than trusting the venue
+/// alone. Both token accounts must belong to the SPL Token program.
+fn swap_signed(
+    program_id: &Address,
+    oracle: &AccountView,
+    signer: &OracleState,
+    source_token: &AccountView,
+    dest_token: &AccountView,
+    min_amount_out: u64,
+    venue: impl FnOnce(&[Signer]) -> ProgramResult,
+) -> Result<u64, ProgramError> {
+    signer.verify_bump(oracle.address(), program_id)?;
+    let seeds = signer.signer_seeds();
+    let signers = [Signer::from(&seeds[..])];
+
+    // Both sides must be real token accounts, or the balances mean nothing
+    require_token_account(source_token)?;
+    let balance_before = token_amount(dest_token)?;
+    venue(&signers)?;
+    let balance_after = token_amount(dest_token)?;
+
+    let amount_out = balance_after.saturating_sub(balance_before);
+    if amount_out < min_amount_out {
+        return Err(OracleError::SlippageExceeded.into());
+    }
+
+    Ok(amount_out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instructions::protocol;
+    use crate::state::status;
+
+    #[test]
+    fn test_rejects_unknown_protocol_and_short_data() {
+        let mut data = [0u8; 17];
+        data[16] = protocol::MAX_PROTOCOL;
+        assert!(ExecuteSwapData::try_from(&data[..]).is_ok());
+
+        data[16] = 5;
+        assert_eq!(
+            ExecuteSwapData::try_from(&data[..]).err(),
+            Some(OracleError::InvalidProtocol.into())
+        );
+
+        assert_eq!(
+            ExecuteSwapData::try_from(&data[..16]).err(),
+            Some(OracleError::DataTooShort.into())
+        );
+    }
+
+    #[test]
+    fn test_rejects_duplicate_accounts() {
+        let [oracle, authority, source, dest] =
+            [1u8, 2, 3, 4].map(|byte| Address::new_from_array([byte; 32]));
+
+        assert!(require_distinct(&[&oracle, &authority, &source, &dest]).is_ok());
+
+        // Oracle passed again as a token account
+        assert_eq!(
+            require_distinct(&[&oracle, &authority, &oracle, &dest]),
+            Err(ProgramError::InvalidArgument)
+        );
+        assert_eq!(
+            require_distinct(&[&oracle, &authority, &source, &oracle]),
+            Err(ProgramError::InvalidArgument)
+        );
+
+        // Same token account on both sides, or the signer's key reused
+        assert_eq!(
+            require_distinct(&[&oracle, &authority, &source, &source]),
+            Err(ProgramError::InvalidArgument)
+        );
+        assert_eq!(
+            require_distinct(&[&oracle, &authority, &authority, &dest]),
+            Err(ProgramError::InvalidArgument)
+        );
+    }
+
+    #[test]
+    fn test_reference_price_is_optional() {
+        let mut data = [0u8; 25];
+        data[17..25].copy_from_slice(&PRICE_SCALE.to_le_bytes());
+        assert_eq!(ExecuteSwapData::try_from(&data[..]).unwrap().reference_price, PRICE_SCALE);
+        assert_eq!(ExecuteSwapData::try_from(&data[..17]).unwrap().reference_price, 0);
+    }
+
+    #[test]
+    fn test_parse_route_tail() {
+        let mut data = [0u8; ROUTE_OFFSET + 2 + 4];
+        data[16] = protocol::JUPITER_ROUTE;
+        data[ROUTE_OFFSET..ROUTE_OFFSET + 2].copy_from_slice(&4u16.to_le_bytes());
+        data[ROUTE_OFFSET + 2..].copy_from_slice(&[0xe5, 0x17, 0xcb, 0x97]);
+
+        let swap = ExecuteSwapData::try_from(&data[..]).unwrap();
+        assert_eq!(swap.route_data, [0xe5, 0x17, 0xcb, 0x97]);
+
+        // No route: executed off-chain
+        assert!(ExecuteSwapData::try_from(&data[..ROUTE_OFFSET]).unwrap().route_data.is_empty());
+
+        // Cut short, or in the prefix itself
+        assert_eq!(
+            ExecuteSwapData::try_from(&data[..data.len() - 1]).err(),
+            Some(OracleError::DataTooShort.into())
+        );
+        assert_eq!(
+            ExecuteSwapData::try_from(&data[..ROUTE_OFFSET + 1]).err(),
+            Some(OracleError::DataTooShort.into())
+        );
+
+        // Bytes past the route
+        let mut long = [0u8; ROUTE_OFFSET + 2 + 5];
+        long[..data.len()].copy_from_slice(&data);
+        assert_eq!(
+            ExecuteSwapData::try_from(&long[..]).err(),
+            Some(ProgramError::InvalidInstructionData)
+        );
+
+        // Only Jupiter takes a route
+        data[16] = protocol::RAYDIUM_CPMM;
+        assert_eq!(
+            ExecuteSwapData::try_from(&data[..]).err(),
+            Some(ProgramError::InvalidInstructionData)
+        );
+    }
+
+    #[test]
+    fn test_swap_books_realized_pnl() {
+        let state = &mut OracleState::test_state();
+        state.set(status::INITIALIZED);
+        state.authority = [7u8; 32];
+        state.set_total_value_managed(1_000_000_000);
+        state.set_net_deposits(1_000_000_000);
+
+        // 1 SOL in for jitoSOL worth 1.25 SOL each
+        let swap = ExecuteSwapData {
+            amount_in: 1_000_000_000,
+            min_amount_out: 790_000_000,
+            protocol: ProtocolId::RaydiumCpmm,
+            reference_price: 1_250_000_000,
+            priority_fee_lamports: 0,
+            urgent: false,
+            route_data: &[],
+        };
+
+        // 0.82 jitoSOL = 1.025 SOL
+        swap.authorize(state, &[7u8; 32], 0).unwrap();
+        swap.record(state, Some(820_000_000)).unwrap();
+        assert_eq!(state.cumulative_pnl(), 25_000_000);
+        assert_eq!(state.total_value_managed(), 1_025_000_000);
+        assert_eq!(state.true_pnl(), state.cumulative_pnl());
+
+        // 0.78 jitoSOL = 0.975 SOL
+        swap.authorize(state, &[7u8; 32], 0).unwrap();
+        swap.record(state, Some(780_000_000)).unwrap();
+        assert_eq!(state.cumulative_pnl(), 0);
+        assert_eq!(state.total_value_managed(), 1_000_000_000);
+        assert_eq!(state.peak_pnl(), 25_000_000);
+        assert_eq!(state.decisions_count(), 2);
+
+        // Off-chain fills book nothing
+        swap.authorize(state, &[7u8; 32], 0).unwrap();
+        swap.record(state, None).unwrap();
+        assert_eq!(state.cumulative_pnl(), 0);
+        assert_eq!(state.decisions_count(), 3);
+        assert_eq!(state.swaps_count(), 3);
+        assert_eq!(state.rebalances_count(), 0);
+    }
+
+    #[test]
+    fn test_swap_keeps_best_protocol() {
+        let state = &mut OracleState::test_state();
+        state.set(status::INITIALIZED);
+        state.authority = [7u8; 32];
+        state.best_protocol = protocol::MARINADE;
+        state.set_current_apy_bps(900);
+
+        // Trading through a venue doesn't make it the best yield
+        let swap = ExecuteSwapData {
+            amount_in: 1_000_000_000,
+            min_amount_out: 0,
+            protocol: ProtocolId::RaydiumCpmm,
+            reference_price: 1_250_000_000,
+            priority_fee_lamports: 0,
+            urgent: false,
+            route_data: &[],
+        };
+        swap.authorize(state, &[7u8; 32], 0).unwrap();
+        swap.record(state, Some(800_000_000)).unwrap();
+
+        assert_eq!(state.best_protocol, protocol::MARINADE);
+        assert_eq!(state.current_apy_bps(), 900);
+        assert_eq!(state.swaps_count(), 1);
+    }
+
+    #[test]
+    fn test_authorize_and_record_in_one_borrow() {
+        let state = &mut OracleState::test_state();
+        let swap = ExecuteSwapData {
+            amount_in: 1_000_000_000,
+            min_amount_out: 0,
+            protocol: ProtocolId::JupiterRoute,
+            reference_price: 0,
+            priority_fee_lamports: 0,
+            urgent: false,
+            route_data: &[],
+        };
+
+        // One &mut OracleState from checks through bookkeeping
+        state.set(status::INITIALIZED);
+        state.authority = [7u8; 32];
+        state.best_protocol = protocol::KAMINO;
+        swap.authorize(state, &[7u8; 32], 100_000).unwrap();
+        swap.record(state, None).unwrap();
+
+        assert_eq!(state.decisions_count(), 1);
+        assert_eq!(state.best_protocol, protocol::KAMINO);
+        assert_eq!(state.decisions_window_start(), 100_000);
+
+        // Rejected swaps aren't counted
+        assert_eq!(
+            swap.authorize(state, &[8u8; 32], 100_000),
+            Err(OracleError::InvalidAuthority.into())
+        );
+        state.set_paused(true);
+        assert_eq!(
+            swap.authorize(state, &[7u8; 32], 100_000),
+            Err(OracleError::EmergencyModeActive.into())
+        );
+        assert_eq!(state.decisions_count(), 1);
+    }
+
+    #[test]
+    fn test_protocol_allowlist() {
+        let state = &mut OracleState::test_state();
+        state.set(status::INITIALIZED);
+        state.authority = [7u8; 32];
+        state.set_protocol_allowlist(1 << protocol::JUPITER_ROUTE);
+
+        let swap = |protocol| ExecuteSwapData {
+            amount_in: 1_000_000_000,
+            min_amount_out: 0,
+            protocol,
+            reference_price: 0,
+            priority_fee_lamports: 0,
+            urgent: false,
+            route_data: &[],
+        };
+
+        swap(ProtocolId::JupiterRoute).authorize(state, &[7u8; 32], 0).unwrap();
+        assert_eq!(
+            swap(ProtocolId::RaydiumCpmm).authorize(state, &[7u8; 32], 0),
+            Err(OracleError::InvalidProtocol.into())
+        );
+        assert_eq!(state.swaps_count(), 1);
+    }
+}
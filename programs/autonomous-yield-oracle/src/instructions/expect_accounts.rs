@@ -0,0 +1,38 @@
+//! Account count check shared by the stricter instructions
+//!
+//! Most instructions destructure `[oracle, authority, ..]` and ignore any
+//! accounts after the ones they use. Where a misordered list must not slip
+//! through, the count is checked exactly first.
+
+use pinocchio::ProgramResult;
+use solana_program_error::ProgramError;
+
+use crate::error::OracleError;
+
+/// Fail unless exactly `n` accounts were passed
+///
+/// Too few fails with `NotEnoughAccountKeys`, too many with
+/// `TooManyAccounts`.
+pub fn expect_accounts<T>(accounts: &[T], n: usize) -> ProgramResult {
+    if accounts.len() < n {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    if accounts.len() > n {
+        return Err(OracleError::TooManyAccounts.into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expect_accounts() {
+        let accounts = [(); 3];
+        assert_eq!(expect_accounts(&accounts[..2], 2), Ok(()));
+        assert_eq!(expect_accounts(&accounts[..1], 2), Err(ProgramError::NotEnoughAccountKeys));
+        assert_eq!(expect_accounts(&accounts[..0], 2), Err(ProgramError::NotEnoughAccountKeys));
+        assert_eq!(expect_accounts(&accounts, 2), Err(OracleError::TooManyAccounts.into()));
+    }
+}
@@ -0,0 +1,162 @@
+//! Get State instruction
+//!
+//! Returns the oracle's state through return data, followed by values
+//! derived from it that clients would otherwise have to recompute.
+//! Read-only; no signer required. Works on accounts of older versions,
+//! with the fields they lack reported as defaults.
+//!
+//! Return data layout: state (`OracleState::LEN`) +
+//! allocation_lamports (6 x u64 le: five buckets, then cash) = 910 bytes
+//!
+//! The state is also logged with `sol_log_data`, so it can be followed with
+//! `logsSubscribe`. The snapshot is byte for byte the current on-disk
+//! layout of `OracleState`: integers little-endian,
+//! addresses raw, and the first byte is the layout version of the account
+//! read. Fields that version lacks are zero. A second logged field holds
+//! one byte of flags (see `state_log_flag`). The signed PnL is also logged
+//! as text (see `logging::format_pnl`), followed by the annualized return
+//! since Initialize (see `OracleState::annualized_return_bps`).
+
+use pinocchio::{
+    cpi::set_return_data,
+    log::sol_log_data,
+    sysvars::{clock::Clock, Sysvar},
+    AccountView, Address, ProgramResult,
+};
+use solana_program_error::ProgramError;
+
+use crate::logging;
+use crate::state::OracleState;
+use super::{load_oracle_any_version, protocol};
+
+/// Size of the state report in return data
+pub const STATE_REPORT_LEN: usize = OracleState::LEN + (protocol::COUNT + 1) * 8;
+
+/// Bits of the flags byte logged after the snapshot
+pub mod state_log_flag {
+    /// The allocation drifted past `drift_threshold_bps`
+    pub const NEEDS_REBALANCE: u8 = 1 << 0;
+}
+
+/// Accounts required for reading the state
+pub struct GetStateAccounts<'a> {
+    /// The oracle account
+    pub oracle: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for GetStateAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [oracle, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self { oracle })
+    }
+}
+
+/// Encode the state as logged: the current on-disk layout
+pub fn state_snapshot(state: &OracleState) -> [u8; OracleState::LEN] {
+    let mut snapshot = [0u8; OracleState::LEN];
+    snapshot.copy_from_slice(state.as_bytes());
+    snapshot
+}
+
+/// Flags logged alongside the snapshot
+pub fn state_log_flags(state: &OracleState) -> u8 {
+    let mut flags = 0;
+    if state.needs_rebalance() {
+        flags |= state_log_flag::NEEDS_REBALANCE;
+    }
+    flags
+}
+
+/// Encode the state and its derived values
+pub fn state_report(state: &OracleState) -> [u8; STATE_REPORT_LEN] {
+    let mut report = [0u8; STATE_REPORT_LEN];
+    report[..OracleState::LEN].copy_from_slice(state.as_bytes());
+    for (chunk, lamports) in report[OracleState::LEN..]
+        .chunks_exact_mut(8)
+        .zip(state.allocation_lamports())
+    {
+        chunk.copy_from_slice(&lamports.to_le_bytes());
+    }
+    report
+}
+
+/// Get State instruction
+pub struct GetState<'a> {
+    pub accounts: GetStateAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for GetState<'a> {
+    type Error = ProgramError;
+
+    fn try_from((_data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = GetStateAccounts::try_from(accounts)?;
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> GetState<'a> {
+    pub fn process(&self, program_id: &Address) -> ProgramResult {
+        // Program-owned, initialized oracle of any version
+        let state = load_oracle_any_version(self.accounts.oracle, program_id)?;
+
+        sol_log_data(&[&state_snapshot(&state), &[state_log_flags(&state)]]);
+        logging::format_pnl(state.cumulative_pnl(), state.total_value_managed()).log();
+        let annualized = state.annualized_return_bps(Clock::get()?.unix_timestamp);
+        logging::format_return(annualized, state.stats_since()).log();
+        set_return_data(&state_report(&state));
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_snapshot() {
+        let state = &mut OracleState::test_state();
+        state.set_total_value_managed(0x0102_0304_0506_0708);
+        state.set_fee_bps(250);
+
+        let snapshot = state_snapshot(state);
+        assert_eq!(&snapshot[..], state.as_bytes());
+        assert_eq!(snapshot[0], OracleState::VERSION);
+
+        // total_value_managed and fee_bps, little-endian at their offsets
+        assert_eq!(snapshot[45..53], [8, 7, 6, 5, 4, 3, 2, 1]);
+        assert_eq!(snapshot[617..619], [250, 0]);
+    }
+
+    #[test]
+    fn test_state_report() {
+        let state = &mut OracleState::test_state();
+        state.set_total_value_managed(1000);
+        state.set_current_allocation_bps([6000, 0, 0, 0, 0]).unwrap();
+
+        let report = state_report(state);
+        assert_eq!(&report[..OracleState::LEN], state.as_bytes());
+
+        let first = OracleState::LEN;
+        let cash = OracleState::LEN + 5 * 8;
+        assert_eq!(u64::from_le_bytes(report[first..first + 8].try_into().unwrap()), 600);
+        assert_eq!(u64::from_le_bytes(report[cash..].try_into().unwrap()), 400);
+    }
+    #[test]
+    fn test_state_log_flags() {
+        let state = &mut OracleState::test_state();
+        state.set_drift_threshold_bps(500);
+        state.set_target_allocation_bps([5000, 5000, 0, 0, 0]);
+
+        state.set_current_allocation_bps([5400, 4600, 0, 0, 0]).unwrap();
+        assert_eq!(state_log_flags(state), 0);
+
+        state.set_current_allocation_bps([5600, 4400, 0, 0, 0]).unwrap();
+        assert_eq!(state_log_flags(state), state_log_flag::NEEDS_REBALANCE);
+    }
+}
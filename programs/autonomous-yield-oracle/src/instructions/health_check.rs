@@ -0,0 +1,101 @@
+//! Health Check instruction
+//!
+//! Reports operational status through return data, so clients can see
+//! whether the oracle will accept a decision and, if not, how long to back
+//! off. Read-only; no signer required; works on accounts of older versions.
+//!
+//! Return data layout: paused (1) + config_locked (1) +
+//! seconds_until_next_decision (8, le) = 10 bytes
+
+use pinocchio::{
+    cpi::set_return_data,
+    sysvars::{clock::Clock, Sysvar},
+    AccountView, Address, ProgramResult,
+};
+use solana_program_error::ProgramError;
+
+use crate::state::OracleState;
+use super::load_oracle_any_version;
+
+/// Size of the health report in return data
+pub const HEALTH_REPORT_LEN: usize = 10;
+
+/// Accounts required for a health check
+pub struct HealthCheckAccounts<'a> {
+    /// The oracle account
+    pub oracle: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for HealthCheckAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [oracle, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self { oracle })
+    }
+}
+
+/// Encode the oracle's health as of `now`
+pub fn health_report(state: &OracleState, now: i64) -> [u8; HEALTH_REPORT_LEN] {
+    let mut report = [0u8; HEALTH_REPORT_LEN];
+    report[0] = state.is_paused() as u8;
+    report[1] = state.is_config_locked() as u8;
+    report[2..10].copy_from_slice(&state.seconds_until_next_decision(now).to_le_bytes());
+    report
+}
+
+/// Health Check instruction
+pub struct HealthCheck<'a> {
+    pub accounts: HealthCheckAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for HealthCheck<'a> {
+    type Error = ProgramError;
+
+    fn try_from((_data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = HealthCheckAccounts::try_from(accounts)?;
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> HealthCheck<'a> {
+    pub fn process(&self, program_id: &Address) -> ProgramResult {
+        // Program-owned, initialized oracle of any version
+        let state = load_oracle_any_version(self.accounts.oracle, program_id)?;
+
+        let now = Clock::get()?.unix_timestamp;
+        set_return_data(&health_report(&state, now));
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_health_report() {
+        let state = &mut OracleState::test_state();
+        state.set_last_update(10_000);
+        state.set_min_update_interval_secs(300);
+        state.set_paused(true);
+
+        let report = health_report(state, 10_100);
+        assert_eq!(report[0], 1);
+        assert_eq!(report[1], 0);
+        assert_eq!(u64::from_le_bytes(report[2..10].try_into().unwrap()), 200);
+
+        // Today's decisions are used up: wait for tomorrow's window
+        let start = 1_770_000_000;
+        state.set_max_decisions_per_day(1);
+        state.roll_decision_window(start);
+        state.increment_decisions().unwrap();
+        let report = health_report(state, start + 100);
+        let wait = OracleState::DECISION_WINDOW_SECS + 1 - 100;
+        assert_eq!(u64::from_le_bytes(report[2..10].try_into().unwrap()), wait as u64);
+    }
+}
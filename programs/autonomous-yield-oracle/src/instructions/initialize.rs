@@ -1,17 +1,32 @@
 //! Initialize instruction
 //!
 //! Sets up the oracle with an authority.
+//!
+//! The oracle account is the PDA derived from
+//! `[OracleState::SEED_PREFIX, authority]`. If it doesn't exist yet it is
+//! created via the system program, funded by the authority.
+//!
+//! Creating and initializing happen in this one instruction, so the account
+//! never exists uninitialized; and only the authority the PDA is derived
+//! from can sign for it, so no one else can claim it first. An account
+//! created beforehand (e.g. pre-funded) is initialized in place.
 
-use pinocchio::{AccountView, ProgramResult};
+use pinocchio::{
+    cpi::{Seed, Signer},
+    sysvars::{clock::Clock, rent::Rent, Sysvar},
+    AccountView, Address, ProgramResult,
+};
+use pinocchio_system::instructions::{Allocate, Assign, CreateAccount, Transfer};
 use solana_program_error::ProgramError;
 
-use crate::state::OracleState;
+use crate::state::{status, OracleState};
+use crate::error::OracleError;
 
 /// Accounts required for initialization
 pub struct InitializeAccounts<'a> {
     /// The oracle account to initialize
     pub oracle: &'a AccountView,
-    /// The authority that will control the oracle
+    /// The authority that will control the oracle (pays for the account)
     pub authority: &'a AccountView,
     /// System program
     pub system_program: &'a AccountView,
@@ -30,6 +45,13 @@ impl<'a> TryFrom<&'a [AccountView]> for InitializeAccounts<'a> {
             return Err(ProgramError::MissingRequiredSignature);
         }
 
+        // The account creation CPI must go to the real system program
+        if *system_program.address() != pinocchio_system::ID {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        check_initial_authority(authority.address(), oracle.address())?;
+
         Ok(Self {
             oracle,
             authority,
@@ -38,39 +60,383 @@ impl<'a> TryFrom<&'a [AccountView]> for InitializeAccounts<'a> {
     }
 }
 
+/// Fail with `InvalidAuthority` for an authority no one could act as
+///
+/// That is the all-zeros address, the system program, or the oracle
+/// itself; any of them would leave an oracle no one controls.
+pub fn check_initial_authority(authority: &Address, oracle: &Address) -> ProgramResult {
+    if *authority == Address::new_from_array([0u8; 32])
+        || *authority == pinocchio_system::ID
+        || authority == oracle
+    {
+        return Err(OracleError::InvalidAuthority.into());
+    }
+    Ok(())
+}
+
+/// Instruction data for initialization
+/// Layout: initial_risk_score (1) + max_acceptable_risk (1) +
+/// min_update_interval_secs (4) + fee_bps (2, optional) +
+/// fee_treasury (32, optional) + max_value_managed (8, optional) +
+/// guardian (32, optional) + protocol_allowlist (1, optional) = 81 bytes,
+/// or empty for the defaults
+pub struct InitializeData {
+    /// Starting risk score (0-100)
+    pub initial_risk_score: u8,
+    /// Highest risk score the oracle will switch into (0-100)
+    pub max_acceptable_risk: u8,
+    /// Minimum seconds between accepted `MonitorYields` updates
+    pub min_update_interval_secs: u32,
+    /// Performance fee on positive PnL (bps, up to `MAX_FEE_BPS`)
+    pub fee_bps: u16,
+    /// Account performance fees are paid to
+    pub fee_treasury: [u8; 32],
+    /// Most value deposits may take the oracle to (0 = no cap)
+    pub max_value_managed: u64,
+    /// May pause the oracle (all zeros = none)
+    pub guardian: [u8; 32],
+    /// Protocols the oracle may act on, bit i for protocol i (0 = all)
+    pub protocol_allowlist: u8,
+}
+
+impl Default for InitializeData {
+    fn default() -> Self {
+        Self {
+            initial_risk_score: 50, // Default medium risk
+            max_acceptable_risk: 100,
+            min_update_interval_secs: 0,
+            fee_bps: 0,
+            fee_treasury: [0u8; 32],
+            max_value_managed: 0,
+            guardian: [0u8; 32],
+            protocol_allowlist: 0,
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for InitializeData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        // Older clients send no data
+        if data.is_empty() {
+            return Ok(Self::default());
+        }
+
+        if data.len() < 6 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let initial_risk_score = data[0];
+        let max_acceptable_risk = data[1];
+        if initial_risk_score > 100 || max_acceptable_risk > 100 {
+            return Err(OracleError::InvalidRiskScore.into());
+        }
+
+        // Clients without fees omit the trailer
+        let (fee_bps, fee_treasury) = match data.get(6..40) {
+            Some(fee) => (u16::from_le_bytes([fee[0], fee[1]]), fee[2..34].try_into().unwrap()),
+            None => (0, [0u8; 32]),
+        };
+        if fee_bps > OracleState::MAX_FEE_BPS {
+            return Err(OracleError::InvalidConfig.into());
+        }
+        let max_value_managed = match data.get(40..48) {
+            Some(cap) => u64::from_le_bytes(cap.try_into().unwrap()),
+            None => 0,
+        };
+        let guardian = match data.get(48..80) {
+            Some(guardian) => guardian.try_into().unwrap(),
+            None => [0u8; 32],
+        };
+        let protocol_allowlist = data.get(80).copied().unwrap_or(0);
+
+        Ok(Self {
+            initial_risk_score,
+            max_acceptable_risk,
+            min_update_interval_secs: u32::from_le_bytes(data[2..6].try_into().unwrap()),
+            fee_bps,
+            fee_treasury,
+            max_value_managed,
+            guardian,
+            protocol_allowlist,
+        })
+    }
+}
+
+impl InitializeData {
+    /// Write the initial settings into a fresh oracle
+    pub fn apply(&self, state: &mut OracleState) {
+        state.risk_score = self.initial_risk_score;
+        state.set_max_acceptable_risk(self.max_acceptable_risk);
+        state.set_min_update_interval_secs(self.min_update_interval_secs);
+        state.set_fee_bps(self.fee_bps);
+        state.fee_destination = self.fee_treasury;
+        state.set_max_value_managed(self.max_value_managed);
+        state.guardian = self.guardian;
+        state.set_protocol_allowlist(self.protocol_allowlist);
+    }
+}
+
 /// Initialize instruction
 pub struct Initialize<'a> {
     pub accounts: InitializeAccounts<'a>,
+    pub data: InitializeData,
 }
 
 impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for Initialize<'a> {
     type Error = ProgramError;
 
-    fn try_from((_data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
         let accounts = InitializeAccounts::try_from(accounts)?;
-        Ok(Self { accounts })
+        let data = InitializeData::try_from(data)?;
+        Ok(Self { accounts, data })
     }
 }
 
 impl<'a> Initialize<'a> {
-    pub fn process(&self) -> ProgramResult {
-        let mut data = self.accounts.oracle.try_borrow_mut()?;
-        let state = OracleState::from_bytes_mut(&mut data)?;
+    pub fn process(&self, program_id: &Address) -> ProgramResult {
+        self.accounts.initialize_oracle(program_id, |state| self.data.apply(state))
+    }
+}
+
+impl<'a> InitializeAccounts<'a> {
+    /// Create (if needed) and initialize the oracle PDA
+    ///
+    /// `configure` writes the initial settings after the defaults, within
+    /// the same instruction, so the oracle is never live half-configured.
+    pub(crate) fn initialize_oracle(
+        &self,
+        program_id: &Address,
+        configure: impl FnOnce(&mut OracleState),
+    ) -> ProgramResult {
+        // Oracle must be the PDA for this authority
+        let bump = OracleState::verify_address(
+            self.oracle.address(),
+            self.authority.address(),
+            program_id,
+        )?;
 
-        // Check not already initialized
-        if state.is_initialized != 0 {
+        // Create the account if the program doesn't own it yet
+        if !self.oracle.owned_by(program_id) {
+            self.create_oracle_account(program_id, bump)?;
+        }
+
+        // Oracle must be owned by this program
+        if !self.oracle.owned_by(program_id) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        let mut data = self.oracle.try_borrow_mut()?;
+        let state = OracleState::from_bytes_mut_unchecked(&mut data)?;
+
+        // Check not already initialized (or holding another account type)
+        if state.version != 0 {
             return Err(ProgramError::AccountAlreadyInitialized);
         }
 
-        // Initialize state
-        state.is_initialized = 1;
-        state.authority.copy_from_slice(self.accounts.authority.address().as_ref());
+        // Initialize state, writing the discriminator
+        state.version = OracleState::DISCRIMINATOR;
+        state.set(status::INITIALIZED);
+        state.authority.copy_from_slice(self.authority.address().as_ref());
         state.best_protocol = 0;
         state.set_current_apy_bps(0);
-        state.risk_score = 50; // Default medium risk
         state.set_last_update(0);
         state.set_total_value_managed(0);
+        state.set_created_at(Clock::get()?.unix_timestamp);
+        state.set_stale_after_secs(OracleState::STALE_AFTER_SECS as u32);
+        state.set_bump(bump);
+        state.set_seed_authority(self.authority.address());
+        configure(state);
 
         Ok(())
     }
+
+    /// Create the oracle PDA, rent-exempt and owned by this program
+    ///
+    /// Anyone can transfer lamports to the PDA ahead of time, which makes
+    /// `CreateAccount` fail, so a pre-funded account is topped up,
+    /// allocated, and assigned instead.
+    fn create_oracle_account(&self, program_id: &Address, bump: u8) -> ProgramResult {
+        let oracle = self.oracle;
+        let authority = self.authority;
+        let rent_exempt = Rent::get()?.try_minimum_balance(OracleState::LEN)?;
+
+        let bump = [bump];
+        let seeds = [
+            Seed::from(OracleState::SEED_PREFIX),
+            Seed::from(authority.address().as_ref()),
+            Seed::from(bump.as_slice()),
+        ];
+        let signers = [Signer::from(&seeds[..])];
+
+        let lamports = oracle.lamports();
+        if lamports == 0 {
+            return CreateAccount {
+                from: authority,
+                to: oracle,
+                lamports: rent_exempt,
+                space: OracleState::LEN as u64,
+                owner: program_id,
+            }
+            .invoke_signed(&signers);
+        }
+
+        if lamports < rent_exempt {
+            Transfer {
+                from: authority,
+                to: oracle,
+                lamports: rent_exempt - lamports,
+            }
+            .invoke()?;
+        }
+
+        Allocate {
+            account: oracle,
+            space: OracleState::LEN as u64,
+        }
+        .invoke_signed(&signers)?;
+
+        Assign {
+            account: oracle,
+            owner: program_id,
+        }
+        .invoke_signed(&signers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instructions::protocol;
+
+    #[test]
+    fn test_initialize_data() {
+        let mut data = [20u8, 60, 0, 0, 0, 0];
+        data[2..6].copy_from_slice(&30u32.to_le_bytes());
+        let config = InitializeData::try_from(&data[..]).unwrap();
+
+        let state = &mut OracleState::test_state();
+        config.apply(state);
+        assert_eq!(state.risk_score, 20);
+        assert_eq!(state.max_acceptable_risk(), 60);
+        assert_eq!(state.min_update_interval_secs(), 30);
+
+        // No data keeps the old defaults
+        let config = InitializeData::try_from(&[][..]).unwrap();
+        config.apply(state);
+        assert_eq!(state.risk_score, 50);
+        assert_eq!(state.max_acceptable_risk(), 100);
+        assert_eq!(state.min_update_interval_secs(), 0);
+        assert_eq!(state.fee_bps(), 0);
+        assert_eq!(state.max_value_managed(), 0);
+    }
+
+    #[test]
+    fn test_initialize_data_sets_value_cap() {
+        let mut data = [0u8; 48];
+        data[0] = 50;
+        data[1] = 100;
+        data[40..48].copy_from_slice(&10_000_000_000u64.to_le_bytes());
+        let config = InitializeData::try_from(&data[..]).unwrap();
+
+        let state = &mut OracleState::test_state();
+        config.apply(state);
+        assert_eq!(state.max_value_managed(), 10_000_000_000);
+
+        // Clients that stop after the fee leave it uncapped
+        let config = InitializeData::try_from(&data[..40]).unwrap();
+        config.apply(state);
+        assert_eq!(state.max_value_managed(), 0);
+    }
+
+    #[test]
+    fn test_initialize_data_sets_guardian() {
+        let mut data = [0u8; 80];
+        data[0] = 50;
+        data[1] = 100;
+        data[48..80].copy_from_slice(&[3u8; 32]);
+        let config = InitializeData::try_from(&data[..]).unwrap();
+
+        let state = &mut OracleState::test_state();
+        config.apply(state);
+        assert_eq!(state.guardian, [3u8; 32]);
+
+        // Without it there is none
+        let config = InitializeData::try_from(&data[..48]).unwrap();
+        config.apply(state);
+        assert_eq!(state.guardian, [0u8; 32]);
+    }
+
+    #[test]
+    fn test_initialize_data_sets_protocol_allowlist() {
+        let mut data = [0u8; 81];
+        data[0] = 50;
+        data[1] = 100;
+        data[80] = 1 << protocol::MARINADE | 1 << protocol::JITO;
+        let config = InitializeData::try_from(&data[..]).unwrap();
+
+        let state = &mut OracleState::test_state();
+        config.apply(state);
+        assert_eq!(state.require_protocol_allowed(protocol::MARINADE), Ok(()));
+        assert_eq!(
+            state.require_protocol_allowed(protocol::RAYDIUM_CPMM),
+            Err(OracleError::InvalidProtocol.into())
+        );
+
+        // Without it everything is allowed
+        let config = InitializeData::try_from(&data[..80]).unwrap();
+        config.apply(state);
+        assert_eq!(state.protocol_allowlist(), u8::MAX);
+    }
+
+    #[test]
+    fn test_initialize_data_sets_fee() {
+        let mut data = [0u8; 40];
+        data[0] = 50;
+        data[1] = 100;
+        data[6..8].copy_from_slice(&200u16.to_le_bytes());
+        data[8..40].copy_from_slice(&[4u8; 32]);
+        let config = InitializeData::try_from(&data[..]).unwrap();
+
+        let state = &mut OracleState::test_state();
+        config.apply(state);
+        assert_eq!(state.fee_bps(), 200);
+        assert_eq!(state.fee_destination, [4u8; 32]);
+
+        data[6..8].copy_from_slice(&(OracleState::MAX_FEE_BPS + 1).to_le_bytes());
+        assert_eq!(
+            InitializeData::try_from(&data[..]).err(),
+            Some(OracleError::InvalidConfig.into())
+        );
+    }
+
+    #[test]
+    fn test_check_initial_authority() {
+        let authority = Address::new_from_array([7u8; 32]);
+        let oracle = Address::new_from_array([8u8; 32]);
+        assert_eq!(check_initial_authority(&authority, &oracle), Ok(()));
+
+        let invalid: ProgramResult = Err(OracleError::InvalidAuthority.into());
+        let zero = Address::new_from_array([0u8; 32]);
+        assert_eq!(check_initial_authority(&zero, &oracle), invalid);
+        assert_eq!(check_initial_authority(&pinocchio_system::ID, &oracle), invalid);
+        assert_eq!(check_initial_authority(&oracle, &oracle), invalid);
+    }
+
+    #[test]
+    fn test_initialize_data_rejects_bad_risk() {
+        assert_eq!(
+            InitializeData::try_from(&[101u8, 100, 0, 0, 0, 0][..]).err(),
+            Some(OracleError::InvalidRiskScore.into())
+        );
+        assert_eq!(
+            InitializeData::try_from(&[50u8, 101, 0, 0, 0, 0][..]).err(),
+            Some(OracleError::InvalidRiskScore.into())
+        );
+        assert_eq!(
+            InitializeData::try_from(&[50u8][..]).err(),
+            Some(ProgramError::InvalidInstructionData)
+        );
+    }
 }
@@ -0,0 +1,167 @@
+//! Initialize With Config instruction
+//!
+//! Same as `Initialize`, but applies a full `OracleConfig` in the same
+//! instruction, so the oracle is never live with default settings while
+//! follow-up setters land. The whole config is validated before anything
+//! is written.
+
+use pinocchio::{AccountView, Address, ProgramResult};
+use solana_program_error::ProgramError;
+
+use super::InitializeAccounts;
+use crate::state::{OracleConfig, OracleState};
+use crate::error::OracleError;
+
+/// Instruction data for initializing with a config
+/// Layout: initial_risk_score (1) + config (`OracleConfig::LEN`) = 26 bytes
+pub struct InitializeWithConfigData {
+    /// Starting risk score (0-100)
+    pub initial_risk_score: u8,
+    /// Settings to apply
+    pub config: OracleConfig,
+}
+
+impl TryFrom<&[u8]> for InitializeWithConfigData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        let Some((&initial_risk_score, config)) = data.split_first() else {
+            return Err(ProgramError::InvalidInstructionData);
+        };
+
+        let data = Self {
+            initial_risk_score,
+            config: OracleConfig::try_from(config)?,
+        };
+        data.validate()?;
+        Ok(data)
+    }
+}
+
+impl InitializeWithConfigData {
+    /// Reject out-of-range or conflicting settings
+    pub fn validate(&self) -> Result<(), ProgramError> {
+        if self.initial_risk_score > 100 {
+            return Err(OracleError::InvalidRiskScore.into());
+        }
+
+        self.config.validate()?;
+
+        // Starting out riskier than allowed would pin the oracle there
+        if self.initial_risk_score > self.config.max_acceptable_risk {
+            return Err(OracleError::InvalidConfig.into());
+        }
+
+        Ok(())
+    }
+
+    /// Write the initial settings into a fresh oracle
+    pub fn apply(&self, state: &mut OracleState) {
+        state.risk_score = self.initial_risk_score;
+        self.config.apply(state);
+    }
+}
+
+/// Initialize With Config instruction
+pub struct InitializeWithConfig<'a> {
+    pub accounts: InitializeAccounts<'a>,
+    pub data: InitializeWithConfigData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for InitializeWithConfig<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = InitializeAccounts::try_from(accounts)?;
+        let data = InitializeWithConfigData::try_from(data)?;
+        Ok(Self { accounts, data })
+    }
+}
+
+impl<'a> InitializeWithConfig<'a> {
+    pub fn process(&self, program_id: &Address) -> ProgramResult {
+        self.accounts.initialize_oracle(program_id, |state| self.data.apply(state))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::{ProtocolCategory, RiskCurve};
+    use crate::state::ApyPrecision;
+
+    fn encode(initial_risk_score: u8, config: &OracleConfig) -> [u8; 1 + OracleConfig::LEN] {
+        let mut data = [0u8; 1 + OracleConfig::LEN];
+        data[0] = initial_risk_score;
+        data[1] = config.max_acceptable_risk;
+        data[2..6].copy_from_slice(&config.min_update_interval_secs.to_le_bytes());
+        data[6..8].copy_from_slice(&config.lot_size_bps.to_le_bytes());
+        data[8..10].copy_from_slice(&config.min_actionable_adjusted_apy_bps.to_le_bytes());
+        data[10..18].copy_from_slice(&config.max_drawdown_lamports.to_le_bytes());
+        data[18..20].copy_from_slice(&config.phase_one_fraction_bps.to_le_bytes());
+        data[20] = config.apy_precision as u8;
+        data[21..23].copy_from_slice(&config.stale_override_floor_bps.to_le_bytes());
+        for (byte, curve) in data[23..26].iter_mut().zip(config.risk_curves) {
+            *byte = curve as u8;
+        }
+        data
+    }
+
+    fn config() -> OracleConfig {
+        OracleConfig {
+            max_acceptable_risk: 60,
+            min_update_interval_secs: 30,
+            lot_size_bps: 500,
+            min_actionable_adjusted_apy_bps: 200,
+            max_drawdown_lamports: 1_000_000_000,
+            phase_one_fraction_bps: 5000,
+            apy_precision: ApyPrecision::CentiBps,
+            stale_override_floor_bps: 300,
+            risk_curves: [RiskCurve::Quadratic, RiskCurve::Stepwise, RiskCurve::Linear],
+        }
+    }
+
+    #[test]
+    fn test_initialize_with_full_config() {
+        let data = InitializeWithConfigData::try_from(&encode(40, &config())[..]).unwrap();
+        assert_eq!(data.config, config());
+
+        let state = &mut OracleState::test_state();
+        data.apply(state);
+
+        assert_eq!(state.risk_score, 40);
+        assert_eq!(state.max_acceptable_risk(), 60);
+        assert_eq!(state.min_update_interval_secs(), 30);
+        assert_eq!(state.lot_size_bps(), 500);
+        assert_eq!(state.min_actionable_adjusted_apy_bps(), 200);
+        assert_eq!(state.max_drawdown_lamports(), 1_000_000_000);
+        assert_eq!(state.phase_one_fraction_bps(), 5000);
+        assert_eq!(state.apy_precision(), ApyPrecision::CentiBps);
+        assert_eq!(state.stale_override_floor_bps(), 300);
+        assert_eq!(state.risk_curve(ProtocolCategory::Lp), RiskCurve::Quadratic);
+        assert_eq!(state.risk_curve(ProtocolCategory::Lending), RiskCurve::Stepwise);
+        assert_eq!(state.risk_curve(ProtocolCategory::Staking), RiskCurve::Linear);
+    }
+
+    #[test]
+    fn test_rejects_conflicting_config() {
+        // Starting risk above the accepted maximum
+        assert_eq!(
+            InitializeWithConfigData::try_from(&encode(70, &config())[..]).err(),
+            Some(OracleError::InvalidConfig.into())
+        );
+
+        // Phase one covering the whole move
+        let full_first_leg = OracleConfig { phase_one_fraction_bps: 10000, ..config() };
+        assert_eq!(
+            InitializeWithConfigData::try_from(&encode(40, &full_first_leg)[..]).err(),
+            Some(OracleError::InvalidConfig.into())
+        );
+
+        // Truncated blob
+        assert_eq!(
+            InitializeWithConfigData::try_from(&encode(40, &config())[..25]).err(),
+            Some(ProgramError::InvalidInstructionData)
+        );
+    }
+}
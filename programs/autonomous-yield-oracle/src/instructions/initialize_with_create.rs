@@ -0,0 +1,42 @@
+//! Initialize With Create instruction
+//!
+//! Creates the oracle PDA, rent-exempt and funded by the authority, and
+//! initializes it in the same handler. Unlike `Initialize`, which also
+//! initializes in place an account this program already owns, it refuses
+//! any account the program owns, so success means this very instruction
+//! created the oracle. The PDA is derived from the authority, so no one
+//! else can claim it first.
+//!
+//! Accounts and data: as `Initialize`.
+
+use pinocchio::{AccountView, Address, ProgramResult};
+use solana_program_error::ProgramError;
+
+use super::{InitializeAccounts, InitializeData};
+
+/// Initialize With Create instruction
+pub struct InitializeWithCreate<'a> {
+    pub accounts: InitializeAccounts<'a>,
+    pub data: InitializeData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for InitializeWithCreate<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = InitializeAccounts::try_from(accounts)?;
+        let data = InitializeData::try_from(data)?;
+        Ok(Self { accounts, data })
+    }
+}
+
+impl<'a> InitializeWithCreate<'a> {
+    pub fn process(&self, program_id: &Address) -> ProgramResult {
+        // Only an account this instruction creates
+        if self.accounts.oracle.owned_by(program_id) {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        self.accounts.initialize_oracle(program_id, |state| self.data.apply(state))
+    }
+}
@@ -0,0 +1,107 @@
+//! Oracle account checks shared by the instructions that act on it
+
+use pinocchio::{AccountView, Address, ProgramResult};
+use solana_program_error::ProgramError;
+
+use crate::state::OracleState;
+
+/// Check `oracle` is an initialized oracle of the current layout, owned by
+/// this program
+///
+/// Fails with `InvalidAccountOwner` for an account of another program, so
+/// an oracle-shaped account can't stand in for the real one.
+pub fn load_oracle(oracle: &AccountView, program_id: &Address) -> ProgramResult {
+    let data = oracle.try_borrow()?;
+    check_oracle_account(oracle.owned_by(program_id), &data)
+}
+
+/// Owner, length, discriminator and initialization checks of `load_oracle`
+pub fn check_oracle_account(owned_by_program: bool, data: &[u8]) -> ProgramResult {
+    if !owned_by_program {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+    OracleState::from_bytes(data)?.require_initialized()
+}
+
+/// As `load_oracle`, but accepting any known layout version, for read-only
+/// instructions; returns a copy of the state (see
+/// `OracleState::load_any_version`)
+pub fn load_oracle_any_version(
+    oracle: &AccountView,
+    program_id: &Address,
+) -> Result<OracleState, ProgramError> {
+    let data = oracle.try_borrow()?;
+    read_oracle_account(oracle.owned_by(program_id), &data)
+}
+
+/// Checks of `load_oracle_any_version`, returning the state read
+pub fn read_oracle_account(
+    owned_by_program: bool,
+    data: &[u8],
+) -> Result<OracleState, ProgramError> {
+    if !owned_by_program {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+    let state = OracleState::load_any_version(data)?;
+    state.require_initialized()?;
+    Ok(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::OracleError;
+    use crate::state::status;
+
+    #[test]
+    fn test_check_oracle_account() {
+        let mut buf = [0u8; OracleState::LEN];
+        buf[0] = OracleState::DISCRIMINATOR;
+        assert_eq!(check_oracle_account(true, &buf), Err(OracleError::NotInitialized.into()));
+
+        OracleState::from_bytes_mut(&mut buf).unwrap().set(status::INITIALIZED);
+        assert!(check_oracle_account(true, &buf).is_ok());
+
+        // The same bytes under another program
+        assert_eq!(check_oracle_account(false, &buf), Err(ProgramError::InvalidAccountOwner));
+
+        assert_eq!(
+            check_oracle_account(true, &buf[..OracleState::LEN - 1]),
+            Err(ProgramError::InvalidAccountData)
+        );
+
+        buf[0] = 0;
+        assert_eq!(check_oracle_account(true, &buf), Err(OracleError::NotInitialized.into()));
+    }
+
+    #[test]
+    fn test_read_oracle_account() {
+        // A version 1 account, not migrated yet
+        let mut v1 = [0u8; 69];
+        v1[0] = 1;
+        v1[1..33].copy_from_slice(&[7u8; 32]);
+        assert_eq!(
+            check_oracle_account(true, &v1),
+            Err(OracleError::MigrationRequired.into())
+        );
+        let state = read_oracle_account(true, &v1).unwrap();
+        assert_eq!(state.version, 1);
+        assert_eq!(state.authority, [7u8; 32]);
+
+        // The same bytes under another program
+        assert_eq!(
+            read_oracle_account(false, &v1).err(),
+            Some(ProgramError::InvalidAccountOwner)
+        );
+
+        // The current layout still needs the status bit
+        let mut buf = [0u8; OracleState::LEN];
+        buf[0] = OracleState::DISCRIMINATOR;
+        assert_eq!(
+            read_oracle_account(true, &buf).err(),
+            Some(OracleError::NotInitialized.into())
+        );
+        OracleState::from_bytes_mut(&mut buf).unwrap().set(status::INITIALIZED);
+        assert!(read_oracle_account(true, &buf).is_ok());
+    }
+}
@@ -0,0 +1,66 @@
+//! Lock Config instruction
+//!
+//! Freezes settings, e.g. ahead of an audit. Setting instructions fail with
+//! `ConfigLocked` until `UnlockConfig` completes its timelock; operational
+//! instructions are unaffected.
+
+use pinocchio::{AccountView, Address, ProgramResult};
+use solana_program_error::ProgramError;
+
+use crate::state::OracleState;
+use super::load_oracle;
+
+/// Accounts required for locking settings
+pub struct LockConfigAccounts<'a> {
+    /// The oracle account
+    pub oracle: &'a AccountView,
+    /// The authority (must sign)
+    pub authority: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for LockConfigAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [oracle, authority, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !authority.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self { oracle, authority })
+    }
+}
+
+/// Lock Config instruction
+pub struct LockConfig<'a> {
+    pub accounts: LockConfigAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for LockConfig<'a> {
+    type Error = ProgramError;
+
+    fn try_from((_data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = LockConfigAccounts::try_from(accounts)?;
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> LockConfig<'a> {
+    pub fn process(&self, program_id: &Address) -> ProgramResult {
+        // Program-owned, initialized oracle only
+        load_oracle(self.accounts.oracle, program_id)?;
+
+        let mut oracle_data = self.accounts.oracle.try_borrow_mut()?;
+        let state = OracleState::from_bytes_mut(&mut oracle_data)?;
+
+        // Verify initialized, then authority
+        state.require_authority(self.accounts.authority.address().as_ref())?;
+
+        state.lock_config();
+
+        Ok(())
+    }
+}
@@ -0,0 +1,101 @@
+//! Migrate instruction
+//!
+//! Upgrades an oracle created under an older state layout: grows the account
+//! to `OracleState::LEN`, tops its rent up from the authority, and fills the
+//! fields added since with their defaults, and the stored PDA bump with the
+//! canonical one. A no-op on current accounts.
+//!
+//! Version 1 oracles were created at keypair addresses, like the one on
+//! mainnet, so the oracle needn't be the authority's PDA. One that isn't
+//! keeps working, but can't sign CPIs: `verify_bump` refuses its seeds.
+
+use pinocchio::{
+    sysvars::{clock::Clock, rent::Rent, Sysvar},
+    AccountView, Address, ProgramResult,
+};
+use pinocchio_system::instructions::Transfer;
+use solana_program_error::ProgramError;
+
+use crate::state::OracleState;
+use super::load_oracle_any_version;
+
+/// Accounts required for migrating the oracle
+pub struct MigrateAccounts<'a> {
+    /// The oracle account
+    pub oracle: &'a AccountView,
+    /// The authority (must sign; pays any extra rent)
+    pub authority: &'a AccountView,
+    /// System program
+    pub system_program: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for MigrateAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [oracle, authority, system_program, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !authority.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self {
+            oracle,
+            authority,
+            system_program,
+        })
+    }
+}
+
+/// Migrate instruction
+pub struct Migrate<'a> {
+    pub accounts: MigrateAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for Migrate<'a> {
+    type Error = ProgramError;
+
+    fn try_from((_data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = MigrateAccounts::try_from(accounts)?;
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> Migrate<'a> {
+    pub fn process(&self, program_id: &Address) -> ProgramResult {
+        let oracle = self.accounts.oracle;
+
+        // Program-owned, initialized oracle of any version
+        let state = load_oracle_any_version(oracle, program_id)?;
+
+        // Verify initialized, then authority
+        state.require_authority(self.accounts.authority.address().as_ref())?;
+
+        if state.version == OracleState::VERSION {
+            return Ok(());
+        }
+
+        // Older layouts have no stored bump
+        let (_, bump) = OracleState::find_address(self.accounts.authority.address(), program_id);
+
+        // Grow the account, keeping it rent-exempt
+        oracle.resize(OracleState::LEN)?;
+        let rent_exempt = Rent::get()?.try_minimum_balance(OracleState::LEN)?;
+        if oracle.lamports() < rent_exempt {
+            Transfer {
+                from: self.accounts.authority,
+                to: oracle,
+                lamports: rent_exempt - oracle.lamports(),
+            }
+            .invoke()?;
+        }
+
+        let mut oracle_data = oracle.try_borrow_mut()?;
+        OracleState::migrate(&mut oracle_data, Clock::get()?.unix_timestamp)?;
+        OracleState::from_bytes_mut(&mut oracle_data)?.set_bump(bump);
+
+        Ok(())
+    }
+}
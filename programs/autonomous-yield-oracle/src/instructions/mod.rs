@@ -8,6 +8,40 @@ mod execute_swap;
 mod rebalance;
 mod publish_strategy;
 mod emergency_withdraw;
+mod verify;
+mod set_paused;
+mod transfer_authority;
+mod accept_authority;
+mod compute_realized_apy;
+mod lock_config;
+mod unlock_config;
+mod seed_history;
+mod initialize_with_config;
+mod health_check;
+mod close_oracle;
+mod set_max_priority_fee;
+mod get_state;
+mod pause_protocol_until;
+mod configure;
+mod compute_downside;
+mod propose_fee_destination;
+mod accept_fee_destination;
+mod deposit;
+mod compare_benchmark;
+mod migrate;
+mod rebalance_and_settle;
+mod record_pnl;
+mod require_nonces;
+mod require_upgrade_authority;
+mod query;
+mod reset_stats;
+mod initialize_with_create;
+mod observation;
+mod expect_accounts;
+mod load_oracle;
+
+#[cfg(test)]
+mod parser_fuzz;
 
 pub use initialize::*;
 pub use monitor_yields::*;
@@ -15,6 +49,37 @@ pub use execute_swap::*;
 pub use rebalance::*;
 pub use publish_strategy::*;
 pub use emergency_withdraw::*;
+pub use verify::*;
+pub use set_paused::*;
+pub use transfer_authority::*;
+pub use accept_authority::*;
+pub use compute_realized_apy::*;
+pub use lock_config::*;
+pub use unlock_config::*;
+pub use seed_history::*;
+pub use initialize_with_config::*;
+pub use health_check::*;
+pub use close_oracle::*;
+pub use set_max_priority_fee::*;
+pub use get_state::*;
+pub use pause_protocol_until::*;
+pub use configure::*;
+pub use compute_downside::*;
+pub use propose_fee_destination::*;
+pub use accept_fee_destination::*;
+pub use deposit::*;
+pub use compare_benchmark::*;
+pub use migrate::*;
+pub use rebalance_and_settle::*;
+pub use record_pnl::*;
+pub use require_nonces::*;
+pub use require_upgrade_authority::*;
+pub use query::*;
+pub use reset_stats::*;
+pub use initialize_with_create::*;
+pub use observation::*;
+pub use expect_accounts::*;
+pub use load_oracle::*;
 
 /// Instruction discriminators
 pub mod discriminator {
@@ -24,4 +89,32 @@ pub mod discriminator {
     pub const REBALANCE: u8 = 3;
     pub const PUBLISH_STRATEGY: u8 = 4;
     pub const EMERGENCY_WITHDRAW: u8 = 5;
+    pub const VERIFY: u8 = 6;
+    pub const SET_PAUSED: u8 = 7;
+    pub const TRANSFER_AUTHORITY: u8 = 8;
+    pub const ACCEPT_AUTHORITY: u8 = 9;
+    pub const COMPUTE_REALIZED_APY: u8 = 10;
+    pub const LOCK_CONFIG: u8 = 11;
+    pub const UNLOCK_CONFIG: u8 = 12;
+    pub const SEED_HISTORY: u8 = 13;
+    pub const INITIALIZE_WITH_CONFIG: u8 = 14;
+    pub const HEALTH_CHECK: u8 = 15;
+    pub const CLOSE_ORACLE: u8 = 16;
+    pub const SET_MAX_PRIORITY_FEE: u8 = 17;
+    pub const GET_STATE: u8 = 18;
+    pub const PAUSE_PROTOCOL_UNTIL: u8 = 19;
+    pub const CONFIGURE: u8 = 20;
+    pub const COMPUTE_DOWNSIDE: u8 = 21;
+    pub const PROPOSE_FEE_DESTINATION: u8 = 22;
+    pub const ACCEPT_FEE_DESTINATION: u8 = 23;
+    pub const DEPOSIT: u8 = 24;
+    pub const COMPARE_BENCHMARK: u8 = 25;
+    pub const MIGRATE: u8 = 26;
+    pub const REBALANCE_AND_SETTLE: u8 = 27;
+    pub const RECORD_PNL: u8 = 28;
+    pub const REQUIRE_NONCES: u8 = 29;
+    pub const REQUIRE_UPGRADE_AUTHORITY: u8 = 30;
+    pub const QUERY: u8 = 31;
+    pub const RESET_STATS: u8 = 32;
+    pub const INITIALIZE_WITH_CREATE: u8 = 33;
 }
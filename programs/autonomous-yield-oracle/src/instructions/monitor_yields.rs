@@ -3,35 +3,101 @@
 //! Updates the oracle with current yield data from protocols.
 //! The AI agent monitors yields off-chain and submits updates on-chain
 //! for transparent, auditable decision tracking.
+//!
+//! Several protocols can be reported in one call: data that is a multiple
+//! of `OBSERVATION_LEN` longer than one observation is read as a batch of
+//! plain observations, each for a different protocol.
+//!
+//! Each observation carries a nonce above the last accepted one, so a
+//! relayer can't replay an old update. Oracles migrated from before nonces
+//! take the legacy layout without one until `RequireNonces`.
 
-use pinocchio::{AccountView, ProgramResult};
+use pinocchio::{sysvars::clock::Clock, AccountView, Address, ProgramResult};
 use solana_program_error::ProgramError;
 
-use crate::state::OracleState;
+use crate::logging;
+use crate::math::{self, RiskCurve};
+use crate::scoring::{self, Observation, Source, UpdateDecision};
+use crate::state::{status, ApyPrecision, OracleState};
 use crate::error::OracleError;
+use super::{load_oracle, parse_apy_centi_bps, ObservationData};
+
+/// A supported protocol, as it appears on the wire and in oracle state
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ProtocolId {
+    RaydiumCpmm = 0,
+    JupiterRoute = 1,
+    Kamino = 2,
+    Marinade = 3,
+    Jito = 4,
+}
+
+impl ProtocolId {
+    /// Index into per-protocol arrays
+    pub fn index(self) -> usize {
+        self as usize
+    }
+}
+
+impl TryFrom<u8> for ProtocolId {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            protocol::RAYDIUM_CPMM => Ok(ProtocolId::RaydiumCpmm),
+            protocol::JUPITER_ROUTE => Ok(ProtocolId::JupiterRoute),
+            protocol::KAMINO => Ok(ProtocolId::Kamino),
+            protocol::MARINADE => Ok(ProtocolId::Marinade),
+            protocol::JITO => Ok(ProtocolId::Jito),
+            _ => Err(OracleError::InvalidProtocol.into()),
+        }
+    }
+}
+
+/// Index into per-protocol arrays of a raw protocol ID
+///
+/// Fails with `InvalidProtocol`, where indexing with the byte itself would
+/// panic and abort the transaction without saying why.
+pub fn protocol_index(protocol: u8) -> Result<usize, ProgramError> {
+    ProtocolId::try_from(protocol).map(ProtocolId::index)
+}
 
-/// Protocol identifiers
+/// Protocol identifiers as raw bytes (see `ProtocolId`)
 pub mod protocol {
-    pub const RAYDIUM_CPMM: u8 = 0;
-    pub const JUPITER_ROUTE: u8 = 1;
-    pub const KAMINO: u8 = 2;
-    pub const MARINADE: u8 = 3;
-    pub const JITO: u8 = 4;
+    use super::ProtocolId;
+
+    pub const RAYDIUM_CPMM: u8 = ProtocolId::RaydiumCpmm as u8;
+    pub const JUPITER_ROUTE: u8 = ProtocolId::JupiterRoute as u8;
+    pub const KAMINO: u8 = ProtocolId::Kamino as u8;
+    pub const MARINADE: u8 = ProtocolId::Marinade as u8;
+    pub const JITO: u8 = ProtocolId::Jito as u8;
+
+    /// Highest valid protocol ID
+    pub const MAX_PROTOCOL: u8 = JITO;
+
+    /// Number of protocol IDs
+    pub const COUNT: usize = MAX_PROTOCOL as usize + 1;
 }
 
+/// How far (seconds) an observation may run ahead of on-chain time
+pub const MAX_CLOCK_DRIFT_SECS: i64 = 60;
+
 /// Accounts required for monitoring yields
 pub struct MonitorYieldsAccounts<'a> {
     /// The oracle account to update
     pub oracle: &'a AccountView,
     /// The authority (AI agent's wallet)
     pub authority: &'a AccountView,
+    /// Clock sysvar
+    pub clock: &'a AccountView,
 }
 
 impl<'a> TryFrom<&'a [AccountView]> for MonitorYieldsAccounts<'a> {
     type Error = ProgramError;
 
     fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
-        let [oracle, authority, ..] = accounts else {
+        let [oracle, authority, clock, ..] = accounts else {
             return Err(ProgramError::NotEnoughAccountKeys);
         };
 
@@ -39,49 +105,269 @@ impl<'a> TryFrom<&'a [AccountView]> for MonitorYieldsAccounts<'a> {
             return Err(ProgramError::MissingRequiredSignature);
         }
 
-        Ok(Self { oracle, authority })
+        Ok(Self {
+            oracle,
+            authority,
+            clock,
+        })
     }
 }
 
+/// Size of one observation without the optional precise APY
+pub const OBSERVATION_LEN: usize = 20;
+
+/// Size of one legacy observation, without a nonce: just the common head
+pub const LEGACY_OBSERVATION_LEN: usize = ObservationData::LEN;
+
 /// Instruction data for monitor yields
-/// Layout: protocol (1) + apy_bps (2) + risk_score (1) + timestamp (8) = 12 bytes
+/// Layout: protocol (1) + apy_bps (2) + risk_score (1) + timestamp (8) +
+/// nonce (8) + apy_centi_bps (4, optional) + observed_liquidity (8,
+/// optional) = 32 bytes
+///
+/// The legacy layout has no nonce and no liquidity: the optional precise
+/// APY follows the timestamp, 16 bytes in all. Batched observations carry
+/// neither optional field.
+#[derive(Clone, Copy)]
 pub struct MonitorYieldsData {
-    /// Protocol ID (see protocol module)
-    pub protocol: u8,
+    /// Protocol observed
+    pub protocol: ProtocolId,
     /// APY in basis points (e.g., 1500 = 15.00%)
     pub apy_bps: u16,
     /// Risk score (0-100, lower is safer)
     pub risk_score: u8,
     /// Unix timestamp of this observation
     pub timestamp: i64,
+    /// APY in hundredths of a bps, used under `ApyPrecision::CentiBps`
+    /// (defaults to `apy_bps * 100` when omitted)
+    pub apy_centi_bps: u32,
+    /// Replay guard, above the last accepted nonce (`None` in the legacy
+    /// layout)
+    pub nonce: Option<u64>,
+    /// Liquidity (lamports) behind the yield, checked against
+    /// `min_liquidity` (0 when omitted)
+    pub observed_liquidity: u64,
 }
 
 impl TryFrom<&[u8]> for MonitorYieldsData {
     type Error = ProgramError;
 
     fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
-        if data.len() < 12 {
-            return Err(ProgramError::InvalidInstructionData);
-        }
+        Self::parse(data, false)
+    }
+}
+
+impl MonitorYieldsData {
+    /// Parse an observation in the legacy layout, without a nonce
+    pub fn try_from_legacy(data: &[u8]) -> Result<Self, ProgramError> {
+        Self::parse(data, true)
+    }
 
-        let risk_score = data[3];
-        if risk_score > 100 {
-            return Err(OracleError::InvalidRiskScore.into());
+    fn parse(data: &[u8], legacy: bool) -> Result<Self, ProgramError> {
+        let len = if legacy { LEGACY_OBSERVATION_LEN } else { OBSERVATION_LEN };
+        if data.len() < len {
+            return Err(OracleError::DataTooShort.into());
         }
 
+        let ObservationData { protocol, apy_bps, risk_score, timestamp } =
+            ObservationData::try_from(data)?;
+        let observed_liquidity = match data.get(len + 4..len + 12) {
+            Some(liquidity) if !legacy => u64::from_le_bytes(liquidity.try_into().unwrap()),
+            _ => 0,
+        };
+
         Ok(Self {
-            protocol: data[0],
-            apy_bps: u16::from_le_bytes([data[1], data[2]]),
+            protocol,
+            apy_bps,
             risk_score,
-            timestamp: i64::from_le_bytes(data[4..12].try_into().unwrap()),
+            timestamp,
+            apy_centi_bps: parse_apy_centi_bps(data, len, apy_bps),
+            nonce: (!legacy).then(|| u64::from_le_bytes(data[12..20].try_into().unwrap())),
+            observed_liquidity,
         })
     }
 }
 
+/// Risk-adjusted yield, in the same unit as `apy`
+///
+/// Higher risk = lower adjusted yield.
+/// Formula: adjusted_apy = apy * 100 / (100 + risk_score)
+///
+/// This is the default hyperbolic curve; `OracleState::risk_adjusted_apy`
+/// applies the curve configured for a protocol's category.
+pub fn risk_adjusted_apy(apy: u32, risk_score: u8) -> u32 {
+    math::risk_adjusted_apy(apy, risk_score, RiskCurve::Hyperbolic)
+}
+
+impl MonitorYieldsData {
+    /// This observation as scored by `scoring::evaluate_update`
+    pub fn observation(&self) -> Observation {
+        Observation {
+            source: Source::Monitor,
+            protocol: self.protocol,
+            apy_bps: self.apy_bps,
+            apy_centi_bps: self.apy_centi_bps,
+            risk_score: self.risk_score,
+            liquidity: self.observed_liquidity,
+        }
+    }
+
+    /// Reject observations dated in the future or before the last update
+    ///
+    /// `now` is the on-chain clock; a small drift allowance absorbs the gap
+    /// between the agent's clock and the cluster's.
+    pub fn validate_timestamp(&self, now: i64, last_update: i64) -> ProgramResult {
+        if self.timestamp > now.saturating_add(MAX_CLOCK_DRIFT_SECS) || self.timestamp < last_update {
+            return Err(OracleError::InvalidTimestamp.into());
+        }
+        Ok(())
+    }
+
+    /// Fail with `UpdateTooFrequent` within `min_update_interval_secs` of
+    /// the last update, unless the current data is stale
+    pub fn check_update_interval(&self, state: &OracleState) -> ProgramResult {
+        let since_last_update = self.timestamp.saturating_sub(state.last_update());
+
+        // Throttle aggressive pollers; stale data is always refreshed
+        if !state.is_stale(self.timestamp)
+            && since_last_update < state.min_update_interval_secs() as i64
+        {
+            return Err(OracleError::UpdateTooFrequent.into());
+        }
+        Ok(())
+    }
+
+    /// Apply this observation to the oracle state
+    ///
+    /// Every accepted call records the observation in the per-protocol
+    /// snapshot. Within `min_update_interval_secs` of the last update, fails
+    /// with `UpdateTooFrequent` unless the current data is stale; otherwise
+    /// `scoring::evaluate_update`, judged at the observation's timestamp,
+    /// decides whether it becomes the new best, is tracked only (history and
+    /// update time move forward), or is ignored. Protocols outside the
+    /// allowlist fail with `InvalidProtocol`.
+    pub fn apply(&self, state: &mut OracleState) -> ProgramResult {
+        self.check_update_interval(state)?;
+        self.select(state)
+    }
+
+    /// `apply` without the update interval
+    pub fn select(&self, state: &mut OracleState) -> ProgramResult {
+        state.require_protocol_allowed(self.protocol as u8)?;
+        let decision = scoring::evaluate_update(state, &self.observation(), self.timestamp)?;
+
+        // Keep the dashboard view current, whatever happens next
+        state.record_protocol_observation(self.protocol as u8, self.apy_bps, self.risk_score)?;
+
+        if decision == UpdateDecision::Ignore {
+            return Ok(());
+        }
+
+        state.push_apy_history(self.timestamp, self.apy_bps);
+
+        if decision == UpdateDecision::RefreshOnly {
+            state.set_last_update(self.timestamp);
+            return Ok(());
+        }
+
+        state.increment_decisions()?;
+        state.best_protocol = self.protocol as u8;
+        match state.apy_precision() {
+            ApyPrecision::Bps => state.set_current_apy_bps(self.apy_bps),
+            ApyPrecision::CentiBps => state.set_current_apy_centi_bps(self.apy_centi_bps),
+        }
+        state.update_apy_ema(self.apy_bps);
+        state.risk_score = self.risk_score;
+        state.set_best_liquidity(self.observed_liquidity);
+        state.set_last_update(self.timestamp);
+
+        logging::format_observation(
+            logging::MONITOR,
+            self.protocol as u8,
+            self.apy_bps,
+            self.apy_centi_bps,
+            self.risk_score,
+            self.timestamp,
+        )
+        .log();
+
+        Ok(())
+    }
+}
+
+/// The observations of one call, in order
+///
+/// Holds a single observation, or a batch of up to one per protocol.
+pub struct MonitorYieldsBatch {
+    pub observations: [Option<MonitorYieldsData>; protocol::COUNT],
+}
+
+impl TryFrom<&[u8]> for MonitorYieldsBatch {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        Self::parse(data, false)
+    }
+}
+
+impl MonitorYieldsBatch {
+    /// Parse observations in the legacy layout, without nonces
+    pub fn try_from_legacy(data: &[u8]) -> Result<Self, ProgramError> {
+        Self::parse(data, true)
+    }
+
+    fn parse(data: &[u8], legacy: bool) -> Result<Self, ProgramError> {
+        let mut observations = [None; protocol::COUNT];
+        let len = if legacy { LEGACY_OBSERVATION_LEN } else { OBSERVATION_LEN };
+
+        if data.len() <= len || !data.len().is_multiple_of(len) {
+            observations[0] = Some(MonitorYieldsData::parse(data, legacy)?);
+            return Ok(Self { observations });
+        }
+
+        // More observations than protocols must repeat one
+        if data.len() / len > protocol::COUNT {
+            return Err(OracleError::InvalidProtocol.into());
+        }
+
+        let mut seen = [false; protocol::COUNT];
+        for (slot, chunk) in observations.iter_mut().zip(data.chunks_exact(len)) {
+            let observation = MonitorYieldsData::parse(chunk, legacy)?;
+            if core::mem::replace(&mut seen[observation.protocol.index()], true) {
+                return Err(OracleError::InvalidProtocol.into());
+            }
+            *slot = Some(observation);
+        }
+
+        Ok(Self { observations })
+    }
+
+    /// Apply each observation in turn (see `MonitorYieldsData::apply`)
+    ///
+    /// Every timestamp is checked against `now` and the update time left by
+    /// the observation before it, and every nonce must be above the one
+    /// before it, failing with `StaleNonce`. The update interval throttles
+    /// calls, so only the first observation is held to it.
+    pub fn apply(&self, state: &mut OracleState, now: i64) -> ProgramResult {
+        for (i, observation) in self.observations.iter().flatten().enumerate() {
+            observation.validate_timestamp(now, state.last_update())?;
+            if let Some(nonce) = observation.nonce {
+                state.advance_nonce(nonce)?;
+            }
+            if i == 0 {
+                observation.apply(state)?;
+            } else {
+                observation.select(state)?;
+            }
+        }
+        Ok(())
+    }
+}
+
 /// Monitor Yields instruction
 pub struct MonitorYields<'a> {
     pub accounts: MonitorYieldsAccounts<'a>,
-    pub data: MonitorYieldsData,
+    /// Observations, parsed once the oracle's layout is known
+    pub data: &'a [u8],
 }
 
 impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for MonitorYields<'a> {
@@ -89,49 +375,34 @@ impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for MonitorYields<'a> {
 
     fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
         let accounts = MonitorYieldsAccounts::try_from(accounts)?;
-        let data = MonitorYieldsData::try_from(data)?;
         Ok(Self { accounts, data })
     }
 }
 
 impl<'a> MonitorYields<'a> {
-    pub fn process(&self) -> ProgramResult {
+    pub fn process(&self, program_id: &Address) -> ProgramResult {
+        // Program-owned, initialized oracle only
+        load_oracle(self.accounts.oracle, program_id)?;
+
         let mut oracle_data = self.accounts.oracle.try_borrow_mut()?;
         let state = OracleState::from_bytes_mut(&mut oracle_data)?;
 
-        // Verify initialized
-        if state.is_initialized == 0 {
-            return Err(OracleError::NotInitialized.into());
-        }
-
-        // Verify authority
-        if state.authority != *self.accounts.authority.address().as_ref() {
-            return Err(OracleError::InvalidAuthority.into());
-        }
-
-        // Calculate risk-adjusted yield
-        // Higher risk = lower adjusted yield
-        // Formula: adjusted_apy = apy * (100 - risk_score) / 100
-        let risk_multiplier = 100u32.saturating_sub(self.data.risk_score as u32);
-        let new_adjusted_apy = (self.data.apy_bps as u32 * risk_multiplier) / 100;
-        
-        let current_risk_multiplier = 100u32.saturating_sub(state.risk_score as u32);
-        let current_adjusted_apy = (state.current_apy_bps() as u32 * current_risk_multiplier) / 100;
+        // Verify initialized, then authority
+        state.require_authority(self.accounts.authority.address().as_ref())?;
 
-        // Update if this opportunity has better risk-adjusted yield
-        // OR if current data is stale (>1 hour old)
-        let is_stale = self.data.timestamp.saturating_sub(state.last_update()) > 3600;
-        let is_better = new_adjusted_apy > current_adjusted_apy;
+        // Refuse to act while paused
+        state.require_not_paused()?;
 
-        if is_better || is_stale {
-            state.best_protocol = self.data.protocol;
-            state.set_current_apy_bps(self.data.apy_bps);
-            state.risk_score = self.data.risk_score;
-            state.set_last_update(self.data.timestamp);
-            state.increment_decisions();
-        }
+        let batch = if state.is_set(status::LEGACY_OBSERVATIONS) {
+            MonitorYieldsBatch::try_from_legacy(self.data)?
+        } else {
+            MonitorYieldsBatch::try_from(self.data)?
+        };
 
-        Ok(())
+        // Don't trust submitted timestamps beyond the on-chain clock
+        let now = Clock::from_account_view(self.accounts.clock)?.unix_timestamp;
+        state.roll_decision_window(now);
+        batch.apply(state, now)
     }
 }
 
@@ -142,10 +413,639 @@ mod tests {
     #[test]
     fn test_risk_adjusted_yield() {
         // 15% APY with 20 risk score
-        // adjusted = 1500 * (100 - 20) / 100 = 1500 * 80 / 100 = 1200
+        // adjusted = 1500 * 100 / (100 + 20) = 150000 / 120 = 1250
         let apy: u32 = 1500;
         let risk: u32 = 20;
-        let adjusted = (apy * (100 - risk)) / 100;
-        assert_eq!(adjusted, 1200);
+        let adjusted = (apy * 100) / (100 + risk);
+        assert_eq!(adjusted, 1250);
+        assert_eq!(risk_adjusted_apy(1500, 20), 1250);
+
+        // Even the riskiest protocol keeps half its yield
+        assert_eq!(risk_adjusted_apy(1500, 100), 750);
+    }
+
+    #[test]
+    fn test_rejects_unknown_protocol_and_short_data() {
+        let mut data = [0u8; OBSERVATION_LEN];
+        data[0] = protocol::MAX_PROTOCOL;
+        assert!(MonitorYieldsData::try_from(&data[..]).is_ok());
+
+        data[0] = 5;
+        assert_eq!(
+            MonitorYieldsData::try_from(&data[..]).err(),
+            Some(OracleError::InvalidProtocol.into())
+        );
+
+        assert_eq!(
+            MonitorYieldsData::try_from(&data[..OBSERVATION_LEN - 1]).err(),
+            Some(OracleError::DataTooShort.into())
+        );
+
+        // The legacy layout stops after the timestamp
+        data[0] = protocol::MAX_PROTOCOL;
+        assert!(MonitorYieldsData::try_from_legacy(&data[..LEGACY_OBSERVATION_LEN]).is_ok());
+        assert_eq!(
+            MonitorYieldsData::try_from_legacy(&data[..LEGACY_OBSERVATION_LEN - 1]).err(),
+            Some(OracleError::DataTooShort.into())
+        );
+    }
+
+    #[test]
+    fn test_protocol_id_conversion() {
+        let ids = [
+            ProtocolId::RaydiumCpmm,
+            ProtocolId::JupiterRoute,
+            ProtocolId::Kamino,
+            ProtocolId::Marinade,
+            ProtocolId::Jito,
+        ];
+        assert_eq!(ids.len(), protocol::COUNT);
+
+        // Same bytes as the raw constants, in both directions
+        for (byte, id) in ids.into_iter().enumerate() {
+            assert_eq!(ProtocolId::try_from(byte as u8), Ok(id));
+            assert_eq!(id as u8, byte as u8);
+            assert_eq!(id.index(), byte);
+        }
+        assert_eq!(ProtocolId::Kamino as u8, protocol::KAMINO);
+
+        for byte in [protocol::MAX_PROTOCOL + 1, u8::MAX] {
+            assert_eq!(ProtocolId::try_from(byte), Err(OracleError::InvalidProtocol.into()));
+        }
+    }
+
+    #[test]
+    fn test_garbage_best_protocol_not_scored() {
+        let state = &mut OracleState::test_state();
+        state.best_protocol = 9;
+
+        let data = encode(protocol::KAMINO, 900, 10, 1_000, 1);
+        assert_eq!(
+            MonitorYieldsData::try_from(&data[..]).unwrap().select(state),
+            Err(OracleError::InvalidProtocol.into())
+        );
+        assert_eq!(state.protocol_apy_bps()[protocol::KAMINO as usize], 0);
+    }
+
+    #[test]
+    fn test_validate_timestamp() {
+        let observation = |timestamp| MonitorYieldsData {
+            protocol: ProtocolId::Jito,
+            apy_bps: 750,
+            risk_score: 10,
+            timestamp,
+            apy_centi_bps: 75_000,
+            nonce: None,
+            observed_liquidity: 0,
+        };
+        let now = 10_000;
+        let last_update = 9_000;
+
+        assert!(observation(now).validate_timestamp(now, last_update).is_ok());
+        assert!(observation(now + MAX_CLOCK_DRIFT_SECS).validate_timestamp(now, last_update).is_ok());
+        assert!(observation(last_update).validate_timestamp(now, last_update).is_ok());
+
+        // Future-dated
+        assert_eq!(
+            observation(now + MAX_CLOCK_DRIFT_SECS + 1).validate_timestamp(now, last_update),
+            Err(OracleError::InvalidTimestamp.into())
+        );
+
+        // Backwards
+        assert_eq!(
+            observation(last_update - 1).validate_timestamp(now, last_update),
+            Err(OracleError::InvalidTimestamp.into())
+        );
+    }
+
+    #[test]
+    fn test_sub_floor_observation_is_tracked_only() {
+        let state = &mut OracleState::test_state();
+        state.set_max_acceptable_risk(100);
+        state.set_min_actionable_adjusted_apy_bps(500);
+
+        // 400 * 80 / 100 = 320 adjusted, below the 500 floor
+        let observation = MonitorYieldsData {
+            protocol: ProtocolId::Kamino,
+            apy_bps: 400,
+            risk_score: 20,
+            timestamp: 1_000,
+            apy_centi_bps: 40_000,
+            nonce: None,
+            observed_liquidity: 0,
+        };
+        observation.apply(state).unwrap();
+        assert_eq!(state.last_update(), 1_000);
+        assert_eq!(state.apy_history().last(), Some((1_000, 400)));
+        assert_eq!(state.best_protocol, protocol::RAYDIUM_CPMM);
+        assert_eq!(state.current_apy_bps(), 0);
+        assert_eq!(state.decisions_count(), 0);
+
+        // 1000 * 80 / 100 = 800 adjusted clears the floor
+        let observation = MonitorYieldsData {
+            protocol: ProtocolId::Kamino,
+            apy_bps: 1000,
+            risk_score: 20,
+            timestamp: 1_060,
+            apy_centi_bps: 100_000,
+            nonce: None,
+            observed_liquidity: 0,
+        };
+        observation.apply(state).unwrap();
+        assert_eq!(state.best_protocol, protocol::KAMINO);
+        assert_eq!(state.current_apy_bps(), 1000);
+        assert_eq!(state.decisions_count(), 1);
+    }
+
+    #[test]
+    fn test_min_update_interval() {
+        let state = &mut OracleState::test_state();
+        state.set_max_acceptable_risk(100);
+        state.set_min_update_interval_secs(300);
+
+        let observation = |apy_bps: u16, timestamp| MonitorYieldsData {
+            protocol: ProtocolId::Jito,
+            apy_bps,
+            risk_score: 10,
+            timestamp,
+            apy_centi_bps: apy_bps as u32 * 100,
+            nonce: None,
+            observed_liquidity: 0,
+        };
+        observation(700, 10_000).apply(state).unwrap();
+        assert_eq!(state.decisions_count(), 1);
+
+        // Inside the window, even with a better yield
+        assert_eq!(
+            observation(900, 10_299).apply(state),
+            Err(OracleError::UpdateTooFrequent.into())
+        );
+        assert_eq!(state.current_apy_bps(), 700);
+
+        observation(900, 10_300).apply(state).unwrap();
+        assert_eq!(state.current_apy_bps(), 900);
+        assert_eq!(state.decisions_count(), 2);
+
+        // An interval longer than the staleness window never blocks stale data
+        state.set_min_update_interval_secs(7_200);
+        state.set_stale_after_secs(OracleState::STALE_AFTER_SECS as u32);
+        observation(500, 10_300 + OracleState::STALE_AFTER_SECS + 1).apply(state).unwrap();
+        assert_eq!(state.current_apy_bps(), 500);
+    }
+
+    #[test]
+    fn test_high_precision_apy() {
+        // 2.0 bps current, then a 2.5 bps observation (apy_bps floors to 2)
+        let observation = |apy_centi_bps: u32, timestamp| MonitorYieldsData {
+            protocol: ProtocolId::Marinade,
+            apy_bps: (apy_centi_bps / 100) as u16,
+            risk_score: 0,
+            timestamp,
+            apy_centi_bps,
+            nonce: None,
+            observed_liquidity: 0,
+        };
+
+        for precision in [ApyPrecision::Bps, ApyPrecision::CentiBps] {
+            let state = &mut OracleState::test_state();
+            state.set_apy_precision(precision);
+
+            observation(200, 1_000).apply(state).unwrap();
+            observation(250, 1_060).apply(state).unwrap();
+
+            match precision {
+                // 2 bps vs 2 bps: not an improvement
+                ApyPrecision::Bps => {
+                    assert_eq!(state.current_apy(), 2);
+                    assert_eq!(state.decisions_count(), 1);
+                }
+                // 2.5 bps beats 2.0 bps
+                ApyPrecision::CentiBps => {
+                    assert_eq!(state.current_apy(), 250);
+                    assert_eq!(state.current_apy_bps(), 2);
+                    assert_eq!(state.decisions_count(), 2);
+
+                    // ...and 3.0 bps beats 2.5 bps, but not the other way round
+                    observation(300, 1_120).apply(state).unwrap();
+                    observation(250, 1_180).apply(state).unwrap();
+                    assert_eq!(state.current_apy(), 300);
+                    assert_eq!(state.decisions_count(), 3);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_max_acceptable_risk_skips_risky_opportunity() {
+        let state = &mut OracleState::test_state();
+        state.set_max_acceptable_risk(50);
+        state.set_stale_after_secs(OracleState::STALE_AFTER_SECS as u32);
+
+        let observation = |apy_bps: u16, risk_score, timestamp| MonitorYieldsData {
+            protocol: ProtocolId::Kamino,
+            apy_bps,
+            risk_score,
+            timestamp,
+            apy_centi_bps: apy_bps as u32 * 100,
+            nonce: None,
+            observed_liquidity: 0,
+        };
+        observation(100, 20, 1_000).apply(state).unwrap();
+        assert_eq!(state.decisions_count(), 1);
+
+        // 2000 * 10 / 100 = 200 adjusted beats 100 * 80 / 100 = 80, but
+        // risk 90 is ignored, even once the current data is stale
+        observation(2000, 90, 1_060).apply(state).unwrap();
+        observation(2000, 90, 1_000 + OracleState::STALE_AFTER_SECS + 1).apply(state).unwrap();
+        assert_eq!(state.current_apy_bps(), 100);
+        assert_eq!(state.risk_score, 20);
+        assert_eq!(state.decisions_count(), 1);
+        assert_eq!(state.last_update(), 1_000);
+
+        // At the limit is fine
+        observation(2000, 50, 1_120).apply(state).unwrap();
+        assert_eq!(state.current_apy_bps(), 2000);
+    }
+
+    #[test]
+    fn test_stale_override_floor() {
+        let state = &mut OracleState::test_state();
+        state.set_max_acceptable_risk(100);
+        state.set_stale_override_floor_bps(600);
+        state.set_stale_after_secs(OracleState::STALE_AFTER_SECS as u32);
+
+        let observation = |apy_bps: u16, timestamp| MonitorYieldsData {
+            protocol: ProtocolId::Marinade,
+            apy_bps,
+            risk_score: 0,
+            timestamp,
+            apy_centi_bps: apy_bps as u32 * 100,
+            nonce: None,
+            observed_liquidity: 0,
+        };
+        let stale = |last_update| last_update + OracleState::STALE_AFTER_SECS + 1;
+
+        observation(800, 1_000).apply(state).unwrap();
+        assert_eq!(state.decisions_count(), 1);
+
+        // Stale and better
+        let ts = stale(1_000);
+        observation(900, ts).apply(state).unwrap();
+        assert_eq!(state.current_apy_bps(), 900);
+        assert_eq!(state.decisions_count(), 2);
+
+        // Stale and slightly worse, but above the floor
+        let ts = stale(ts);
+        observation(700, ts).apply(state).unwrap();
+        assert_eq!(state.current_apy_bps(), 700);
+        assert_eq!(state.decisions_count(), 3);
+
+        // Stale and below the floor: kept, but no longer stale
+        let ts = stale(ts);
+        observation(100, ts).apply(state).unwrap();
+        assert_eq!(state.current_apy_bps(), 700);
+        assert_eq!(state.decisions_count(), 3);
+        assert_eq!(state.last_update(), ts);
+    }
+
+    #[test]
+    fn test_protocol_snapshot() {
+        let state = &mut OracleState::test_state();
+        state.set_max_acceptable_risk(50);
+
+        let observation = |protocol, apy_bps: u16, risk_score, timestamp| MonitorYieldsData {
+            protocol,
+            apy_bps,
+            risk_score,
+            timestamp,
+            apy_centi_bps: apy_bps as u32 * 100,
+            nonce: None,
+            observed_liquidity: 0,
+        };
+        observation(ProtocolId::Kamino, 900, 10, 1_000).apply(state).unwrap();
+        // Worse than the best, and too risky: recorded all the same
+        observation(ProtocolId::RaydiumCpmm, 300, 20, 1_060).apply(state).unwrap();
+        observation(ProtocolId::Jito, 2000, 80, 1_120).apply(state).unwrap();
+
+        assert_eq!(state.best_protocol, protocol::KAMINO);
+        assert_eq!(state.protocol_apy_bps(), [300, 0, 900, 0, 2000]);
+        assert_eq!(state.protocol_risk(), [20, 0, 10, 0, 80]);
+
+        // Kamino falls to 300 * 100 / 110 = 272 adjusted; 400 * 100 / 120 =
+        // 333 now beats it although it wouldn't have beaten the old 818
+        observation(ProtocolId::Kamino, 300, 10, 1_180).apply(state).unwrap();
+        assert_eq!(state.best_protocol, protocol::KAMINO);
+        observation(ProtocolId::RaydiumCpmm, 400, 20, 1_240).apply(state).unwrap();
+        assert_eq!(state.best_protocol, protocol::RAYDIUM_CPMM);
+        assert_eq!(state.current_apy_bps(), 400);
+    }
+
+    #[test]
+    fn test_paused_protocol_not_selected() {
+        let state = &mut OracleState::test_state();
+        state.set_max_acceptable_risk(100);
+        state.set_protocol_paused_until(protocol::KAMINO, 1_100).unwrap();
+
+        let observation = |protocol, apy_bps: u16, timestamp| MonitorYieldsData {
+            protocol,
+            apy_bps,
+            risk_score: 0,
+            timestamp,
+            apy_centi_bps: apy_bps as u32 * 100,
+            nonce: None,
+            observed_liquidity: 0,
+        };
+
+        // Within the pause: recorded, not selected; others are unaffected
+        observation(ProtocolId::Kamino, 900, 1_000).apply(state).unwrap();
+        assert_eq!(state.protocol_apy_bps()[protocol::KAMINO as usize], 900);
+        assert_eq!(state.decisions_count(), 0);
+        observation(ProtocolId::Marinade, 700, 1_050).apply(state).unwrap();
+        assert_eq!(state.best_protocol, protocol::MARINADE);
+
+        // Past the pause
+        observation(ProtocolId::Kamino, 900, 1_100).apply(state).unwrap();
+        assert_eq!(state.best_protocol, protocol::KAMINO);
+        assert_eq!(state.current_apy_bps(), 900);
+    }
+
+    #[test]
+    fn test_protocol_allowlist() {
+        let state = &mut OracleState::test_state();
+        state.set_max_acceptable_risk(100);
+        state.set_protocol_allowlist(1 << protocol::MARINADE | 1 << protocol::JITO);
+
+        let mut data = [0u8; 2 * OBSERVATION_LEN];
+        data[0..20].copy_from_slice(&encode(protocol::JITO, 700, 0, 1_000, 1));
+        data[20..40].copy_from_slice(&encode(protocol::MARINADE, 600, 0, 1_000, 2));
+        MonitorYieldsBatch::try_from(&data[..]).unwrap().apply(state, 1_000).unwrap();
+        assert_eq!(state.best_protocol, protocol::JITO);
+
+        // Not even recorded
+        let data = encode(protocol::RAYDIUM_CPMM, 2000, 0, 1_000, 3);
+        assert_eq!(
+            MonitorYieldsBatch::try_from(&data[..]).unwrap().apply(state, 1_000),
+            Err(OracleError::InvalidProtocol.into())
+        );
+        assert_eq!(state.protocol_apy_bps()[protocol::RAYDIUM_CPMM as usize], 0);
+    }
+
+    fn encode(
+        protocol: u8,
+        apy_bps: u16,
+        risk_score: u8,
+        timestamp: i64,
+        nonce: u64,
+    ) -> [u8; OBSERVATION_LEN] {
+        let mut data = [0u8; OBSERVATION_LEN];
+        data[0] = protocol;
+        data[1..3].copy_from_slice(&apy_bps.to_le_bytes());
+        data[3] = risk_score;
+        data[4..12].copy_from_slice(&timestamp.to_le_bytes());
+        data[12..20].copy_from_slice(&nonce.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn test_batch_of_three() {
+        let state = &mut OracleState::test_state();
+        state.set_max_acceptable_risk(100);
+        state.set_min_update_interval_secs(300);
+
+        let mut data = [0u8; 3 * OBSERVATION_LEN];
+        data[0..20].copy_from_slice(&encode(protocol::RAYDIUM_CPMM, 500, 20, 1_000, 1));
+        data[20..40].copy_from_slice(&encode(protocol::KAMINO, 900, 10, 1_000, 2));
+        data[40..60].copy_from_slice(&encode(protocol::JITO, 700, 0, 1_001, 3));
+        let batch = MonitorYieldsBatch::try_from(&data[..]).unwrap();
+        assert_eq!(batch.observations.iter().flatten().count(), 3);
+
+        batch.apply(state, 1_001).unwrap();
+
+        // 900 * 100 / 110 = 818 adjusted beats 416 and 700
+        assert_eq!(state.best_protocol, protocol::KAMINO);
+        assert_eq!(state.current_apy_bps(), 900);
+        assert_eq!(state.protocol_apy_bps(), [500, 0, 900, 0, 700]);
+        assert_eq!(state.protocol_risk(), [20, 0, 10, 0, 0]);
+
+        // The next call is still throttled
+        let data = encode(protocol::MARINADE, 2000, 0, 1_100, 4);
+        assert_eq!(
+            MonitorYieldsBatch::try_from(&data[..]).unwrap().apply(state, 1_100),
+            Err(OracleError::UpdateTooFrequent.into())
+        );
+    }
+
+    #[test]
+    fn test_switch_margin() {
+        let state = &mut OracleState::test_state();
+        state.set_max_acceptable_risk(100);
+        state.set_switch_margin_bps(10);
+
+        let observe = |state: &mut OracleState, protocol, apy_bps, timestamp| {
+            let data = encode(protocol, apy_bps, 0, timestamp, timestamp as u64);
+            MonitorYieldsBatch::try_from(&data[..]).unwrap().apply(state, timestamp)
+        };
+
+        observe(state, protocol::KAMINO, 1500, 1_000).unwrap();
+        assert_eq!(state.best_protocol, protocol::KAMINO);
+
+        // 1 bps better isn't worth a switch
+        observe(state, protocol::JITO, 1501, 1_100).unwrap();
+        assert_eq!(state.best_protocol, protocol::KAMINO);
+        assert_eq!(state.decisions_count(), 1);
+
+        // 10 bps better is
+        observe(state, protocol::JITO, 1510, 1_200).unwrap();
+        assert_eq!(state.best_protocol, protocol::JITO);
+        assert_eq!(state.current_apy_bps(), 1510);
+        assert_eq!(state.decisions_count(), 2);
+
+        // The best protocol's own updates aren't held to the margin
+        observe(state, protocol::JITO, 1512, 1_300).unwrap();
+        assert_eq!(state.current_apy_bps(), 1512);
+        assert_eq!(state.decisions_count(), 3);
+    }
+
+    #[test]
+    fn test_rejects_implausible_apy() {
+        let state = &mut OracleState::test_state();
+        state.set_max_acceptable_risk(100);
+        state.set_max_apy_bps(20_000);
+
+        let data = encode(protocol::KAMINO, 20_000, 10, 1_000, 1);
+        MonitorYieldsBatch::try_from(&data[..]).unwrap().apply(state, 1_000).unwrap();
+        assert_eq!(state.best_protocol, protocol::KAMINO);
+        assert_eq!(state.current_apy_bps(), 20_000);
+
+        // 655% isn't tracked, let alone selected
+        let data = encode(protocol::JITO, u16::MAX, 0, 5_000, 2);
+        assert_eq!(
+            MonitorYieldsBatch::try_from(&data[..]).unwrap().apply(state, 5_000),
+            Err(OracleError::ImplausibleApy.into())
+        );
+        assert_eq!(state.protocol_apy_bps()[protocol::JITO as usize], 0);
+        assert_eq!(state.best_protocol, protocol::KAMINO);
+    }
+
+    #[test]
+    fn test_batch_rejects_duplicate_protocol() {
+        let mut data = [0u8; 3 * OBSERVATION_LEN];
+        data[0..20].copy_from_slice(&encode(protocol::RAYDIUM_CPMM, 500, 20, 1_000, 1));
+        data[20..40].copy_from_slice(&encode(protocol::KAMINO, 900, 10, 1_000, 2));
+        data[40..60].copy_from_slice(&encode(protocol::RAYDIUM_CPMM, 700, 0, 1_001, 3));
+        assert_eq!(
+            MonitorYieldsBatch::try_from(&data[..]).err(),
+            Some(OracleError::InvalidProtocol.into())
+        );
+
+        // More observations than there are protocols
+        let data = [0u8; (protocol::COUNT + 1) * OBSERVATION_LEN];
+        assert_eq!(
+            MonitorYieldsBatch::try_from(&data[..]).err(),
+            Some(OracleError::InvalidProtocol.into())
+        );
+    }
+
+    #[test]
+    fn test_single_observation_is_not_a_batch() {
+        // With and without the precise APY
+        let mut data = [0u8; 24];
+        data[..20].copy_from_slice(&encode(protocol::JITO, 700, 0, 1_000, 1));
+        data[20..24].copy_from_slice(&70_050u32.to_le_bytes());
+
+        for len in [OBSERVATION_LEN, 24] {
+            let batch = MonitorYieldsBatch::try_from(&data[..len]).unwrap();
+            let mut observations = batch.observations.iter().flatten();
+            assert_eq!(observations.next().map(|o| o.protocol), Some(ProtocolId::Jito));
+            assert!(observations.next().is_none());
+        }
+        let batch = MonitorYieldsBatch::try_from(&data[..]).unwrap();
+        assert_eq!(batch.observations[0].unwrap().apy_centi_bps, 70_050);
+    }
+
+    #[test]
+    fn test_parse_apy_centi_bps() {
+        let mut data = [0u8; 24];
+        data[1..3].copy_from_slice(&3u16.to_le_bytes());
+        assert_eq!(MonitorYieldsData::try_from(&data[..20]).unwrap().apy_centi_bps, 300);
+
+        data[20..24].copy_from_slice(&250u32.to_le_bytes());
+        assert_eq!(MonitorYieldsData::try_from(&data[..]).unwrap().apy_centi_bps, 250);
+
+        // Right after the timestamp in the legacy layout
+        let mut data = [0u8; 16];
+        data[1..3].copy_from_slice(&3u16.to_le_bytes());
+        data[12..16].copy_from_slice(&250u32.to_le_bytes());
+        assert_eq!(MonitorYieldsData::try_from_legacy(&data[..]).unwrap().apy_centi_bps, 250);
+    }
+
+    #[test]
+    fn test_min_liquidity() {
+        let state = &mut OracleState::test_state();
+        state.set_max_acceptable_risk(100);
+        state.set_min_liquidity(5_000_000);
+
+        // Liquidity follows the precise APY
+        let observe = |state: &mut OracleState, apy_bps: u16, liquidity: u64, nonce| {
+            let mut data = [0u8; 32];
+            data[..OBSERVATION_LEN]
+                .copy_from_slice(&encode(protocol::KAMINO, apy_bps, 0, 1_000, nonce));
+            data[20..24].copy_from_slice(&(apy_bps as u32 * 100).to_le_bytes());
+            data[24..32].copy_from_slice(&liquidity.to_le_bytes());
+            let batch = MonitorYieldsBatch::try_from(&data[..])?;
+            assert_eq!(batch.observations[0].unwrap().observed_liquidity, liquidity);
+            batch.apply(state, 1_000)
+        };
+
+        // A high yield on a thin pool is tracked, not selected
+        observe(state, 3000, 4_999_999, 1).unwrap();
+        assert_eq!(state.current_apy_bps(), 0);
+        assert_eq!(state.decisions_count(), 0);
+        assert_eq!(state.protocol_apy_bps()[protocol::KAMINO as usize], 3000);
+
+        // A deep enough one is selected, and its liquidity shown
+        observe(state, 900, 5_000_000, 2).unwrap();
+        assert_eq!(state.best_protocol, protocol::KAMINO);
+        assert_eq!(state.current_apy_bps(), 900);
+        assert_eq!(state.best_liquidity(), 5_000_000);
+
+        // Without it, liquidity reads as unreported
+        let data = encode(protocol::KAMINO, 900, 0, 1_000, 1);
+        assert_eq!(MonitorYieldsData::try_from(&data[..]).unwrap().observed_liquidity, 0);
+    }
+
+    #[test]
+    fn test_batch_under_min_liquidity() {
+        let state = &mut OracleState::test_state();
+        state.set_max_acceptable_risk(100);
+        state.set_min_liquidity(5_000_000);
+
+        // Batched observations report no liquidity, so none is selected, but
+        // none fails the others either
+        let mut data = [0u8; 3 * OBSERVATION_LEN];
+        data[0..20].copy_from_slice(&encode(protocol::RAYDIUM_CPMM, 500, 20, 1_000, 1));
+        data[20..40].copy_from_slice(&encode(protocol::KAMINO, 900, 10, 1_000, 2));
+        data[40..60].copy_from_slice(&encode(protocol::JITO, 700, 0, 1_001, 3));
+        MonitorYieldsBatch::try_from(&data[..]).unwrap().apply(state, 1_001).unwrap();
+
+        assert_eq!(state.best_protocol, protocol::RAYDIUM_CPMM);
+        assert_eq!(state.current_apy_bps(), 0);
+        assert_eq!(state.decisions_count(), 0);
+        assert_eq!(state.protocol_apy_bps(), [500, 0, 900, 0, 700]);
+        assert_eq!(state.last_update(), 1_001);
+        assert_eq!(state.last_nonce(), 3);
+    }
+
+    #[test]
+    fn test_nonce_must_increase() {
+        let state = &mut OracleState::test_state();
+        state.set_max_acceptable_risk(100);
+
+        let observe = |state: &mut OracleState, timestamp, nonce| {
+            let data = encode(protocol::KAMINO, 900, 10, timestamp, nonce);
+            MonitorYieldsBatch::try_from(&data[..]).unwrap().apply(state, timestamp)
+        };
+
+        // In order
+        observe(state, 1_000, 1).unwrap();
+        observe(state, 1_060, 2).unwrap();
+        assert_eq!(state.last_nonce(), 2);
+
+        // A replay, or an older nonce, however fresh the data
+        for nonce in [2, 1, 0] {
+            assert_eq!(observe(state, 1_120, nonce), Err(OracleError::StaleNonce.into()));
+        }
+        assert_eq!(state.last_nonce(), 2);
+
+        // Nonces may skip ahead
+        observe(state, 1_120, 10).unwrap();
+        assert_eq!(state.last_nonce(), 10);
+
+        // Within a batch too
+        let mut data = [0u8; 2 * OBSERVATION_LEN];
+        data[0..20].copy_from_slice(&encode(protocol::JITO, 700, 0, 1_180, 12));
+        data[20..40].copy_from_slice(&encode(protocol::MARINADE, 700, 0, 1_180, 11));
+        assert_eq!(
+            MonitorYieldsBatch::try_from(&data[..]).unwrap().apply(state, 1_180),
+            Err(OracleError::StaleNonce.into())
+        );
+    }
+
+    #[test]
+    fn test_legacy_layout_has_no_nonce() {
+        let state = &mut OracleState::test_state();
+        state.set_max_acceptable_risk(100);
+
+        // Two legacy observations, the length of one with a precise APY
+        let mut data = [0u8; 2 * LEGACY_OBSERVATION_LEN];
+        data[..12].copy_from_slice(&encode(protocol::KAMINO, 900, 10, 1_000, 0)[..12]);
+        data[12..].copy_from_slice(&encode(protocol::JITO, 700, 0, 1_000, 0)[..12]);
+        let batch = MonitorYieldsBatch::try_from_legacy(&data[..]).unwrap();
+        assert_eq!(batch.observations.iter().flatten().count(), 2);
+        assert!(batch.observations.iter().flatten().all(|o| o.nonce.is_none()));
+
+        batch.apply(state, 1_000).unwrap();
+        assert_eq!(state.best_protocol, protocol::KAMINO);
+        assert_eq!(state.last_nonce(), 0);
     }
 }
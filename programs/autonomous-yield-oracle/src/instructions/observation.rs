@@ -0,0 +1,111 @@
+//! Observation data
+//!
+//! The fields `MonitorYields` and `PublishStrategy` data both start with,
+//! parsed and validated in one place so the two can't drift apart.
+
+use solana_program_error::ProgramError;
+
+use crate::error::OracleError;
+use super::ProtocolId;
+
+/// The common head of an observation
+/// Layout: protocol (1) + apy_bps (2) + risk_score (1) + timestamp (8) =
+/// 12 bytes
+///
+/// Parsing fails with `DataTooShort`, `InvalidProtocol`, then
+/// `InvalidRiskScore`, checked in that order; bytes past the head are left
+/// to the caller.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ObservationData {
+    /// Protocol observed
+    pub protocol: ProtocolId,
+    /// APY in basis points (e.g., 1500 = 15.00%)
+    pub apy_bps: u16,
+    /// Risk score (0-100, lower is safer)
+    pub risk_score: u8,
+    /// Unix timestamp of the observation
+    pub timestamp: i64,
+}
+
+impl ObservationData {
+    /// Size of the common head
+    pub const LEN: usize = 12;
+}
+
+impl TryFrom<&[u8]> for ObservationData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() < Self::LEN {
+            return Err(OracleError::DataTooShort.into());
+        }
+
+        let protocol = ProtocolId::try_from(data[0])?;
+
+        let risk_score = data[3];
+        if risk_score > 100 {
+            return Err(OracleError::InvalidRiskScore.into());
+        }
+
+        Ok(Self {
+            protocol,
+            apy_bps: u16::from_le_bytes([data[1], data[2]]),
+            risk_score,
+            timestamp: i64::from_le_bytes(data[4..12].try_into().unwrap()),
+        })
+    }
+}
+
+/// Parse the optional high-precision APY at `offset`
+pub(crate) fn parse_apy_centi_bps(data: &[u8], offset: usize, apy_bps: u16) -> u32 {
+    match data.get(offset..offset + 4) {
+        Some(apy) => u32::from_le_bytes(apy.try_into().unwrap()),
+        None => apy_bps as u32 * 100,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instructions::{protocol, MonitorYieldsData, PublishStrategyData, OBSERVATION_LEN};
+
+    #[test]
+    fn test_instructions_reject_malformed_heads_alike() {
+        let mut valid = [0u8; OBSERVATION_LEN];
+        valid[0] = protocol::KAMINO;
+        valid[1..3].copy_from_slice(&900u16.to_le_bytes());
+        valid[3] = 100;
+
+        let mut bad_protocol = valid;
+        bad_protocol[0] = protocol::MAX_PROTOCOL + 1;
+        let mut bad_risk = valid;
+        bad_risk[3] = 101;
+        let mut bad_both = bad_protocol;
+        bad_both[3] = 101;
+
+        let cases = [
+            (&valid[..0], OracleError::DataTooShort),
+            (&valid[..ObservationData::LEN - 1], OracleError::DataTooShort),
+            (&bad_protocol[..], OracleError::InvalidProtocol),
+            (&bad_risk[..], OracleError::InvalidRiskScore),
+            (&bad_both[..], OracleError::InvalidProtocol),
+        ];
+        for (data, error) in cases {
+            let error = Some(ProgramError::from(error));
+            assert_eq!(ObservationData::try_from(data).err(), error);
+            assert_eq!(MonitorYieldsData::try_from(data).err(), error);
+            assert_eq!(MonitorYieldsData::try_from_legacy(data).err(), error);
+            assert_eq!(PublishStrategyData::try_from(data).err(), error);
+        }
+
+        // ...and parse a valid head the same way
+        let head = ObservationData::try_from(&valid[..]).unwrap();
+        let monitored = MonitorYieldsData::try_from(&valid[..]).unwrap();
+        let published = PublishStrategyData::try_from(&valid[..]).unwrap();
+        assert_eq!(head.protocol, ProtocolId::Kamino);
+        assert_eq!((monitored.protocol, monitored.apy_bps), (head.protocol, head.apy_bps));
+        assert_eq!((monitored.risk_score, monitored.timestamp), (head.risk_score, head.timestamp));
+        assert_eq!((published.protocol, published.expected_apy_bps), (head.protocol, 900));
+        assert_eq!(published.risk_score, head.risk_score);
+    }
+}
@@ -0,0 +1,178 @@
+//! Fuzz-style tests for the instruction data parsers
+//!
+//! Feeds pseudo-random byte slices of every length up to `MAX_LEN` to each
+//! parser, which must parse them or return an error, never panic. Half the
+//! inputs are shaped to get past the first checks (a valid protocol byte,
+//! an allocation summing to 10000, ...), so the later reads are reached
+//! too. The generator is seeded, so every run sees the same inputs.
+
+use super::*;
+
+/// Longest input tried
+const MAX_LEN: usize = 64;
+
+/// Inputs tried per parser and length
+const ROUNDS: usize = 200;
+
+/// xorshift64: tiny, deterministic, and good enough to spread bytes
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    fn fill(&mut self, buf: &mut [u8]) {
+        for byte in buf {
+            *byte = self.next() as u8;
+        }
+    }
+}
+
+/// Run `parse` on random inputs, letting `shape` fix up every other one
+fn fuzz(seed: u64, shape: impl Fn(&mut Rng, &mut [u8]), parse: impl Fn(&[u8])) {
+    let mut rng = Rng(seed);
+    let mut buf = [0u8; MAX_LEN];
+    for len in 0..=MAX_LEN {
+        for round in 0..ROUNDS {
+            let data = &mut buf[..len];
+            rng.fill(data);
+            if round % 2 == 1 {
+                shape(&mut rng, data);
+            }
+            parse(data);
+        }
+    }
+}
+
+/// Set `data[index]` if the input is long enough to hold it
+fn put(data: &mut [u8], index: usize, value: u8) {
+    if let Some(byte) = data.get_mut(index) {
+        *byte = value;
+    }
+}
+
+/// A valid protocol byte at `index`
+fn shape_protocol(rng: &mut Rng, data: &mut [u8], index: usize) {
+    put(data, index, rng.below(protocol::COUNT as u64) as u8);
+}
+
+/// Valid protocol and risk bytes of a plain observation at `offset`
+fn shape_observation(rng: &mut Rng, data: &mut [u8], offset: usize) {
+    shape_protocol(rng, data, offset);
+    put(data, offset + 3, rng.below(101) as u8);
+}
+
+#[test]
+fn test_monitor_yields_parsers_never_panic() {
+    fuzz(
+        0x9E37_79B9_7F4A_7C15,
+        |rng, data| shape_observation(rng, data, 0),
+        |data| {
+            let _ = MonitorYieldsData::try_from(data);
+        },
+    );
+
+    // Batches repeat the observation layout
+    fuzz(
+        0xD1B5_4A32_D192_ED03,
+        |rng, data| {
+            for offset in (0..data.len()).step_by(OBSERVATION_LEN) {
+                shape_observation(rng, data, offset);
+            }
+        },
+        |data| {
+            let _ = MonitorYieldsBatch::try_from(data);
+        },
+    );
+
+    // ...and so does the legacy layout, without nonces
+    fuzz(
+        0x6A09_E667_F3BC_C909,
+        |rng, data| {
+            for offset in (0..data.len()).step_by(LEGACY_OBSERVATION_LEN) {
+                shape_observation(rng, data, offset);
+            }
+        },
+        |data| {
+            let _ = MonitorYieldsData::try_from_legacy(data);
+            let _ = MonitorYieldsBatch::try_from_legacy(data);
+        },
+    );
+}
+
+#[test]
+fn test_execute_swap_parser_never_panics() {
+    fuzz(
+        0x2545_F491_4F6C_DD1D,
+        |rng, data| {
+            // Jupiter, so route data is allowed, with a short route
+            put(data, 16, protocol::JUPITER_ROUTE);
+            put(data, 34, rng.below(MAX_LEN as u64) as u8);
+            put(data, 35, 0);
+        },
+        |data| {
+            let _ = ExecuteSwapData::try_from(data);
+        },
+    );
+}
+
+#[test]
+fn test_rebalance_parser_never_panics() {
+    fuzz(
+        0xBF58_476D_1CE4_E5B9,
+        |rng, data| {
+            // Everything in one bucket sums to 10000
+            let slots = data.len().min(2 * protocol::COUNT) / 2;
+            data[..2 * slots].fill(0);
+            if slots > 0 {
+                let slot = rng.below(slots as u64) as usize;
+                data[2 * slot..2 * slot + 2].copy_from_slice(&10000u16.to_le_bytes());
+            }
+        },
+        |data| {
+            let _ = RebalanceData::try_from(data);
+        },
+    );
+}
+
+#[test]
+fn test_publish_strategy_parser_never_panics() {
+    fuzz(
+        0x94D0_49BB_1331_11EB,
+        |rng, data| {
+            for offset in (0..data.len()).step_by(PublishStrategyData::LEN) {
+                shape_observation(rng, data, offset);
+            }
+        },
+        |data| {
+            let _ = PublishStrategyData::try_from(data);
+            let _ = PublishStrategyBatch::try_from(data);
+        },
+    );
+}
+
+#[test]
+fn test_shaped_inputs_do_parse() {
+    // Without this the shaping could silently stop reaching past the
+    // first checks
+    let mut rng = Rng(1);
+    let mut data = [0u8; 2 * protocol::COUNT + 2];
+    rng.fill(&mut data);
+    data[..2 * protocol::COUNT].fill(0);
+    data[0..2].copy_from_slice(&10000u16.to_le_bytes());
+    assert!(RebalanceData::try_from(&data[..]).is_ok());
+
+    let mut data = [0u8; OBSERVATION_LEN];
+    rng.fill(&mut data);
+    shape_observation(&mut rng, &mut data, 0);
+    assert!(MonitorYieldsData::try_from(&data[..]).is_ok());
+    assert!(PublishStrategyData::try_from(&data[..]).is_ok());
+}
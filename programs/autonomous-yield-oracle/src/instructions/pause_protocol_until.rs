@@ -0,0 +1,93 @@
+//! Pause Protocol Until instruction
+//!
+//! Briefly stops routing to a single protocol (e.g. while its pool is
+//! rebalancing). The pause lapses on its own at the given time, so no
+//! follow-up transaction is needed. Not a setting: allowed while config is
+//! locked.
+
+use pinocchio::{AccountView, Address, ProgramResult};
+use solana_program_error::ProgramError;
+
+use super::{load_oracle, ProtocolId};
+use crate::state::OracleState;
+
+/// Accounts required for pausing a protocol
+pub struct PauseProtocolUntilAccounts<'a> {
+    /// The oracle account
+    pub oracle: &'a AccountView,
+    /// The authority (must sign)
+    pub authority: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for PauseProtocolUntilAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [oracle, authority, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !authority.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self { oracle, authority })
+    }
+}
+
+/// Instruction data for pausing a protocol
+/// Layout: protocol (1) + until (8) = 9 bytes
+pub struct PauseProtocolUntilData {
+    /// Protocol to pause
+    pub protocol: ProtocolId,
+    /// Unix timestamp the pause lapses at (0 or past = lift now)
+    pub until: i64,
+}
+
+impl TryFrom<&[u8]> for PauseProtocolUntilData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() < 9 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let protocol = ProtocolId::try_from(data[0])?;
+
+        Ok(Self {
+            protocol,
+            until: i64::from_le_bytes(data[1..9].try_into().unwrap()),
+        })
+    }
+}
+
+/// Pause Protocol Until instruction
+pub struct PauseProtocolUntil<'a> {
+    pub accounts: PauseProtocolUntilAccounts<'a>,
+    pub data: PauseProtocolUntilData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for PauseProtocolUntil<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = PauseProtocolUntilAccounts::try_from(accounts)?;
+        let data = PauseProtocolUntilData::try_from(data)?;
+        Ok(Self { accounts, data })
+    }
+}
+
+impl<'a> PauseProtocolUntil<'a> {
+    pub fn process(&self, program_id: &Address) -> ProgramResult {
+        // Program-owned, initialized oracle only
+        load_oracle(self.accounts.oracle, program_id)?;
+
+        let mut oracle_data = self.accounts.oracle.try_borrow_mut()?;
+        let state = OracleState::from_bytes_mut(&mut oracle_data)?;
+
+        // Verify initialized, then authority
+        state.require_authority(self.accounts.authority.address().as_ref())?;
+
+        state.set_protocol_paused_until(self.data.protocol as u8, self.data.until)
+    }
+}
@@ -0,0 +1,106 @@
+//! Propose Fee Destination instruction
+//!
+//! First step of rotating the fee destination: records the new account,
+//! which `AcceptFeeDestination` can make current once
+//! `FEE_DESTINATION_TIMELOCK_SECS` have passed. Proposing all zeros cancels.
+//!
+//! With `status::REQUIRE_UPGRADE_AUTHORITY` set, the
+//! `UpgradeAuthorityAccounts` follow the authority and must co-sign.
+
+use pinocchio::{
+    sysvars::{clock::Clock, Sysvar},
+    AccountView, Address, ProgramResult,
+};
+use solana_program_error::ProgramError;
+
+use crate::state::OracleState;
+use super::{load_oracle, require_upgrade_cosigner, UpgradeAuthorityAccounts};
+
+/// Accounts required for proposing a fee destination
+pub struct ProposeFeeDestinationAccounts<'a> {
+    /// The oracle account
+    pub oracle: &'a AccountView,
+    /// The authority (must sign)
+    pub authority: &'a AccountView,
+    /// Upgrade authority co-signing, when the oracle requires it
+    pub remaining: &'a [AccountView],
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for ProposeFeeDestinationAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [oracle, authority, remaining @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !authority.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self {
+            oracle,
+            authority,
+            remaining,
+        })
+    }
+}
+
+/// Instruction data for proposing a fee destination
+/// Layout: destination (32) = 32 bytes
+pub struct ProposeFeeDestinationData {
+    /// The proposed fee destination
+    pub destination: [u8; 32],
+}
+
+impl TryFrom<&[u8]> for ProposeFeeDestinationData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() < 32 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            destination: data[0..32].try_into().unwrap(),
+        })
+    }
+}
+
+/// Propose Fee Destination instruction
+pub struct ProposeFeeDestination<'a> {
+    pub accounts: ProposeFeeDestinationAccounts<'a>,
+    pub data: ProposeFeeDestinationData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for ProposeFeeDestination<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = ProposeFeeDestinationAccounts::try_from(accounts)?;
+        let data = ProposeFeeDestinationData::try_from(data)?;
+        Ok(Self { accounts, data })
+    }
+}
+
+impl<'a> ProposeFeeDestination<'a> {
+    pub fn process(&self, program_id: &Address) -> ProgramResult {
+        // Program-owned, initialized oracle only
+        load_oracle(self.accounts.oracle, program_id)?;
+
+        let mut oracle_data = self.accounts.oracle.try_borrow_mut()?;
+        let state = OracleState::from_bytes_mut(&mut oracle_data)?;
+
+        // Verify initialized, then authority
+        state.require_authority(self.accounts.authority.address().as_ref())?;
+        require_upgrade_cosigner(
+            state,
+            UpgradeAuthorityAccounts::try_from(self.accounts.remaining),
+            program_id,
+        )?;
+
+        state.propose_fee_destination(self.data.destination, Clock::get()?.unix_timestamp);
+
+        Ok(())
+    }
+}
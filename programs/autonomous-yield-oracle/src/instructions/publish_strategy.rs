@@ -1,12 +1,25 @@
 //! Publish Strategy instruction
 //!
 //! Publishes current strategy recommendation to oracle state.
+//!
+//! Data is a single strategy, or a batch of up to one full-length (16 byte)
+//! strategy per protocol. A batch is ranked by risk-adjusted APY net of
+//! fees; the best is applied as a single strategy would be, and the whole
+//! ranking is kept in `OracleState::recommendations`.
+
+use core::cmp::Reverse;
 
-use pinocchio::{AccountView, ProgramResult};
+use pinocchio::{
+    sysvars::{clock::Clock, Sysvar},
+    AccountView, Address, ProgramResult,
+};
 use solana_program_error::ProgramError;
 
-use crate::state::OracleState;
+use crate::logging;
+use crate::scoring::{self, Observation, Source, UpdateDecision};
+use crate::state::{ApyPrecision, OracleState};
 use crate::error::OracleError;
+use super::{load_oracle, parse_apy_centi_bps, protocol, ObservationData, ProtocolId};
 
 /// Accounts required for publishing strategy
 pub struct PublishStrategyAccounts<'a> {
@@ -33,43 +46,184 @@ impl<'a> TryFrom<&'a [AccountView]> for PublishStrategyAccounts<'a> {
 }
 
 /// Instruction data for strategy publishing
+/// Layout: protocol (1) + expected_apy_bps (2) + risk_score (1) +
+/// timestamp (8) + expected_apy_centi_bps (4, optional) = 16 bytes
+#[derive(Clone, Copy)]
 pub struct PublishStrategyData {
     /// Recommended protocol
-    pub protocol: u8,
+    pub protocol: ProtocolId,
     /// Expected APY (basis points)
     pub expected_apy_bps: u16,
     /// Risk assessment (0-100)
     pub risk_score: u8,
     /// Timestamp of analysis
     pub timestamp: i64,
+    /// Expected APY in hundredths of a bps, used under
+    /// `ApyPrecision::CentiBps` (defaults to `expected_apy_bps * 100`)
+    pub expected_apy_centi_bps: u32,
 }
 
 impl TryFrom<&[u8]> for PublishStrategyData {
     type Error = ProgramError;
 
     fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
-        if data.len() < 12 {
-            return Err(ProgramError::InvalidInstructionData);
+        let head = ObservationData::try_from(data)?;
+
+        Ok(Self {
+            protocol: head.protocol,
+            expected_apy_bps: head.apy_bps,
+            risk_score: head.risk_score,
+            timestamp: head.timestamp,
+            expected_apy_centi_bps: parse_apy_centi_bps(
+                data,
+                ObservationData::LEN,
+                head.apy_bps,
+            ),
+        })
+    }
+}
+
+impl PublishStrategyData {
+    /// Size of one strategy in a batch
+    pub const LEN: usize = 16;
+
+    /// This strategy as scored by `scoring::evaluate_update`
+    pub fn observation(&self) -> Observation {
+        Observation {
+            source: Source::Strategy,
+            protocol: self.protocol,
+            apy_bps: self.expected_apy_bps,
+            apy_centi_bps: self.expected_apy_centi_bps,
+            risk_score: self.risk_score,
+            liquidity: 0,
+        }
+    }
+
+    /// Apply this strategy to the oracle state
+    ///
+    /// Strategies below the actionable floor are tracked (the update time
+    /// moves forward) but never replace the best protocol. Expected APYs
+    /// above `max_apy_bps` fail with `ImplausibleApy`, and protocols outside
+    /// the allowlist with `InvalidProtocol`. A strategy that becomes the
+    /// best is recorded in the per-protocol snapshot too, as the yield it
+    /// holds the position with.
+    pub fn apply(&self, state: &mut OracleState) -> ProgramResult {
+        state.require_protocol_allowed(self.protocol as u8)?;
+        let decision = scoring::evaluate_update(state, &self.observation(), self.timestamp)?;
+        state.push_apy_history(self.timestamp, self.expected_apy_bps);
+
+        if decision != UpdateDecision::Switch {
+            state.set_last_update(self.timestamp);
+            return Ok(());
         }
 
-        let risk_score = data[3];
-        if risk_score > 100 {
-            return Err(OracleError::InvalidRiskScore.into());
+        // Update oracle with strategy data
+        state.increment_decisions()?;
+        state.best_protocol = self.protocol as u8;
+        state.record_protocol_observation(
+            self.protocol as u8,
+            self.expected_apy_bps,
+            self.risk_score,
+        )?;
+        match state.apy_precision() {
+            ApyPrecision::Bps => state.set_current_apy_bps(self.expected_apy_bps),
+            ApyPrecision::CentiBps => state.set_current_apy_centi_bps(self.expected_apy_centi_bps),
         }
+        state.update_apy_ema(self.expected_apy_bps);
+        state.risk_score = self.risk_score;
+        state.set_best_liquidity(0);
+        state.set_last_update(self.timestamp);
 
-        Ok(Self {
-            protocol: data[0],
-            expected_apy_bps: u16::from_le_bytes([data[1], data[2]]),
-            risk_score,
-            timestamp: i64::from_le_bytes(data[4..12].try_into().unwrap()),
-        })
+        logging::format_observation(
+            logging::PUBLISH,
+            self.protocol as u8,
+            self.expected_apy_bps,
+            self.expected_apy_centi_bps,
+            self.risk_score,
+            self.timestamp,
+        )
+        .log();
+
+        Ok(())
+    }
+}
+
+/// The strategies of one call, in submitted order
+///
+/// Holds a single strategy, or a batch of up to one per protocol.
+pub struct PublishStrategyBatch {
+    pub strategies: [Option<PublishStrategyData>; protocol::COUNT],
+}
+
+impl TryFrom<&[u8]> for PublishStrategyBatch {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        let mut strategies = [None; protocol::COUNT];
+        let len = PublishStrategyData::LEN;
+
+        if data.len() <= len || !data.len().is_multiple_of(len) {
+            strategies[0] = Some(PublishStrategyData::try_from(data)?);
+            return Ok(Self { strategies });
+        }
+
+        // More strategies than protocols must repeat one
+        if data.len() / len > protocol::COUNT {
+            return Err(OracleError::InvalidProtocol.into());
+        }
+
+        let mut seen = [false; protocol::COUNT];
+        for (slot, chunk) in strategies.iter_mut().zip(data.chunks_exact(len)) {
+            let strategy = PublishStrategyData::try_from(chunk)?;
+            if core::mem::replace(&mut seen[strategy.protocol.index()], true) {
+                return Err(OracleError::InvalidProtocol.into());
+            }
+            *slot = Some(strategy);
+        }
+
+        Ok(Self { strategies })
+    }
+}
+
+impl PublishStrategyBatch {
+    /// Rank the strategies and apply the best (see `PublishStrategyData::apply`)
+    ///
+    /// Strategies rank by `scoring::adjusted_apy`, ties in submitted order.
+    /// Every expected APY and protocol is checked before anything is
+    /// written, so one bad entry fails the whole batch.
+    pub fn apply(&self, state: &mut OracleState) -> ProgramResult {
+        let mut ranked = [(0u32, 0usize); protocol::COUNT];
+        let mut len = 0;
+        for (i, strategy) in self.strategies.iter().enumerate() {
+            let Some(strategy) = strategy else { continue };
+            state.check_apy_plausible(strategy.expected_apy_bps)?;
+            state.require_protocol_allowed(strategy.protocol as u8)?;
+            ranked[len] = (scoring::adjusted_apy(state, &strategy.observation())?, i);
+            len += 1;
+        }
+        let ranked = &mut ranked[..len];
+        ranked.sort_unstable_by_key(|&(score, i)| (Reverse(score), i));
+
+        let ranked = ranked.iter().filter_map(|&(_, i)| self.strategies[i].as_ref());
+        let Some(best) = ranked.clone().next() else {
+            return Ok(());
+        };
+        best.apply(state)?;
+
+        let mut recommendations = [(0u8, 0u16, 0u8); protocol::COUNT];
+        for (entry, strategy) in recommendations.iter_mut().zip(ranked) {
+            *entry = (strategy.protocol as u8, strategy.expected_apy_bps, strategy.risk_score);
+        }
+        state.set_recommendations(&recommendations[..len]);
+
+        Ok(())
     }
 }
 
 /// Publish Strategy instruction
 pub struct PublishStrategy<'a> {
     pub accounts: PublishStrategyAccounts<'a>,
-    pub data: PublishStrategyData,
+    pub data: PublishStrategyBatch,
 }
 
 impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for PublishStrategy<'a> {
@@ -77,33 +231,176 @@ impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for PublishStrategy<'a> {
 
     fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
         let accounts = PublishStrategyAccounts::try_from(accounts)?;
-        let data = PublishStrategyData::try_from(data)?;
+        let data = PublishStrategyBatch::try_from(data)?;
         Ok(Self { accounts, data })
     }
 }
 
 impl<'a> PublishStrategy<'a> {
-    pub fn process(&self) -> ProgramResult {
+    pub fn process(&self, program_id: &Address) -> ProgramResult {
+        // Program-owned, initialized oracle only
+        load_oracle(self.accounts.oracle, program_id)?;
+
         let mut oracle_data = self.accounts.oracle.try_borrow_mut()?;
         let state = OracleState::from_bytes_mut(&mut oracle_data)?;
 
-        // Verify initialized
-        if state.is_initialized == 0 {
-            return Err(OracleError::NotInitialized.into());
-        }
+        // Verify initialized, then authority
+        state.require_authority(self.accounts.authority.address().as_ref())?;
 
-        // Verify authority
-        if state.authority != *self.accounts.authority.address().as_ref() {
-            return Err(OracleError::InvalidAuthority.into());
-        }
+        // Refuse to act while paused
+        state.require_not_paused()?;
 
-        // Update oracle with strategy data
-        state.best_protocol = self.data.protocol;
-        state.set_current_apy_bps(self.data.expected_apy_bps);
-        state.risk_score = self.data.risk_score;
-        state.set_last_update(self.data.timestamp);
-        state.increment_decisions();
+        let now = Clock::get()?.unix_timestamp;
+        state.roll_decision_window(now);
+        self.data.apply(state)?;
 
+        logging::format_score(state.composite_score(now), now).log();
+        logging::format_pnl(state.cumulative_pnl(), state.total_value_managed()).log();
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instructions::{protocol, MonitorYieldsData};
+
+    #[test]
+    fn test_rejects_unknown_protocol_and_short_data() {
+        let mut data = [0u8; 12];
+        data[0] = protocol::MAX_PROTOCOL;
+        assert!(PublishStrategyData::try_from(&data[..]).is_ok());
+
+        data[0] = 5;
+        assert_eq!(
+            PublishStrategyData::try_from(&data[..]).err(),
+            Some(OracleError::InvalidProtocol.into())
+        );
+
+        assert_eq!(
+            PublishStrategyData::try_from(&data[..11]).err(),
+            Some(OracleError::DataTooShort.into())
+        );
+    }
+
+    #[test]
+    fn test_rejects_implausible_apy() {
+        let state = &mut OracleState::test_state();
+
+        let strategy = |expected_apy_bps| PublishStrategyData {
+            protocol: ProtocolId::Kamino,
+            expected_apy_bps,
+            risk_score: 10,
+            timestamp: 1_000,
+            expected_apy_centi_bps: expected_apy_bps as u32 * 100,
+        };
+
+        strategy(OracleState::MAX_APY_BPS).apply(state).unwrap();
+        assert_eq!(state.current_apy_bps(), OracleState::MAX_APY_BPS);
+
+        assert_eq!(
+            strategy(OracleState::MAX_APY_BPS + 1).apply(state),
+            Err(OracleError::ImplausibleApy.into())
+        );
+        assert_eq!(state.decisions_count(), 1);
+    }
+
+    fn strategy_bytes(protocol: u8, expected_apy_bps: u16, risk_score: u8) -> [u8; 16] {
+        let mut data = [0u8; 16];
+        data[0] = protocol;
+        data[1..3].copy_from_slice(&expected_apy_bps.to_le_bytes());
+        data[3] = risk_score;
+        data[4..12].copy_from_slice(&1_000i64.to_le_bytes());
+        data[12..16].copy_from_slice(&(expected_apy_bps as u32 * 100).to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn test_batch_ranks_and_applies_the_best() {
+        let state = &mut OracleState::test_state();
+
+        // Kamino's headline APY is highest, but not once risk adjusted
+        let mut data = [0u8; 48];
+        data[..16].copy_from_slice(&strategy_bytes(protocol::KAMINO, 1200, 90));
+        data[16..32].copy_from_slice(&strategy_bytes(protocol::MARINADE, 800, 10));
+        data[32..].copy_from_slice(&strategy_bytes(protocol::JITO, 1000, 10));
+
+        let batch = PublishStrategyBatch::try_from(&data[..]).unwrap();
+        batch.apply(state).unwrap();
+
+        assert_eq!(state.best_protocol, protocol::JITO);
+        assert_eq!(state.current_apy_bps(), 1000);
+        assert!(state.recommendations().eq([
+            (protocol::JITO, 1000, 10),
+            (protocol::MARINADE, 800, 10),
+            (protocol::KAMINO, 1200, 90),
+        ]));
+
+        // A single strategy is a ranking of one
+        let single = PublishStrategyBatch::try_from(&data[16..32]).unwrap();
+        single.apply(state).unwrap();
+        assert!(state.recommendations().eq([(protocol::MARINADE, 800, 10)]));
+    }
+
+    #[test]
+    fn test_published_best_holds_against_worse_yield() {
+        let state = &mut OracleState::test_state();
+
+        let data = strategy_bytes(protocol::JITO, 1000, 10);
+        PublishStrategyBatch::try_from(&data[..]).unwrap().apply(state).unwrap();
+        assert_eq!(state.protocol_apy_bps()[ProtocolId::Jito.index()], 1000);
+
+        // Scored against the published 1000, not an empty snapshot
+        let worse = MonitorYieldsData {
+            protocol: ProtocolId::Marinade,
+            apy_bps: 600,
+            risk_score: 10,
+            timestamp: 1_100,
+            apy_centi_bps: 60_000,
+            nonce: None,
+            observed_liquidity: 0,
+        };
+        worse.apply(state).unwrap();
+        assert_eq!(state.best_protocol, protocol::JITO);
+        assert_eq!(state.current_apy_bps(), 1000);
+        assert_eq!(state.decisions_count(), 1);
+    }
+
+    #[test]
+    fn test_protocol_allowlist() {
+        let state = &mut OracleState::test_state();
+        state.set_protocol_allowlist(1 << protocol::MARINADE | 1 << protocol::JITO);
+
+        let data = strategy_bytes(protocol::JITO, 1000, 10);
+        PublishStrategyBatch::try_from(&data[..]).unwrap().apply(state).unwrap();
+        assert_eq!(state.best_protocol, protocol::JITO);
+
+        // One disallowed entry fails the whole batch
+        let mut data = [0u8; 32];
+        data[..16].copy_from_slice(&strategy_bytes(protocol::MARINADE, 900, 10));
+        data[16..].copy_from_slice(&strategy_bytes(protocol::KAMINO, 1500, 10));
+        assert_eq!(
+            PublishStrategyBatch::try_from(&data[..]).unwrap().apply(state),
+            Err(OracleError::InvalidProtocol.into())
+        );
+        assert_eq!(state.best_protocol, protocol::JITO);
+        assert_eq!(state.decisions_count(), 1);
+    }
+
+    #[test]
+    fn test_batch_rejects_repeated_protocols() {
+        let mut data = [0u8; 32];
+        data[..16].copy_from_slice(&strategy_bytes(protocol::JITO, 1000, 10));
+        data[16..].copy_from_slice(&strategy_bytes(protocol::JITO, 900, 10));
+        assert_eq!(
+            PublishStrategyBatch::try_from(&data[..]).err(),
+            Some(OracleError::InvalidProtocol.into())
+        );
+
+        let data = [0u8; 16 * (protocol::COUNT + 1)];
+        assert_eq!(
+            PublishStrategyBatch::try_from(&data[..]).err(),
+            Some(OracleError::InvalidProtocol.into())
+        );
+    }
+}
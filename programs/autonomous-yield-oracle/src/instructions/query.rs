@@ -0,0 +1,158 @@
+//! Query instruction
+//!
+//! Returns the current decision through return data, in a fixed layout a
+//! client simulating the transaction can read straight from the RPC
+//! response, without parsing logs or the whole state (see `GetState`).
+//! Read-only; no signer required; works on accounts of older versions.
+//!
+//! Return data layout: best_protocol (1) + current_apy_bps (2, le) +
+//! risk_score (1) + last_update (8, le) + cumulative_pnl (8, le) = 20 bytes
+
+use pinocchio::{cpi::set_return_data, AccountView, Address, ProgramResult};
+use solana_program_error::ProgramError;
+
+use crate::state::OracleState;
+use super::load_oracle_any_version;
+
+/// Accounts required for a query
+pub struct QueryAccounts<'a> {
+    /// The oracle account
+    pub oracle: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for QueryAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [oracle, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self { oracle })
+    }
+}
+
+/// The current decision, as returned by `Query`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct QueryResponse {
+    /// Raw byte, as stored; not necessarily a valid protocol ID
+    pub best_protocol: u8,
+    pub current_apy_bps: u16,
+    pub risk_score: u8,
+    pub last_update: i64,
+    pub cumulative_pnl: i64,
+}
+
+impl QueryResponse {
+    /// Size of the response in return data
+    pub const LEN: usize = 20;
+
+    /// Encode in the return data layout
+    pub fn encode(&self) -> [u8; Self::LEN] {
+        let mut data = [0u8; Self::LEN];
+        data[0] = self.best_protocol;
+        data[1..3].copy_from_slice(&self.current_apy_bps.to_le_bytes());
+        data[3] = self.risk_score;
+        data[4..12].copy_from_slice(&self.last_update.to_le_bytes());
+        data[12..20].copy_from_slice(&self.cumulative_pnl.to_le_bytes());
+        data
+    }
+
+    /// Decode the return data of a simulated `Query`
+    ///
+    /// Fails with `InvalidArgument` if it is too short to be a response.
+    #[cfg(feature = "client")]
+    pub fn decode(data: &[u8]) -> Result<Self, ProgramError> {
+        let Some(data) = data.get(..Self::LEN) else {
+            return Err(ProgramError::InvalidArgument);
+        };
+
+        Ok(Self {
+            best_protocol: data[0],
+            current_apy_bps: u16::from_le_bytes([data[1], data[2]]),
+            risk_score: data[3],
+            last_update: i64::from_le_bytes(data[4..12].try_into().unwrap()),
+            cumulative_pnl: i64::from_le_bytes(data[12..20].try_into().unwrap()),
+        })
+    }
+}
+
+impl From<&OracleState> for QueryResponse {
+    fn from(state: &OracleState) -> Self {
+        Self {
+            best_protocol: state.best_protocol,
+            current_apy_bps: state.current_apy_bps(),
+            risk_score: state.risk_score,
+            last_update: state.last_update(),
+            cumulative_pnl: state.cumulative_pnl(),
+        }
+    }
+}
+
+/// Query instruction
+pub struct Query<'a> {
+    pub accounts: QueryAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for Query<'a> {
+    type Error = ProgramError;
+
+    fn try_from((_data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = QueryAccounts::try_from(accounts)?;
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> Query<'a> {
+    pub fn process(&self, program_id: &Address) -> ProgramResult {
+        // Program-owned, initialized oracle of any version
+        let state = load_oracle_any_version(self.accounts.oracle, program_id)?;
+
+        set_return_data(&QueryResponse::from(&state).encode());
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instructions::protocol;
+
+    fn response() -> QueryResponse {
+        let state = &mut OracleState::test_state();
+        state.best_protocol = protocol::JITO;
+        state.set_current_apy_bps(750);
+        state.risk_score = 10;
+        state.set_last_update(1_770_000_000);
+        state.add_pnl(-4_200).unwrap();
+        QueryResponse::from(&*state)
+    }
+
+    #[test]
+    fn test_query_response_layout() {
+        let data = response().encode();
+        assert_eq!(data[0], protocol::JITO);
+        assert_eq!(data[1..3], 750u16.to_le_bytes());
+        assert_eq!(data[3], 10);
+        assert_eq!(data[4..12], 1_770_000_000i64.to_le_bytes());
+        assert_eq!(data[12..20], (-4_200i64).to_le_bytes());
+    }
+
+    #[cfg(feature = "client")]
+    #[test]
+    fn test_query_response_round_trip() {
+        let response = response();
+        let data = response.encode();
+        assert_eq!(QueryResponse::decode(&data), Ok(response));
+
+        // Trailing bytes are ignored; a short buffer is refused
+        let mut padded = [0xffu8; QueryResponse::LEN + 4];
+        padded[..QueryResponse::LEN].copy_from_slice(&data);
+        assert_eq!(QueryResponse::decode(&padded), Ok(response));
+        assert_eq!(
+            QueryResponse::decode(&data[..QueryResponse::LEN - 1]),
+            Err(ProgramError::InvalidArgument)
+        );
+    }
+}
@@ -1,12 +1,22 @@
 //! Rebalance instruction
 //!
 //! Autonomous rebalancing based on yield optimization.
+//!
+//! The move from the current allocation to the new one is split into legs,
+//! each a Raydium CPMM swap from one slot (a protocol bucket or cash) to
+//! another, signed by the oracle PDA. Every leg's accounts follow the
+//! authority as a group of `LEG_ACCOUNTS`: source token, destination token,
+//! then the `RaydiumSwapAccounts`; the fee treasury follows the last leg.
+//! State is only written once every leg has filled within
+//! `max_slippage_bps` (see `RebalanceAndSettle`).
 
 use pinocchio::{AccountView, ProgramResult};
 use solana_program_error::ProgramError;
 
-use crate::state::OracleState;
+use crate::logging;
+use crate::state::{OracleState, RebalancePhase};
 use crate::error::OracleError;
+use super::{protocol, RebalanceAndSettle};
 
 /// Accounts required for rebalancing
 pub struct RebalanceAccounts<'a> {
@@ -14,14 +24,15 @@ pub struct RebalanceAccounts<'a> {
     pub oracle: &'a AccountView,
     /// The authority
     pub authority: &'a AccountView,
-    // Additional accounts for token operations
+    /// Swap accounts, `LEG_ACCOUNTS` per leg
+    pub remaining: &'a [AccountView],
 }
 
 impl<'a> TryFrom<&'a [AccountView]> for RebalanceAccounts<'a> {
     type Error = ProgramError;
 
     fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
-        let [oracle, authority, ..] = accounts else {
+        let [oracle, authority, remaining @ ..] = accounts else {
             return Err(ProgramError::NotEnoughAccountKeys);
         };
 
@@ -29,86 +40,649 @@ impl<'a> TryFrom<&'a [AccountView]> for RebalanceAccounts<'a> {
             return Err(ProgramError::MissingRequiredSignature);
         }
 
-        Ok(Self { oracle, authority })
+        Ok(Self {
+            oracle,
+            authority,
+            remaining,
+        })
     }
 }
 
 /// Instruction data for rebalancing
+/// Layout: target_allocation_bps (5 x 2) + max_slippage_bps (2) +
+/// priority_fee_lamports (8, optional) + urgent (1, optional) +
+/// dry_run (1, optional) = 22 bytes
 pub struct RebalanceData {
-    /// Target allocation percentages (basis points, must sum to 10000)
-    pub target_allocation_bps: [u16; 4],
+    /// Target allocation percentages per protocol bucket (basis points,
+    /// must sum to 10000)
+    pub target_allocation_bps: [u16; protocol::COUNT],
     /// Maximum slippage allowed (basis points)
     pub max_slippage_bps: u16,
+    /// Priority fee the transaction was sent with (client-supplied)
+    pub priority_fee_lamports: u64,
+    /// Urgent exit; bypasses the priority fee cap
+    pub urgent: bool,
+    /// Only log the planned legs; nothing is swapped or written
+    pub dry_run: bool,
 }
 
 impl TryFrom<&[u8]> for RebalanceData {
     type Error = ProgramError;
 
     fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
-        if data.len() < 10 {
-            return Err(ProgramError::InvalidInstructionData);
+        const SLIPPAGE: usize = 2 * protocol::COUNT;
+        if data.len() < SLIPPAGE + 2 {
+            return Err(OracleError::DataTooShort.into());
         }
 
-        let target_allocation_bps = [
-            u16::from_le_bytes([data[0], data[1]]),
-            u16::from_le_bytes([data[2], data[3]]),
-            u16::from_le_bytes([data[4], data[5]]),
-            u16::from_le_bytes([data[6], data[7]]),
-        ];
+        let mut target_allocation_bps = [0u16; protocol::COUNT];
+        for (bps, bytes) in target_allocation_bps.iter_mut().zip(data.chunks_exact(2)) {
+            *bps = u16::from_le_bytes([bytes[0], bytes[1]]);
+        }
 
-        // Verify allocations sum to 10000 (100%)
-        let sum: u16 = target_allocation_bps.iter().sum();
+        // Verify allocations sum to 10000 (100%), without wrapping
+        let sum: u32 = target_allocation_bps.iter().map(|&bps| bps as u32).sum();
         if sum != 10000 {
-            return Err(ProgramError::InvalidInstructionData);
+            return Err(OracleError::InvalidAllocation.into());
         }
 
+        let (priority_fee_lamports, urgent) = parse_priority_fee(&data[SLIPPAGE + 2..]);
+
         Ok(Self {
             target_allocation_bps,
-            max_slippage_bps: u16::from_le_bytes([data[8], data[9]]),
+            max_slippage_bps: u16::from_le_bytes([data[SLIPPAGE], data[SLIPPAGE + 1]]),
+            priority_fee_lamports,
+            urgent,
+            dry_run: data.get(SLIPPAGE + 11).is_some_and(|&dry_run| dry_run != 0),
         })
     }
 }
 
-/// Rebalance instruction
-pub struct Rebalance<'a> {
-    pub accounts: RebalanceAccounts<'a>,
-    pub data: RebalanceData,
+/// Parse the optional priority fee (8, le) and urgent flag (1) trailer
+///
+/// Older clients omit both, which reads as a zero fee, not urgent.
+pub(crate) fn parse_priority_fee(data: &[u8]) -> (u64, bool) {
+    let fee = match data.get(0..8) {
+        Some(fee) => u64::from_le_bytes(fee.try_into().unwrap()),
+        None => 0,
+    };
+    (fee, data.get(8).is_some_and(|&urgent| urgent != 0))
 }
 
-impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for Rebalance<'a> {
-    type Error = ProgramError;
+/// Round each allocation to the nearest multiple of `lot_size_bps`
+///
+/// The rounding remainder is settled one lot at a time, each time on the
+/// largest slot, so the result stays on the grid and sums to 10000. A lot
+/// size of 0, or one that doesn't divide 10000, leaves the allocation as is.
+pub fn round_to_lot(
+    allocation_bps: [u16; protocol::COUNT],
+    lot_size_bps: u16,
+) -> [u16; protocol::COUNT] {
+    if lot_size_bps == 0 || 10000 % lot_size_bps != 0 {
+        return allocation_bps;
+    }
+
+    let lot = lot_size_bps as u32;
+    let mut rounded = [0u16; protocol::COUNT];
+    for (slot, &bps) in rounded.iter_mut().zip(allocation_bps.iter()) {
+        *slot = (((bps as u32 + lot / 2) / lot) * lot) as u16;
+    }
+
+    // Largest slot takes or gives each lot (first one wins on ties); while
+    // over 10000 it holds at least a lot, so none goes negative
+    let mut sum: u32 = rounded.iter().map(|&bps| bps as u32).sum();
+    while sum != 10000 {
+        let largest = rounded
+            .iter()
+            .enumerate()
+            .fold(0, |best, (i, &bps)| if bps > rounded[best] { i } else { best });
+        if sum > 10000 {
+            rounded[largest] -= lot as u16;
+            sum -= lot;
+        } else {
+            rounded[largest] += lot as u16;
+            sum += lot;
+        }
+    }
+
+    rounded
+}
+
+/// Scale an allocation down to a risk budget (bps of capital deployed)
+///
+/// The allocation no longer sums to 10000; the shortfall is held in cash.
+pub fn apply_risk_budget(
+    allocation_bps: [u16; protocol::COUNT],
+    risk_budget_bps: u16,
+) -> [u16; protocol::COUNT] {
+    let budget = risk_budget_bps.min(10000) as u32;
+    allocation_bps.map(|bps| (bps as u32 * budget / 10000) as u16)
+}
+
+/// Move `fraction_bps` of the way from `current` to `target`
+///
+/// Each bucket is a floored weighted average, so the result never sums to
+/// more than the larger of the two allocations.
+pub fn interpolate_allocation(
+    current: [u16; protocol::COUNT],
+    target: [u16; protocol::COUNT],
+    fraction_bps: u16,
+) -> [u16; protocol::COUNT] {
+    let fraction = fraction_bps.min(10000) as u32;
+    let mut interim = [0u16; protocol::COUNT];
+    for (i, slot) in interim.iter_mut().enumerate() {
+        *slot = ((current[i] as u32 * (10000 - fraction) + target[i] as u32 * fraction) / 10000) as u16;
+    }
+    interim
+}
+
+/// Accounts per leg: source token, destination token, Raydium pool (11)
+pub const LEG_ACCOUNTS: usize = 13;
+
+/// Most legs a rebalance can need (the buckets and cash pair off in one
+/// fewer)
+pub const MAX_REBALANCE_LEGS: usize = protocol::COUNT;
+
+/// Slot of the cash held outside the buckets
+pub const CASH_SLOT: usize = protocol::COUNT;
+
+/// One swap of a rebalance
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RebalanceLeg {
+    /// Slot sold from (bucket index, or `CASH_SLOT`)
+    pub from: usize,
+    /// Slot bought into (bucket index, or `CASH_SLOT`)
+    pub to: usize,
+    /// Value moved, in lamports
+    pub amount_lamports: u64,
+}
+
+/// Swaps moving `tvl` from `current` to `target`
+///
+/// Both allocations are completed with their cash share, then sellers are
+/// paired with buyers in slot order. Each leg is rounded down to whole
+/// lamports; legs that round to nothing are dropped.
+pub fn rebalance_legs(
+    current: [u16; protocol::COUNT],
+    target: [u16; protocol::COUNT],
+    tvl: u64,
+) -> [Option<RebalanceLeg>; MAX_REBALANCE_LEGS] {
+    let with_cash = |allocation: [u16; protocol::COUNT]| {
+        let mut slots = [0i32; CASH_SLOT + 1];
+        for (slot, &bps) in slots.iter_mut().zip(allocation.iter()) {
+            *slot = bps as i32;
+        }
+        slots[CASH_SLOT] = 10000 - allocation.iter().map(|&bps| bps as i32).sum::<i32>();
+        slots
+    };
+    let (current, target) = (with_cash(current), with_cash(target));
+
+    // Positive = to sell, negative = to buy
+    let mut excess = [0i32; CASH_SLOT + 1];
+    for (slot, excess) in excess.iter_mut().enumerate() {
+        *excess = current[slot] - target[slot];
+    }
+
+    let mut legs = [None; MAX_REBALANCE_LEGS];
+    let mut count = 0;
+    let (mut from, mut to) = (0, 0);
+    while count < MAX_REBALANCE_LEGS {
+        while from < excess.len() && excess[from] <= 0 {
+            from += 1;
+        }
+        while to < excess.len() && excess[to] >= 0 {
+            to += 1;
+        }
+        if from == excess.len() || to == excess.len() {
+            break;
+        }
 
-    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
-        let accounts = RebalanceAccounts::try_from(accounts)?;
-        let data = RebalanceData::try_from(data)?;
-        Ok(Self { accounts, data })
+        let bps = excess[from].min(-excess[to]);
+        excess[from] -= bps;
+        excess[to] += bps;
+
+        let amount_lamports = (tvl as u128 * bps as u128 / 10000) as u64;
+        if amount_lamports > 0 {
+            legs[count] = Some(RebalanceLeg {
+                from,
+                to,
+                amount_lamports,
+            });
+            count += 1;
+        }
     }
+
+    legs
+}
+
+/// Least a leg of `amount_in` may return under `max_slippage_bps`
+///
+/// Bucket positions are valued at par with lamports.
+pub fn min_leg_out(amount_in: u64, max_slippage_bps: u16) -> u64 {
+    let kept = 10000 - max_slippage_bps.min(10000) as u128;
+    (amount_in as u128 * kept / 10000) as u64
 }
 
-impl<'a> Rebalance<'a> {
-    pub fn process(&self) -> ProgramResult {
-        let mut oracle_data = self.accounts.oracle.try_borrow_mut()?;
-        let state = OracleState::from_bytes_mut(&mut oracle_data)?;
+/// Next allocation of a rebalance, and the phase it leaves behind
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RebalancePlan {
+    /// Allocation after this call
+    pub allocation: [u16; protocol::COUNT],
+    /// Allocation once the move is complete
+    pub target: [u16; protocol::COUNT],
+    /// `PhaseOne` if this call is the first leg of a two-phase move
+    pub phase: RebalancePhase,
+}
 
-        // Verify initialized
-        if state.is_initialized == 0 {
-            return Err(OracleError::NotInitialized.into());
+impl RebalanceData {
+    /// Reject moving more into a bucket whose protocol is paused at `now`
+    ///
+    /// Bucket `i` holds protocol ID `i`. Holding or reducing a paused
+    /// protocol's share is still allowed, so capital can be pulled out.
+    pub fn check_paused_protocols(&self, state: &OracleState, now: i64) -> ProgramResult {
+        let current = state.current_allocation_bps();
+        for (bucket, &target) in self.target_allocation_bps.iter().enumerate() {
+            if target > current[bucket] && state.is_protocol_paused(bucket as u8, now)? {
+                return Err(OracleError::ProtocolPaused.into());
+            }
         }
+        Ok(())
+    }
+
+    /// Work out where this rebalance moves to, without writing anything
+    ///
+    /// With a phase-one fraction configured the move happens in two legs:
+    /// the first call moves that fraction of the way and records the
+    /// target, and a second call with the same target completes it. A
+    /// different target while mid-rebalance fails with `RebalanceInProgress`.
+    pub fn plan(&self, state: &OracleState) -> Result<RebalancePlan, ProgramError> {
+        state.check_priority_fee(self.priority_fee_lamports, self.urgent)?;
+
+        let phase_one_fraction = state.phase_one_fraction_bps();
+        let completing = match state.rebalance_phase() {
+            RebalancePhase::Idle => false,
+            RebalancePhase::PhaseOne => {
+                if state.rebalance_target() != self.target_allocation_bps {
+                    return Err(OracleError::RebalanceInProgress.into());
+                }
+                true
+            }
+        };
+
+        // Snap the target to the configured lot grid, then wind deployment
+        // down as drawdown grows
+        let target = round_to_lot(self.target_allocation_bps, state.lot_size_bps());
+        let target = apply_risk_budget(target, state.risk_budget_bps());
 
-        // Verify authority
-        if state.authority != *self.accounts.authority.address().as_ref() {
-            return Err(OracleError::InvalidAuthority.into());
+        // First leg of a two-phase move only goes part of the way
+        if !completing && phase_one_fraction > 0 {
+            Ok(RebalancePlan {
+                allocation: interpolate_allocation(
+                    state.current_allocation_bps(),
+                    target,
+                    phase_one_fraction,
+                ),
+                target,
+                phase: RebalancePhase::PhaseOne,
+            })
+        } else {
+            Ok(RebalancePlan {
+                allocation: target,
+                target,
+                phase: RebalancePhase::Idle,
+            })
         }
+    }
 
-        // TODO: Implement autonomous rebalancing logic
-        // 1. Get current positions across protocols
-        // 2. Calculate required swaps to reach target allocation
-        // 3. Execute swaps via Raydium/Jupiter
-        // 4. Update oracle state with new positions
-        // 5. Track PnL
+    /// Check this rebalance against `state` and work out its legs
+    ///
+    /// Runs every check `plan` does, plus paused protocols, without
+    /// writing anything; the legs move the current allocation to the
+    /// planned one.
+    pub fn legs(
+        &self,
+        state: &OracleState,
+        now: i64,
+    ) -> Result<[Option<RebalanceLeg>; MAX_REBALANCE_LEGS], ProgramError> {
+        self.check_paused_protocols(state, now)?;
 
-        state.increment_decisions();
+        let plan = self.plan(state)?;
+        Ok(rebalance_legs(
+            state.current_allocation_bps(),
+            plan.allocation,
+            state.total_value_managed(),
+        ))
+    }
+
+    /// Apply this rebalance to the oracle state (see `plan`)
+    pub fn apply(&self, state: &mut OracleState) -> ProgramResult {
+        let plan = self.plan(state)?;
+        state.increment_rebalances()?;
+
+        if plan.phase == RebalancePhase::PhaseOne {
+            state.set_rebalance_target(self.target_allocation_bps);
+        }
+        state.set_rebalance_phase(plan.phase);
+        state.set_target_allocation_bps(plan.target);
+
+        logging::format_rebalance(plan.allocation).log();
+
+        state.set_current_allocation_bps(plan.allocation)?;
 
         Ok(())
     }
 }
+
+/// Rebalance instruction
+///
+/// Runs the complete, settling path of `RebalanceAndSettle`; kept under its
+/// own discriminator for existing clients.
+pub type Rebalance<'a> = RebalanceAndSettle<'a>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instructions::protocol;
+    use crate::state::OracleConfig;
+
+    #[test]
+    fn test_round_to_lot_snaps_to_grid() {
+        let rounded = round_to_lot([2501, 2499, 3100, 1900, 0], 500);
+        assert_eq!(rounded, [2500, 2500, 3000, 2000, 0]);
+        assert_eq!(rounded.iter().sum::<u16>(), 10000);
+    }
+
+    #[test]
+    fn test_round_to_lot_renormalizes_into_largest() {
+        // 3333/3333/3334 all round down to 3000; the first largest slot takes the 1000
+        let rounded = round_to_lot([3333, 3333, 3334, 0, 0], 1000);
+        assert_eq!(rounded, [4000, 3000, 3000, 0, 0]);
+        assert_eq!(rounded.iter().sum::<u16>(), 10000);
+
+        // Rounding up past 10000 is taken back out of the largest slot
+        let rounded = round_to_lot([2600, 2600, 2600, 2200, 0], 1000);
+        assert_eq!(rounded, [2000, 3000, 3000, 2000, 0]);
+        assert_eq!(rounded.iter().sum::<u16>(), 10000);
+
+        // More than one lot over: each comes off the largest slot in turn
+        let rounded = round_to_lot([2500, 2500, 2500, 2500, 0], 5000);
+        assert_eq!(rounded, [0, 0, 5000, 5000, 0]);
+    }
+
+    #[test]
+    fn test_round_to_lot_off_grid_lot() {
+        // 3000 doesn't divide 10000: no multiple of it sums to 10000
+        let allocation = [2000; protocol::COUNT];
+        assert_eq!(round_to_lot(allocation, 3000), allocation);
+
+        let state = &OracleState::test_state();
+        let config = OracleConfig { lot_size_bps: 3000, ..OracleConfig::from_state(state) };
+        assert_eq!(config.validate(), Err(OracleError::InvalidConfig.into()));
+        let config = OracleConfig { lot_size_bps: 2500, ..config };
+        assert_eq!(config.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_apply_risk_budget() {
+        let allocation = [4000, 3000, 2000, 500, 500];
+        let deployed = |budget| apply_risk_budget(allocation, budget).iter().sum::<u16>();
+
+        assert_eq!(apply_risk_budget(allocation, 10000), allocation);
+        assert_eq!(apply_risk_budget(allocation, 7500), [3000, 2250, 1500, 375, 375]);
+        assert_eq!(deployed(7500), 7500);
+        assert_eq!(deployed(5000), 5000);
+        assert_eq!(deployed(2500), 2500);
+        assert_eq!(deployed(0), 0);
+    }
+
+    #[test]
+    fn test_rebalance_persists_allocation() {
+        let state = &mut OracleState::test_state();
+        assert_eq!(state.current_allocation_bps(), [0; 5]);
+
+        let data = RebalanceData::try_from(
+            &[0xd0, 0x07, 0xd0, 0x07, 0xd0, 0x07, 0xd0, 0x07, 0xd0, 0x07, 0x32, 0x00][..],
+        )
+        .unwrap();
+        data.apply(state).unwrap();
+
+        assert_eq!(state.current_allocation_bps(), [2000; 5]);
+        assert_eq!(state.decisions_count(), 1);
+        assert_eq!(state.rebalances_count(), 1);
+        assert_eq!(state.swaps_count(), 0);
+    }
+
+    #[test]
+    fn test_two_phase_rebalance() {
+        let state = &mut OracleState::test_state();
+        state.set_current_allocation_bps([10000, 0, 0, 0, 0]).unwrap();
+        state.set_phase_one_fraction_bps(5000);
+        state.set_drift_threshold_bps(1000);
+
+        let rebalance = |target| RebalanceData {
+            target_allocation_bps: target,
+            max_slippage_bps: 50,
+            priority_fee_lamports: 0,
+            urgent: false,
+            dry_run: false,
+        };
+
+        // Phase one moves half way
+        rebalance([0, 10000, 0, 0, 0]).apply(state).unwrap();
+        assert_eq!(state.rebalance_phase(), RebalancePhase::PhaseOne);
+        assert_eq!(state.current_allocation_bps(), [5000, 5000, 0, 0, 0]);
+        assert_eq!(state.target_allocation_bps(), [0, 10000, 0, 0, 0]);
+        assert!(state.needs_rebalance());
+
+        // A different rebalance can't start mid-phase
+        assert_eq!(
+            rebalance([0, 0, 10000, 0, 0]).apply(state),
+            Err(OracleError::RebalanceInProgress.into())
+        );
+        assert_eq!(state.current_allocation_bps(), [5000, 5000, 0, 0, 0]);
+
+        // Phase two completes the move
+        rebalance([0, 10000, 0, 0, 0]).apply(state).unwrap();
+        assert_eq!(state.rebalance_phase(), RebalancePhase::Idle);
+        assert_eq!(state.current_allocation_bps(), [0, 10000, 0, 0, 0]);
+        assert_eq!(state.decisions_count(), 2);
+        assert!(!state.needs_rebalance());
+    }
+
+    #[test]
+    fn test_rebalance_priority_fee_cap() {
+        let state = &mut OracleState::test_state();
+        state.set_max_priority_fee_lamports(5_000);
+
+        let rebalance = |priority_fee_lamports, urgent| RebalanceData {
+            target_allocation_bps: [2000; 5],
+            max_slippage_bps: 50,
+            priority_fee_lamports,
+            urgent,
+            dry_run: false,
+        };
+
+        assert_eq!(
+            rebalance(5_001, false).apply(state),
+            Err(OracleError::GasPriceTooHigh.into())
+        );
+        assert_eq!(state.decisions_count(), 0);
+
+        rebalance(5_000, false).apply(state).unwrap();
+        rebalance(50_000, true).apply(state).unwrap();
+        assert_eq!(state.decisions_count(), 2);
+    }
+
+    #[test]
+    fn test_paused_protocol_blocks_new_allocation() {
+        let state = &mut OracleState::test_state();
+        state.set_current_allocation_bps([5000, 5000, 0, 0, 0]).unwrap();
+        state.set_protocol_paused_until(protocol::KAMINO, 1_000).unwrap();
+
+        let rebalance = |target| RebalanceData {
+            target_allocation_bps: target,
+            max_slippage_bps: 50,
+            priority_fee_lamports: 0,
+            urgent: false,
+            dry_run: false,
+        };
+
+        // Kamino is bucket 2: no moving in while paused, moving around it is fine
+        assert_eq!(
+            rebalance([5000, 2500, 2500, 0, 0]).check_paused_protocols(state, 999),
+            Err(OracleError::ProtocolPaused.into())
+        );
+        assert!(rebalance([2500, 2500, 0, 0, 5000]).check_paused_protocols(state, 999).is_ok());
+
+        // Pause lapsed
+        assert!(rebalance([5000, 2500, 2500, 0, 0]).check_paused_protocols(state, 1_000).is_ok());
+
+        // Pulling out of a paused protocol is always allowed
+        state.set_current_allocation_bps([0, 0, 10000, 0, 0]).unwrap();
+        assert!(rebalance([5000, 5000, 0, 0, 0]).check_paused_protocols(state, 999).is_ok());
+    }
+
+    #[test]
+    fn test_rebalance_legs() {
+        // 40% moves from bucket 0: 30% to bucket 1, 10% to bucket 4
+        let legs = rebalance_legs([6000, 2000, 2000, 0, 0], [2000, 5000, 2000, 0, 1000], 1_000_000);
+        assert_eq!(
+            legs,
+            [
+                Some(RebalanceLeg { from: 0, to: 1, amount_lamports: 300_000 }),
+                Some(RebalanceLeg { from: 0, to: 4, amount_lamports: 100_000 }),
+                None,
+                None,
+                None,
+            ]
+        );
+
+        // Winding down into cash, and redeploying it
+        let legs = rebalance_legs([5000, 5000, 0, 0, 0], [2500, 2500, 0, 0, 0], 1_000_000);
+        assert_eq!(legs[0], Some(RebalanceLeg { from: 0, to: CASH_SLOT, amount_lamports: 250_000 }));
+        assert_eq!(legs[1], Some(RebalanceLeg { from: 1, to: CASH_SLOT, amount_lamports: 250_000 }));
+        let legs = rebalance_legs([2500, 2500, 0, 0, 0], [0, 0, 10000, 0, 0], 1_000_000);
+        assert_eq!(legs[2], Some(RebalanceLeg { from: CASH_SLOT, to: 2, amount_lamports: 500_000 }));
+
+        // Nothing to move, or nothing managed
+        assert_eq!(rebalance_legs([2000; 5], [2000; 5], 1_000_000), [None; MAX_REBALANCE_LEGS]);
+        assert_eq!(rebalance_legs([10000, 0, 0, 0, 0], [0, 0, 0, 10000, 0], 0), [None; MAX_REBALANCE_LEGS]);
+
+        // Every bucket fed from cash takes the maximum number of legs
+        let legs = rebalance_legs([0; 5], [2000; 5], 4);
+        assert_eq!(legs.iter().flatten().count(), 0);
+        let legs = rebalance_legs([0; 5], [2000; 5], 1_000_000);
+        assert_eq!(legs.iter().flatten().count(), MAX_REBALANCE_LEGS);
+    }
+
+    #[test]
+    fn test_min_leg_out() {
+        assert_eq!(min_leg_out(1_000_000, 50), 995_000);
+        assert_eq!(min_leg_out(1_000_000, 0), 1_000_000);
+        assert_eq!(min_leg_out(1_000_000, u16::MAX), 0);
+    }
+
+    #[test]
+    fn test_plan_writes_nothing() {
+        let state = &mut OracleState::test_state();
+        state.set_phase_one_fraction_bps(5000);
+        let before = *state;
+
+        let data = RebalanceData {
+            target_allocation_bps: [10000, 0, 0, 0, 0],
+            max_slippage_bps: 50,
+            priority_fee_lamports: 0,
+            urgent: false,
+            dry_run: false,
+        };
+        assert_eq!(
+            data.plan(state),
+            Ok(RebalancePlan {
+                allocation: [5000, 0, 0, 0, 0],
+                target: [10000, 0, 0, 0, 0],
+                phase: RebalancePhase::PhaseOne,
+            })
+        );
+        assert_eq!(state.as_bytes(), before.as_bytes());
+    }
+
+    #[test]
+    fn test_parse_priority_fee() {
+        assert_eq!(parse_priority_fee(&[]), (0, false));
+
+        let mut trailer = [0u8; 9];
+        trailer[0..8].copy_from_slice(&7_500u64.to_le_bytes());
+        assert_eq!(parse_priority_fee(&trailer[..8]), (7_500, false));
+
+        trailer[8] = 1;
+        assert_eq!(parse_priority_fee(&trailer), (7_500, true));
+    }
+
+    #[test]
+    fn test_parse_dry_run() {
+        let mut data = [0u8; 22];
+        data[0..2].copy_from_slice(&10000u16.to_le_bytes());
+        assert!(!RebalanceData::try_from(&data[..12]).unwrap().dry_run);
+        assert!(!RebalanceData::try_from(&data[..21]).unwrap().dry_run);
+        assert!(!RebalanceData::try_from(&data[..]).unwrap().dry_run);
+
+        data[21] = 1;
+        assert!(RebalanceData::try_from(&data[..]).unwrap().dry_run);
+    }
+
+    #[test]
+    fn test_parse_errors_are_typed() {
+        // 3000 + 3000 + 2000 + 1000 + 1000, then 50 bps slippage
+        let mut data = [0u8; 12];
+        for (i, bps) in [3000u16, 3000, 2000, 1000, 1000, 50].iter().enumerate() {
+            data[i * 2..i * 2 + 2].copy_from_slice(&bps.to_le_bytes());
+        }
+        assert!(RebalanceData::try_from(&data[..]).is_ok());
+
+        // Custom codes the agent sees
+        assert_eq!(RebalanceData::try_from(&data[..11]).err(), Some(ProgramError::Custom(24)));
+        assert_eq!(
+            RebalanceData::try_from(&data[..11]).err(),
+            Some(OracleError::DataTooShort.into())
+        );
+
+        data[6..8].copy_from_slice(&999u16.to_le_bytes());
+        assert_eq!(RebalanceData::try_from(&data[..]).err(), Some(ProgramError::Custom(23)));
+        assert_eq!(
+            RebalanceData::try_from(&data[..]).err(),
+            Some(OracleError::InvalidAllocation.into())
+        );
+    }
+
+    #[test]
+    fn test_five_bucket_allocation() {
+        let encode = |allocation: [u16; 5]| {
+            let mut data = [0u8; 12];
+            for (i, bps) in allocation.iter().chain(&[50]).enumerate() {
+                data[i * 2..i * 2 + 2].copy_from_slice(&bps.to_le_bytes());
+            }
+            data
+        };
+
+        // Jito (bucket 4) takes a share like any other protocol
+        let data = RebalanceData::try_from(&encode([1000, 2000, 3000, 1500, 2500])[..]).unwrap();
+        assert_eq!(data.target_allocation_bps[protocol::JITO as usize], 2500);
+        assert_eq!(data.max_slippage_bps, 50);
+
+        let state = &mut OracleState::test_state();
+        data.apply(state).unwrap();
+        assert_eq!(state.current_allocation_bps(), [1000, 2000, 3000, 1500, 2500]);
+
+        // One bps short
+        assert_eq!(
+            RebalanceData::try_from(&encode([1000, 2000, 3000, 1500, 2499])[..]).err(),
+            Some(OracleError::InvalidAllocation.into())
+        );
+    }
+
+    #[test]
+    fn test_round_to_lot_disabled() {
+        let allocation = [2501, 2499, 3100, 1900, 0];
+        assert_eq!(round_to_lot(allocation, 0), allocation);
+    }
+}
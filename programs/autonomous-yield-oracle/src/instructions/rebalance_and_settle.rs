@@ -0,0 +1,248 @@
+//! Rebalance And Settle instruction
+//!
+//! The complete rebalance path. The target is checked against every guard
+//! (authority, pause, paused protocols, priority fee, rebalance phase),
+//! the legs are executed, and only then is the result settled in one step:
+//! the legs' cost is booked as PnL, `total_value_managed` moves by the same
+//! amount, the allocation is updated and `last_rebalance_at` stamped, and a
+//! performance fee on any profit is paid to the treasury, passed after the
+//! last leg. Any failure aborts the transaction, so nothing is left
+//! half-applied.
+//!
+//! With `dry_run` set the same checks run, but the planned legs are only
+//! logged (`AYO|REBALANCE_PLAN`): nothing is swapped, and neither the
+//! allocation nor the decision counters move.
+
+use pinocchio::{
+    sysvars::{clock::Clock, Sysvar},
+    AccountView, Address, ProgramResult,
+};
+use solana_program_error::ProgramError;
+
+use crate::logging;
+use crate::state::OracleState;
+use crate::error::OracleError;
+use super::{
+    collect_performance_fee, load_oracle, min_leg_out, swap_raydium_signed, RebalanceAccounts,
+    RebalanceData, RebalanceLeg, LEG_ACCOUNTS,
+};
+
+impl RebalanceData {
+    /// Write an executed rebalance to the oracle state
+    ///
+    /// `pnl` is what the legs returned less what they sent (their slippage
+    /// and fees, so usually negative), or `None` when no leg ran. Every
+    /// check runs before anything is written.
+    pub fn settle(&self, state: &mut OracleState, pnl: Option<i64>, now: i64) -> ProgramResult {
+        let settled_tvl = match pnl {
+            Some(pnl) => state.value_after_pnl(pnl)?,
+            None => state.total_value_managed(),
+        };
+        if let Some(pnl) = pnl {
+            state.cumulative_pnl().checked_add(pnl).ok_or(OracleError::PnlOverflow)?;
+        }
+
+        // Validates the plan before writing
+        self.apply(state)?;
+
+        // Only swaps book PnL; a move with nothing managed isn't a sample
+        if let Some(pnl) = pnl {
+            state.book_pnl(pnl, settled_tvl)?;
+        }
+        state.set_last_rebalance_at(now);
+
+        Ok(())
+    }
+}
+
+/// Rebalance And Settle instruction
+pub struct RebalanceAndSettle<'a> {
+    pub accounts: RebalanceAccounts<'a>,
+    pub data: RebalanceData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for RebalanceAndSettle<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = RebalanceAccounts::try_from(accounts)?;
+        let data = RebalanceData::try_from(data)?;
+        Ok(Self { accounts, data })
+    }
+}
+
+impl<'a> RebalanceAndSettle<'a> {
+    pub fn process(&self, program_id: &Address) -> ProgramResult {
+        let now = Clock::get()?.unix_timestamp;
+
+        // Program-owned, initialized oracle only
+        load_oracle(self.accounts.oracle, program_id)?;
+
+        let (snapshot, legs) = {
+            let oracle_data = self.accounts.oracle.try_borrow()?;
+            let state = OracleState::from_bytes(&oracle_data)?;
+
+            // Verify initialized, then authority
+            state.require_authority(self.accounts.authority.address().as_ref())?;
+
+            // Refuse to act while paused
+            state.require_not_paused()?;
+
+            let legs = self.data.legs(state, now)?;
+            if self.data.dry_run {
+                for leg in legs.iter().flatten() {
+                    let (from, to) = (leg.from as u8, leg.to as u8);
+                    logging::format_rebalance_plan(from, to, leg.amount_lamports).log();
+                }
+                return Ok(());
+            }
+            (*state, legs)
+        };
+
+        // Any leg failing aborts the whole transaction, state included
+        let pnl = self.swap_legs(program_id, &snapshot, &legs)?;
+        let executed = legs.iter().flatten().count();
+        let pnl = (executed > 0).then_some(pnl);
+
+        {
+            let mut oracle_data = self.accounts.oracle.try_borrow_mut()?;
+            let state = OracleState::from_bytes_mut(&mut oracle_data)?;
+            state.roll_decision_window(now);
+            self.data.settle(state, pnl, now)?;
+        }
+
+        if let Some(pnl) = pnl {
+            let treasury = self.accounts.remaining.get(executed * LEG_ACCOUNTS);
+            collect_performance_fee(self.accounts.oracle, treasury, pnl)?;
+        }
+
+        Ok(())
+    }
+
+    /// Execute each leg, returning the PnL booked across them
+    ///
+    /// `signer` is a copy of the oracle's state (see `swap_signed`).
+    fn swap_legs(
+        &self,
+        program_id: &Address,
+        signer: &OracleState,
+        legs: &[Option<RebalanceLeg>],
+    ) -> Result<i64, ProgramError> {
+        let mut leg_accounts = self.accounts.remaining.chunks_exact(LEG_ACCOUNTS);
+        let mut pnl = 0i64;
+
+        for leg in legs.iter().flatten() {
+            let Some([source_token, dest_token, pool @ ..]) = leg_accounts.next() else {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+
+            let amount_out = swap_raydium_signed(
+                program_id,
+                self.accounts.oracle,
+                signer,
+                source_token,
+                dest_token,
+                pool,
+                leg.amount_lamports,
+                min_leg_out(leg.amount_lamports, self.data.max_slippage_bps),
+            )?;
+
+            let leg_pnl = (amount_out as i128 - leg.amount_lamports as i128)
+                .clamp(i64::MIN as i128, i64::MAX as i128) as i64;
+            pnl = pnl.saturating_add(leg_pnl);
+        }
+
+        Ok(pnl)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instructions::protocol;
+
+    fn rebalance(target: [u16; 5], priority_fee_lamports: u64) -> RebalanceData {
+        RebalanceData {
+            target_allocation_bps: target,
+            max_slippage_bps: 50,
+            priority_fee_lamports,
+            urgent: false,
+            dry_run: false,
+        }
+    }
+
+    #[test]
+    fn test_settle_books_swap_cost() {
+        let state = &mut OracleState::test_state();
+        state.set_total_value_managed(1_000_000);
+        state.set_current_allocation_bps([10000, 0, 0, 0, 0]).unwrap();
+
+        rebalance([5000, 5000, 0, 0, 0], 0).settle(state, Some(-2_500), 1_700).unwrap();
+
+        assert_eq!(state.current_allocation_bps(), [5000, 5000, 0, 0, 0]);
+        assert_eq!(state.cumulative_pnl(), -2_500);
+        assert_eq!(state.total_value_managed(), 997_500);
+        assert_eq!(state.last_rebalance_at(), 1_700);
+        assert_eq!(state.decisions_count(), 1);
+
+        // Nothing swapped: no PnL sample, value untouched
+        rebalance([2000; 5], 0).settle(state, None, 1_800).unwrap();
+        assert_eq!(state.pnl_history().count(), 1);
+        assert_eq!(state.total_value_managed(), 997_500);
+        assert_eq!(state.last_rebalance_at(), 1_800);
+    }
+
+    #[test]
+    fn test_dry_run_plans_without_writing() {
+        let state = &mut OracleState::test_state();
+        state.set_total_value_managed(1_000_000);
+        state.set_current_allocation_bps([10000, 0, 0, 0, 0]).unwrap();
+        state.set_max_priority_fee_lamports(5_000);
+        state.set_protocol_paused_until(protocol::KAMINO, 2_000).unwrap();
+        let before = *state;
+
+        let dry_run = |target, priority_fee_lamports| RebalanceData {
+            dry_run: true,
+            ..rebalance(target, priority_fee_lamports)
+        };
+
+        let legs = dry_run([5000, 5000, 0, 0, 0], 0).legs(state, 1_700).unwrap();
+        assert_eq!(legs[0], Some(RebalanceLeg { from: 0, to: 1, amount_lamports: 500_000 }));
+        assert_eq!(legs.iter().flatten().count(), 1);
+
+        // Inputs are still checked
+        assert_eq!(
+            dry_run([5000, 5000, 0, 0, 0], 5_001).legs(state, 1_700),
+            Err(OracleError::GasPriceTooHigh.into())
+        );
+        assert_eq!(
+            dry_run([5000, 0, 5000, 0, 0], 0).legs(state, 1_700),
+            Err(OracleError::ProtocolPaused.into())
+        );
+
+        assert_eq!(state.current_allocation_bps(), [10000, 0, 0, 0, 0]);
+        assert_eq!(state.decisions_count(), 0);
+        assert_eq!(state.as_bytes(), before.as_bytes());
+    }
+
+    #[test]
+    fn test_settle_abort_leaves_state_unchanged() {
+        let state = &mut OracleState::test_state();
+        state.set_total_value_managed(1_000);
+        state.set_max_priority_fee_lamports(5_000);
+        let before = *state;
+
+        // Fee guard
+        assert_eq!(
+            rebalance([2000; 5], 5_001).settle(state, Some(-10), 1_700),
+            Err(OracleError::GasPriceTooHigh.into())
+        );
+
+        // Cost larger than the value managed
+        assert_eq!(
+            rebalance([2000; 5], 0).settle(state, Some(-1_001), 1_700),
+            Err(OracleError::InsufficientFunds.into())
+        );
+        assert_eq!(state.as_bytes(), before.as_bytes());
+    }
+}
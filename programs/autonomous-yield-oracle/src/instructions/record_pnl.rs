@@ -0,0 +1,192 @@
+//! Record PnL instruction
+//!
+//! Books the realized PnL of trades settled off-chain, which the program
+//! can't observe itself. The delta moves `total_value_managed` by the same
+//! amount. Each record carries a timestamp that must be later than the
+//! last one, so a resubmitted record can't be counted twice.
+
+use pinocchio::{
+    sysvars::{clock::Clock, Sysvar},
+    AccountView, Address, ProgramResult,
+};
+use solana_program_error::ProgramError;
+
+use crate::state::OracleState;
+use crate::error::OracleError;
+use super::{load_oracle, MAX_CLOCK_DRIFT_SECS};
+
+/// Accounts required for recording PnL
+pub struct RecordPnlAccounts<'a> {
+    /// The oracle account
+    pub oracle: &'a AccountView,
+    /// The authority (must sign)
+    pub authority: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for RecordPnlAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [oracle, authority, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !authority.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self { oracle, authority })
+    }
+}
+
+/// Instruction data for recording PnL
+/// Layout: delta (8, i64 le) + timestamp (8) = 16 bytes
+pub struct RecordPnlData {
+    /// Realized PnL in lamports (negative = loss)
+    pub delta: i64,
+    /// Unix timestamp of the settlement
+    pub timestamp: i64,
+}
+
+impl TryFrom<&[u8]> for RecordPnlData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() < 16 {
+            return Err(OracleError::DataTooShort.into());
+        }
+
+        Ok(Self {
+            delta: i64::from_le_bytes(data[0..8].try_into().unwrap()),
+            timestamp: i64::from_le_bytes(data[8..16].try_into().unwrap()),
+        })
+    }
+}
+
+impl RecordPnlData {
+    /// Book this record in the oracle state as of `now`
+    ///
+    /// Fails with `InvalidTimestamp` unless the timestamp is later than the
+    /// last record and not ahead of `now`, and with `InsufficientFunds` for
+    /// a loss larger than the value managed, and with `PnlOverflow` where the
+    /// cumulative PnL would leave the i64 range. Nothing is written on
+    /// failure.
+    pub fn apply(&self, state: &mut OracleState, now: i64) -> ProgramResult {
+        if self.timestamp <= state.last_pnl_record_at()
+            || self.timestamp > now.saturating_add(MAX_CLOCK_DRIFT_SECS)
+        {
+            return Err(OracleError::InvalidTimestamp.into());
+        }
+
+        let new_tvl = state.value_after_pnl(self.delta)?;
+        state.book_pnl(self.delta, new_tvl)?;
+        state.set_last_pnl_record_at(self.timestamp);
+        Ok(())
+    }
+}
+
+/// Record PnL instruction
+pub struct RecordPnl<'a> {
+    pub accounts: RecordPnlAccounts<'a>,
+    pub data: RecordPnlData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for RecordPnl<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = RecordPnlAccounts::try_from(accounts)?;
+        let data = RecordPnlData::try_from(data)?;
+        Ok(Self { accounts, data })
+    }
+}
+
+impl<'a> RecordPnl<'a> {
+    pub fn process(&self, program_id: &Address) -> ProgramResult {
+        let now = Clock::get()?.unix_timestamp;
+
+        // Program-owned, initialized oracle only
+        load_oracle(self.accounts.oracle, program_id)?;
+
+        let mut oracle_data = self.accounts.oracle.try_borrow_mut()?;
+        let state = OracleState::from_bytes_mut(&mut oracle_data)?;
+
+        // Verify initialized, then authority
+        state.require_authority(self.accounts.authority.address().as_ref())?;
+
+        self.data.apply(state, now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(delta: i64, timestamp: i64) -> RecordPnlData {
+        let mut data = [0u8; 16];
+        data[0..8].copy_from_slice(&delta.to_le_bytes());
+        data[8..16].copy_from_slice(&timestamp.to_le_bytes());
+        RecordPnlData::try_from(&data[..]).unwrap()
+    }
+
+    #[test]
+    fn test_record_gain() {
+        let state = &mut OracleState::test_state();
+        state.set_total_value_managed(1_000_000);
+
+        record(50_000, 1_000).apply(state, 1_000).unwrap();
+        assert_eq!(state.cumulative_pnl(), 50_000);
+        assert_eq!(state.peak_pnl(), 50_000);
+        assert_eq!(state.total_value_managed(), 1_050_000);
+        assert_eq!(state.last_pnl_record_at(), 1_000);
+    }
+
+    #[test]
+    fn test_record_loss() {
+        let state = &mut OracleState::test_state();
+        state.set_total_value_managed(1_000_000);
+
+        record(-200_000, 1_000).apply(state, 1_000).unwrap();
+        assert_eq!(state.cumulative_pnl(), -200_000);
+        assert_eq!(state.total_value_managed(), 800_000);
+
+        // A loss can't take more than is managed
+        assert_eq!(
+            record(-800_001, 1_001).apply(state, 1_001),
+            Err(OracleError::InsufficientFunds.into())
+        );
+        assert_eq!(state.cumulative_pnl(), -200_000);
+        assert_eq!(state.total_value_managed(), 800_000);
+        assert_eq!(state.last_pnl_record_at(), 1_000);
+
+        assert_eq!(
+            RecordPnlData::try_from(&[0u8; 15][..]).err(),
+            Some(OracleError::DataTooShort.into())
+        );
+    }
+
+    #[test]
+    fn test_rejects_stale_or_future_timestamp() {
+        let state = &mut OracleState::test_state();
+        state.set_total_value_managed(1_000_000);
+
+        record(10_000, 1_000).apply(state, 1_000).unwrap();
+
+        // The same record again, or an older one, would count twice
+        for timestamp in [1_000, 999] {
+            assert_eq!(
+                record(10_000, timestamp).apply(state, 1_100),
+                Err(OracleError::InvalidTimestamp.into())
+            );
+        }
+        assert_eq!(
+            record(10_000, 1_100 + MAX_CLOCK_DRIFT_SECS + 1).apply(state, 1_100),
+            Err(OracleError::InvalidTimestamp.into())
+        );
+        assert_eq!(state.cumulative_pnl(), 10_000);
+        assert_eq!(state.total_value_managed(), 1_010_000);
+
+        record(10_000, 1_001).apply(state, 1_100).unwrap();
+        assert_eq!(state.cumulative_pnl(), 20_000);
+    }
+}
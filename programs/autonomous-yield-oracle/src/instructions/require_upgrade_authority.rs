@@ -0,0 +1,209 @@
+//! Require Upgrade Authority instruction
+//!
+//! The program's upgrade authority is a higher trust level than the
+//! oracle's authority. With `status::REQUIRE_UPGRADE_AUTHORITY` set,
+//! safety-critical settings (the fee destination) also need the upgrade
+//! authority's signature, read from the program data account. Turning the
+//! requirement on or off takes that signature too, so a leaked oracle
+//! authority can't lift it.
+//!
+//! Accounts: oracle, authority, then the `UpgradeAuthorityAccounts`.
+//! Data: enabled (1, 0 = off).
+
+use pinocchio::{AccountView, Address, ProgramResult};
+use solana_program_error::ProgramError;
+
+use crate::cpi::loader;
+use crate::error::OracleError;
+use crate::state::{status, OracleState};
+use super::load_oracle;
+
+/// The program data account and the upgrade authority it names (must sign)
+pub struct UpgradeAuthorityAccounts<'a> {
+    /// This program's program data account
+    pub program_data: &'a AccountView,
+    /// The program's upgrade authority
+    pub upgrade_authority: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for UpgradeAuthorityAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [program_data, upgrade_authority, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self {
+            program_data,
+            upgrade_authority,
+        })
+    }
+}
+
+impl<'a> UpgradeAuthorityAccounts<'a> {
+    /// Verify the upgrade authority of `program_id` signed
+    pub fn verify(&self, program_id: &Address) -> ProgramResult {
+        if !self.upgrade_authority.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        let authority = loader::upgrade_authority(self.program_data, program_id)?;
+        check_upgrade_authority(authority, self.upgrade_authority.address().as_ref())
+    }
+}
+
+/// Fail with `InvalidAuthority` unless `signer` is the upgrade authority
+///
+/// An immutable program (no upgrade authority) has no one to co-sign.
+pub fn check_upgrade_authority(authority: Option<[u8; 32]>, signer: &[u8]) -> ProgramResult {
+    match authority {
+        Some(authority) if authority.as_slice() == signer => Ok(()),
+        _ => Err(OracleError::InvalidAuthority.into()),
+    }
+}
+
+/// Require the upgrade authority's co-signature if the oracle asks for it
+///
+/// `co_signer` is whatever followed the instruction's own accounts; missing
+/// accounts fail with `NotEnoughAccountKeys` only when they are needed.
+pub fn require_upgrade_cosigner(
+    state: &OracleState,
+    co_signer: Result<UpgradeAuthorityAccounts, ProgramError>,
+    program_id: &Address,
+) -> ProgramResult {
+    if !state.is_set(status::REQUIRE_UPGRADE_AUTHORITY) {
+        return Ok(());
+    }
+    co_signer?.verify(program_id)
+}
+
+/// Accounts required for requiring the upgrade authority
+pub struct RequireUpgradeAuthorityAccounts<'a> {
+    /// The oracle account
+    pub oracle: &'a AccountView,
+    /// The authority (must sign)
+    pub authority: &'a AccountView,
+    /// The upgrade authority, co-signing
+    pub upgrade: UpgradeAuthorityAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for RequireUpgradeAuthorityAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [oracle, authority, rest @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !authority.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self {
+            oracle,
+            authority,
+            upgrade: UpgradeAuthorityAccounts::try_from(rest)?,
+        })
+    }
+}
+
+/// Instruction data for requiring the upgrade authority
+/// Layout: enabled (1) = 1 byte
+pub struct RequireUpgradeAuthorityData {
+    /// Whether settings need the upgrade authority's co-signature
+    pub enabled: bool,
+}
+
+impl TryFrom<&[u8]> for RequireUpgradeAuthorityData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        let Some(&enabled) = data.first() else {
+            return Err(ProgramError::InvalidInstructionData);
+        };
+
+        Ok(Self {
+            enabled: enabled != 0,
+        })
+    }
+}
+
+/// Require Upgrade Authority instruction
+pub struct RequireUpgradeAuthority<'a> {
+    pub accounts: RequireUpgradeAuthorityAccounts<'a>,
+    pub data: RequireUpgradeAuthorityData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for RequireUpgradeAuthority<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = RequireUpgradeAuthorityAccounts::try_from(accounts)?;
+        let data = RequireUpgradeAuthorityData::try_from(data)?;
+        Ok(Self { accounts, data })
+    }
+}
+
+impl<'a> RequireUpgradeAuthority<'a> {
+    pub fn process(&self, program_id: &Address) -> ProgramResult {
+        // Program-owned, initialized oracle only
+        load_oracle(self.accounts.oracle, program_id)?;
+
+        let mut oracle_data = self.accounts.oracle.try_borrow_mut()?;
+        let state = OracleState::from_bytes_mut(&mut oracle_data)?;
+
+        // Verify initialized, then authority
+        state.require_authority(self.accounts.authority.address().as_ref())?;
+
+        // Either way, the upgrade authority must agree
+        self.accounts.upgrade.verify(program_id)?;
+
+        if self.data.enabled {
+            state.set(status::REQUIRE_UPGRADE_AUTHORITY);
+        } else {
+            state.clear(status::REQUIRE_UPGRADE_AUTHORITY);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_upgrade_authority() {
+        let upgrade_authority = [7u8; 32];
+        assert_eq!(check_upgrade_authority(Some(upgrade_authority), &[7u8; 32]), Ok(()));
+
+        // Anyone else, including the oracle authority, is refused
+        assert_eq!(
+            check_upgrade_authority(Some(upgrade_authority), &[8u8; 32]),
+            Err(OracleError::InvalidAuthority.into())
+        );
+
+        // An immutable program has no upgrade authority to co-sign
+        assert_eq!(
+            check_upgrade_authority(None, &[0u8; 32]),
+            Err(OracleError::InvalidAuthority.into())
+        );
+    }
+
+    #[test]
+    fn test_cosigner_only_needed_when_required() {
+        let state = &mut OracleState::test_state();
+        let program_id = Address::new_from_array([1u8; 32]);
+
+        assert_eq!(
+            require_upgrade_cosigner(state, Err(ProgramError::NotEnoughAccountKeys), &program_id),
+            Ok(())
+        );
+
+        state.set(status::REQUIRE_UPGRADE_AUTHORITY);
+        assert_eq!(
+            require_upgrade_cosigner(state, Err(ProgramError::NotEnoughAccountKeys), &program_id),
+            Err(ProgramError::NotEnoughAccountKeys)
+        );
+    }
+}
@@ -0,0 +1,122 @@
+//! Reset Stats instruction
+//!
+//! Starts a new stats epoch, so reported performance covers one strategy
+//! at a time: counters, booked PnL, and drawdown go back to zero (see
+//! `OracleState::reset_stats`), while the authority, settings, current
+//! decision, and value managed are kept. The oracle is otherwise left as
+//! it was; `last_reset_ts` records when the epoch began.
+//!
+//! Accounts: oracle, authority. No data.
+
+use pinocchio::{
+    sysvars::{clock::Clock, Sysvar},
+    AccountView, Address, ProgramResult,
+};
+use solana_program_error::ProgramError;
+
+use crate::logging;
+use crate::state::OracleState;
+use super::load_oracle;
+
+/// Accounts required for resetting stats
+pub struct ResetStatsAccounts<'a> {
+    /// The oracle account
+    pub oracle: &'a AccountView,
+    /// The authority (must sign)
+    pub authority: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for ResetStatsAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [oracle, authority, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !authority.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self { oracle, authority })
+    }
+}
+
+/// Reset Stats instruction
+pub struct ResetStats<'a> {
+    pub accounts: ResetStatsAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for ResetStats<'a> {
+    type Error = ProgramError;
+
+    fn try_from((_data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = ResetStatsAccounts::try_from(accounts)?;
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> ResetStats<'a> {
+    pub fn process(&self, program_id: &Address) -> ProgramResult {
+        // Program-owned, initialized oracle only
+        load_oracle(self.accounts.oracle, program_id)?;
+
+        let mut oracle_data = self.accounts.oracle.try_borrow_mut()?;
+        let state = OracleState::from_bytes_mut(&mut oracle_data)?;
+
+        // Verify initialized, then authority
+        state.require_authority(self.accounts.authority.address().as_ref())?;
+
+        let now = Clock::get()?.unix_timestamp;
+        state.reset_stats(now);
+
+        logging::format_reset_stats(now, state.total_value_managed()).log();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::instructions::protocol;
+    use crate::state::{status, OracleState};
+
+    #[test]
+    fn test_reset_keeps_authority_and_value() {
+        let state = &mut OracleState::test_state();
+        state.set(status::INITIALIZED);
+        state.authority = [7u8; 32];
+        state.best_protocol = protocol::JITO;
+        state.set_max_acceptable_risk(40);
+        state.set_created_at(1_000);
+
+        // A first epoch: deposits, decisions, a swap, and a loss
+        state.deposit_value(2_000_000_000).unwrap();
+        state.increment_decisions().unwrap();
+        state.increment_swaps().unwrap();
+        state.book_pnl(500_000_000, 2_500_000_000).unwrap();
+        state.book_pnl(-1_000_000_000, 1_500_000_000).unwrap();
+        assert_eq!(state.decisions_count(), 2);
+        assert!(state.current_drawdown_bps() > 0);
+
+        state.reset_stats(50_000);
+
+        assert_eq!(state.decisions_count(), 0);
+        assert_eq!(state.swaps_count(), 0);
+        assert_eq!(state.cumulative_pnl(), 0);
+        assert_eq!(state.peak_pnl(), 0);
+        assert_eq!(state.pnl_history().count(), 0);
+        assert_eq!(state.current_drawdown_bps(), 0);
+        assert_eq!(state.max_drawdown_bps(), 0);
+        assert_eq!(state.true_pnl(), 0);
+        assert_eq!(state.last_reset_ts(), 50_000);
+        assert_eq!(state.stats_since(), 50_000);
+
+        // Untouched
+        assert_eq!(state.authority, [7u8; 32]);
+        assert_eq!(state.best_protocol, protocol::JITO);
+        assert_eq!(state.max_acceptable_risk(), 40);
+        assert_eq!(state.total_value_managed(), 1_500_000_000);
+        assert_eq!(state.created_at(), 1_000);
+        assert!(state.check_invariants().is_ok());
+    }
+}
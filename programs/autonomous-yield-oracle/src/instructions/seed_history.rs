@@ -0,0 +1,154 @@
+//! Seed History instruction
+//!
+//! One-time import of lifetime metrics (PnL, peak, decision count, value
+//! managed) when migrating from an older oracle or off-chain bookkeeping.
+//! Must run right after `Initialize`, before any decision is recorded.
+
+use pinocchio::{AccountView, Address, ProgramResult};
+use solana_program_error::ProgramError;
+
+use crate::state::OracleState;
+use super::load_oracle;
+
+/// Accounts required for seeding history
+pub struct SeedHistoryAccounts<'a> {
+    /// The oracle account
+    pub oracle: &'a AccountView,
+    /// The authority (must sign)
+    pub authority: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for SeedHistoryAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [oracle, authority, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !authority.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self { oracle, authority })
+    }
+}
+
+/// Instruction data for seeding history
+/// Layout: cumulative_pnl (8) + peak_pnl (8) + decisions_count (8) +
+/// total_value_managed (8) = 32 bytes
+pub struct SeedHistoryData {
+    /// Lifetime profit/loss in lamports
+    pub cumulative_pnl: i64,
+    /// Highest lifetime PnL in lamports
+    pub peak_pnl: i64,
+    /// Decisions made by the prior system
+    pub decisions_count: u64,
+    /// Value managed in lamports
+    pub total_value_managed: u64,
+}
+
+impl TryFrom<&[u8]> for SeedHistoryData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() < 32 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            cumulative_pnl: i64::from_le_bytes(data[0..8].try_into().unwrap()),
+            peak_pnl: i64::from_le_bytes(data[8..16].try_into().unwrap()),
+            decisions_count: u64::from_le_bytes(data[16..24].try_into().unwrap()),
+            total_value_managed: u64::from_le_bytes(data[24..32].try_into().unwrap()),
+        })
+    }
+}
+
+impl SeedHistoryData {
+    /// Write the starting values into the oracle state
+    pub fn apply(&self, state: &mut OracleState) -> ProgramResult {
+        state.seed_history(
+            self.cumulative_pnl,
+            self.peak_pnl,
+            self.decisions_count,
+            self.total_value_managed,
+        )
+    }
+}
+
+/// Seed History instruction
+pub struct SeedHistory<'a> {
+    pub accounts: SeedHistoryAccounts<'a>,
+    pub data: SeedHistoryData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for SeedHistory<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = SeedHistoryAccounts::try_from(accounts)?;
+        let data = SeedHistoryData::try_from(data)?;
+        Ok(Self { accounts, data })
+    }
+}
+
+impl<'a> SeedHistory<'a> {
+    pub fn process(&self, program_id: &Address) -> ProgramResult {
+        // Program-owned, initialized oracle only
+        load_oracle(self.accounts.oracle, program_id)?;
+
+        let mut oracle_data = self.accounts.oracle.try_borrow_mut()?;
+        let state = OracleState::from_bytes_mut(&mut oracle_data)?;
+
+        // Verify initialized, then authority
+        state.require_authority(self.accounts.authority.address().as_ref())?;
+
+        self.data.apply(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::OracleError;
+
+    fn seed() -> SeedHistoryData {
+        SeedHistoryData {
+            cumulative_pnl: 250_000_000,
+            peak_pnl: 400_000_000,
+            decisions_count: 42,
+            total_value_managed: 5_000_000_000,
+        }
+    }
+
+    #[test]
+    fn test_seed_history() {
+        let state = &mut OracleState::test_state();
+
+        seed().apply(state).unwrap();
+        assert!(state.is_history_seeded());
+        assert_eq!(state.cumulative_pnl(), 250_000_000);
+        assert_eq!(state.peak_pnl(), 400_000_000);
+        assert_eq!(state.decisions_count(), 42);
+        assert_eq!(state.total_value_managed(), 5_000_000_000);
+        assert_eq!(state.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn test_seed_history_only_once() {
+        let state = &mut OracleState::test_state();
+
+        seed().apply(state).unwrap();
+        assert_eq!(seed().apply(state), Err(OracleError::HistoryAlreadySeeded.into()));
+    }
+
+    #[test]
+    fn test_seed_history_rejected_after_decisions() {
+        let state = &mut OracleState::test_state();
+        state.increment_decisions().unwrap();
+
+        assert_eq!(seed().apply(state), Err(OracleError::HistoryAlreadySeeded.into()));
+        assert!(!state.is_history_seeded());
+    }
+}
@@ -0,0 +1,90 @@
+//! Set Max Priority Fee instruction
+//!
+//! Records the highest priority fee the agent should pay for non-urgent
+//! trades. `Rebalance` and `ExecuteSwap` carry the fee they were sent with
+//! and fail with `GasPriceTooHigh` above this cap unless flagged urgent.
+
+use pinocchio::{AccountView, Address, ProgramResult};
+use solana_program_error::ProgramError;
+
+use crate::state::OracleState;
+use super::load_oracle;
+
+/// Accounts required for setting the fee cap
+pub struct SetMaxPriorityFeeAccounts<'a> {
+    /// The oracle account
+    pub oracle: &'a AccountView,
+    /// The authority (must sign)
+    pub authority: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for SetMaxPriorityFeeAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [oracle, authority, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !authority.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self { oracle, authority })
+    }
+}
+
+/// Instruction data for setting the fee cap
+/// Layout: max_priority_fee_lamports (8) = 8 bytes
+pub struct SetMaxPriorityFeeData {
+    /// Highest priority fee for non-urgent trades (0 = no cap)
+    pub max_priority_fee_lamports: u64,
+}
+
+impl TryFrom<&[u8]> for SetMaxPriorityFeeData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() < 8 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            max_priority_fee_lamports: u64::from_le_bytes(data[0..8].try_into().unwrap()),
+        })
+    }
+}
+
+/// Set Max Priority Fee instruction
+pub struct SetMaxPriorityFee<'a> {
+    pub accounts: SetMaxPriorityFeeAccounts<'a>,
+    pub data: SetMaxPriorityFeeData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for SetMaxPriorityFee<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = SetMaxPriorityFeeAccounts::try_from(accounts)?;
+        let data = SetMaxPriorityFeeData::try_from(data)?;
+        Ok(Self { accounts, data })
+    }
+}
+
+impl<'a> SetMaxPriorityFee<'a> {
+    pub fn process(&self, program_id: &Address) -> ProgramResult {
+        // Program-owned, initialized oracle only
+        load_oracle(self.accounts.oracle, program_id)?;
+
+        let mut oracle_data = self.accounts.oracle.try_borrow_mut()?;
+        let state = OracleState::from_bytes_mut(&mut oracle_data)?;
+
+        // Verify initialized, then authority
+        state.require_authority(self.accounts.authority.address().as_ref())?;
+
+        state.require_config_unlocked()?;
+        state.set_max_priority_fee_lamports(self.data.max_priority_fee_lamports);
+
+        Ok(())
+    }
+}
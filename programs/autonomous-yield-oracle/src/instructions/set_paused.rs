@@ -0,0 +1,136 @@
+//! Set Paused instruction
+//!
+//! Pauses or resumes the oracle. While paused, every mutating instruction
+//! except `EmergencyWithdraw` fails with `EmergencyModeActive`.
+//!
+//! The guardian, if one is set, may pause too, so an automated watchdog
+//! can halt the oracle without being able to move funds. Only the
+//! authority resumes.
+
+use pinocchio::{AccountView, Address, ProgramResult};
+use solana_program_error::ProgramError;
+
+use crate::state::OracleState;
+use super::load_oracle;
+
+/// Accounts required for pausing
+pub struct SetPausedAccounts<'a> {
+    /// The oracle account
+    pub oracle: &'a AccountView,
+    /// The authority, or the guardian when pausing (must sign)
+    pub authority: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for SetPausedAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [oracle, authority, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !authority.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self { oracle, authority })
+    }
+}
+
+/// Instruction data for pausing
+/// Layout: paused (1) = 1 byte
+pub struct SetPausedData {
+    /// New paused flag (0 = resume, 1 = pause)
+    pub paused: bool,
+}
+
+impl TryFrom<&[u8]> for SetPausedData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        match data.first() {
+            Some(0) => Ok(Self { paused: false }),
+            Some(1) => Ok(Self { paused: true }),
+            _ => Err(ProgramError::InvalidInstructionData),
+        }
+    }
+}
+
+impl SetPausedData {
+    /// Apply the flag on behalf of `signer`
+    ///
+    /// Fails with `InvalidAuthority` unless `signer` is the authority, or
+    /// the guardian when pausing.
+    pub fn apply(&self, state: &mut OracleState, signer: &[u8]) -> ProgramResult {
+        // Verify initialized, then authority (or guardian)
+        if self.paused {
+            state.require_pauser(signer)?;
+        } else {
+            state.require_authority(signer)?;
+        }
+
+        state.set_paused(self.paused);
+        Ok(())
+    }
+}
+
+/// Set Paused instruction
+pub struct SetPaused<'a> {
+    pub accounts: SetPausedAccounts<'a>,
+    pub data: SetPausedData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for SetPaused<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = SetPausedAccounts::try_from(accounts)?;
+        let data = SetPausedData::try_from(data)?;
+        Ok(Self { accounts, data })
+    }
+}
+
+impl<'a> SetPaused<'a> {
+    pub fn process(&self, program_id: &Address) -> ProgramResult {
+        // Program-owned, initialized oracle only
+        load_oracle(self.accounts.oracle, program_id)?;
+
+        let mut oracle_data = self.accounts.oracle.try_borrow_mut()?;
+        let state = OracleState::from_bytes_mut(&mut oracle_data)?;
+
+        self.data.apply(state, self.accounts.authority.address().as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::OracleError;
+    use crate::state::status;
+
+    #[test]
+    fn test_guardian_pauses_but_cannot_resume() {
+        let state = &mut OracleState::test_state();
+        state.set(status::INITIALIZED);
+        state.authority = [1u8; 32];
+        state.guardian = [2u8; 32];
+
+        let pause = SetPausedData { paused: true };
+        let resume = SetPausedData { paused: false };
+
+        pause.apply(state, &[2u8; 32]).unwrap();
+        assert!(state.is_paused());
+
+        // EmergencyWithdraw checks the authority alone
+        assert_eq!(state.require_authority(&[2u8; 32]), Err(OracleError::InvalidAuthority.into()));
+
+        assert_eq!(resume.apply(state, &[2u8; 32]), Err(OracleError::InvalidAuthority.into()));
+        assert!(state.is_paused());
+        resume.apply(state, &[1u8; 32]).unwrap();
+        assert!(!state.is_paused());
+
+        // Anyone else can't pause
+        assert_eq!(pause.apply(state, &[3u8; 32]), Err(OracleError::InvalidAuthority.into()));
+        assert!(!state.is_paused());
+    }
+}
@@ -0,0 +1,91 @@
+//! Transfer Authority instruction
+//!
+//! First step of a two-step authority handoff: the current authority
+//! proposes a new authority, which must then sign `AcceptAuthority`.
+//! This prevents handing control to an address that can't sign.
+
+use pinocchio::{AccountView, Address, ProgramResult};
+use solana_program_error::ProgramError;
+
+use crate::state::OracleState;
+use super::{expect_accounts, load_oracle};
+
+/// Accounts required for proposing a new authority
+pub struct TransferAuthorityAccounts<'a> {
+    /// The oracle account
+    pub oracle: &'a AccountView,
+    /// The current authority (must sign)
+    pub authority: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for TransferAuthorityAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        // Exactly these two, so a misordered list fails loudly
+        expect_accounts(accounts, 2)?;
+        let [oracle, authority] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !authority.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self { oracle, authority })
+    }
+}
+
+/// Instruction data for proposing a new authority
+/// Layout: new_authority (32) = 32 bytes
+pub struct TransferAuthorityData {
+    /// Proposed authority (all zeros cancels a pending handoff)
+    pub new_authority: [u8; 32],
+}
+
+impl TryFrom<&[u8]> for TransferAuthorityData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() < 32 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            new_authority: data[0..32].try_into().unwrap(),
+        })
+    }
+}
+
+/// Transfer Authority instruction
+pub struct TransferAuthority<'a> {
+    pub accounts: TransferAuthorityAccounts<'a>,
+    pub data: TransferAuthorityData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for TransferAuthority<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = TransferAuthorityAccounts::try_from(accounts)?;
+        let data = TransferAuthorityData::try_from(data)?;
+        Ok(Self { accounts, data })
+    }
+}
+
+impl<'a> TransferAuthority<'a> {
+    pub fn process(&self, program_id: &Address) -> ProgramResult {
+        // Program-owned, initialized oracle only
+        load_oracle(self.accounts.oracle, program_id)?;
+
+        let mut oracle_data = self.accounts.oracle.try_borrow_mut()?;
+        let state = OracleState::from_bytes_mut(&mut oracle_data)?;
+
+        // Verify initialized, then authority
+        state.require_authority(self.accounts.authority.address().as_ref())?;
+
+        state.pending_authority = self.data.new_authority;
+
+        Ok(())
+    }
+}
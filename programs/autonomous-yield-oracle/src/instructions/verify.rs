@@ -0,0 +1,82 @@
+//! Verify instruction
+//!
+//! Asserts the oracle matches an expected snapshot, so keepers can check
+//! the state they depend on before acting. Read-only; no signer required.
+
+use pinocchio::{AccountView, Address, ProgramResult};
+use solana_program_error::ProgramError;
+
+use crate::logging;
+use crate::state::OracleState;
+use crate::error::OracleError;
+use super::load_oracle;
+
+/// Accounts required for verification
+pub struct VerifyAccounts<'a> {
+    /// The oracle account
+    pub oracle: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for VerifyAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [oracle, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self { oracle })
+    }
+}
+
+/// Instruction data for verification
+/// Layout: the expected canonical snapshot (`OracleState::LEN` bytes)
+pub struct VerifyData<'a> {
+    /// Expected snapshot, byte-for-byte the on-disk layout
+    pub expected: &'a [u8],
+}
+
+impl<'a> TryFrom<&'a [u8]> for VerifyData<'a> {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != OracleState::LEN {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self { expected: data })
+    }
+}
+
+/// Verify instruction
+pub struct Verify<'a> {
+    pub accounts: VerifyAccounts<'a>,
+    pub data: VerifyData<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for Verify<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = VerifyAccounts::try_from(accounts)?;
+        let data = VerifyData::try_from(data)?;
+        Ok(Self { accounts, data })
+    }
+}
+
+impl<'a> Verify<'a> {
+    pub fn process(&self, program_id: &Address) -> ProgramResult {
+        // Program-owned, initialized oracle only
+        load_oracle(self.accounts.oracle, program_id)?;
+
+        let oracle_data = self.accounts.oracle.try_borrow()?;
+        let state = OracleState::from_bytes(&oracle_data)?;
+
+        if let Some(field) = state.first_mismatch(self.data.expected) {
+            logging::format_mismatch(field).log();
+            return Err(OracleError::StateMismatch.into());
+        }
+
+        Ok(())
+    }
+}
@@ -7,18 +7,52 @@
 //!
 //! - `Initialize` (0): Set up the oracle with an authority
 //! - `MonitorYields` (1): Update yield data from protocols
-//! - `ExecuteSwap` (2): Execute swaps via Raydium/Jupiter
+//! - `ExecuteSwap` (2): Execute swaps via Raydium or a Jupiter route
 //! - `Rebalance` (3): Autonomous portfolio rebalancing
 //! - `PublishStrategy` (4): Publish strategy recommendations
 //! - `EmergencyWithdraw` (5): Safety withdrawal to authority
+//! - `Verify` (6): Assert the oracle matches an expected snapshot
+//! - `SetPaused` (7): Pause or resume the oracle
+//! - `TransferAuthority` (8): Propose a new authority
+//! - `AcceptAuthority` (9): Pending authority accepts control
+//! - `ComputeRealizedApy` (10): Store the APY delivered by booked PnL
+//! - `LockConfig` (11): Freeze settings
+//! - `UnlockConfig` (12): Timelocked unfreeze of settings
+//! - `SeedHistory` (13): Import lifetime metrics from a prior system
+//! - `InitializeWithConfig` (14): Initialize with a full set of settings
+//! - `HealthCheck` (15): Report status and back-off time via return data
+//! - `CloseOracle` (16): Close the oracle and reclaim its rent
+//! - `SetMaxPriorityFee` (17): Cap the priority fee of non-urgent trades
+//! - `GetState` (18): Return and log the state, with allocation in lamports
+//! - `PauseProtocolUntil` (19): Stop routing to one protocol for a while
+//! - `Configure` (20): Change a subset of the settings
+//! - `ComputeDownside` (21): Store the downside deviation of booked PnL
+//! - `ProposeFeeDestination` (22): Propose a new fee destination
+//! - `AcceptFeeDestination` (23): Switch fee destination after the timelock
+//! - `Deposit` (24): Move SOL into the oracle and track it
+//! - `CompareBenchmark` (25): Report whether realized APY beats a benchmark
+//! - `Migrate` (26): Upgrade an oracle from an older state layout
+//! - `RebalanceAndSettle` (27): Rebalance, then book its cost and value
+//! - `RecordPnl` (28): Book the PnL of a trade settled off-chain
+//! - `RequireNonces` (29): Stop accepting observations without a nonce
+//! - `RequireUpgradeAuthority` (30): Have the upgrade authority co-sign
+//!   safety-critical settings
+//! - `Query` (31): Return the current decision in a compact fixed layout
+//! - `ResetStats` (32): Zero the performance stats for a new epoch
+//! - `InitializeWithCreate` (33): Create the oracle PDA and initialize it
 //!
 //! Created by Turbinete 🚀 for the Colosseum Agent Hackathon 2026.
 
 use pinocchio::{AccountView, Address, entrypoint, ProgramResult};
 use solana_program_error::ProgramError;
 
+pub mod cpi;
 pub mod error;
 pub mod instructions;
+pub mod logging;
+pub mod math;
+pub mod notifier;
+pub mod scoring;
 pub mod state;
 
 use instructions::*;
@@ -36,29 +70,173 @@ pub const ID: Address = Address::new_from_array([
 
 /// Main instruction processor
 fn process_instruction(
-    _program_id: &Address,
+    program_id: &Address,
     accounts: &[AccountView],
     instruction_data: &[u8],
 ) -> ProgramResult {
     match instruction_data.split_first() {
         Some((&discriminator::INITIALIZE, data)) => {
-            Initialize::try_from((data, accounts))?.process()
+            Initialize::try_from((data, accounts))?.process(program_id)
         }
         Some((&discriminator::MONITOR_YIELDS, data)) => {
-            MonitorYields::try_from((data, accounts))?.process()
+            MonitorYields::try_from((data, accounts))?.process(program_id)
         }
         Some((&discriminator::EXECUTE_SWAP, data)) => {
-            ExecuteSwap::try_from((data, accounts))?.process()
+            ExecuteSwap::try_from((data, accounts))?.process(program_id)
         }
         Some((&discriminator::REBALANCE, data)) => {
-            Rebalance::try_from((data, accounts))?.process()
+            Rebalance::try_from((data, accounts))?.process(program_id)
         }
         Some((&discriminator::PUBLISH_STRATEGY, data)) => {
-            PublishStrategy::try_from((data, accounts))?.process()
+            PublishStrategy::try_from((data, accounts))?.process(program_id)
         }
         Some((&discriminator::EMERGENCY_WITHDRAW, data)) => {
-            EmergencyWithdraw::try_from((data, accounts))?.process()
+            EmergencyWithdraw::try_from((data, accounts))?.process(program_id)
         }
-        _ => Err(ProgramError::InvalidInstructionData),
+        Some((&discriminator::VERIFY, data)) => {
+            Verify::try_from((data, accounts))?.process(program_id)
+        }
+        Some((&discriminator::SET_PAUSED, data)) => {
+            SetPaused::try_from((data, accounts))?.process(program_id)
+        }
+        Some((&discriminator::TRANSFER_AUTHORITY, data)) => {
+            TransferAuthority::try_from((data, accounts))?.process(program_id)
+        }
+        Some((&discriminator::ACCEPT_AUTHORITY, data)) => {
+            AcceptAuthority::try_from((data, accounts))?.process(program_id)
+        }
+        Some((&discriminator::COMPUTE_REALIZED_APY, data)) => {
+            ComputeRealizedApy::try_from((data, accounts))?.process(program_id)
+        }
+        Some((&discriminator::LOCK_CONFIG, data)) => {
+            LockConfig::try_from((data, accounts))?.process(program_id)
+        }
+        Some((&discriminator::UNLOCK_CONFIG, data)) => {
+            UnlockConfig::try_from((data, accounts))?.process(program_id)
+        }
+        Some((&discriminator::SEED_HISTORY, data)) => {
+            SeedHistory::try_from((data, accounts))?.process(program_id)
+        }
+        Some((&discriminator::INITIALIZE_WITH_CONFIG, data)) => {
+            InitializeWithConfig::try_from((data, accounts))?.process(program_id)
+        }
+        Some((&discriminator::HEALTH_CHECK, data)) => {
+            HealthCheck::try_from((data, accounts))?.process(program_id)
+        }
+        Some((&discriminator::CLOSE_ORACLE, data)) => {
+            CloseOracle::try_from((data, accounts))?.process(program_id)
+        }
+        Some((&discriminator::SET_MAX_PRIORITY_FEE, data)) => {
+            SetMaxPriorityFee::try_from((data, accounts))?.process(program_id)
+        }
+        Some((&discriminator::GET_STATE, data)) => {
+            GetState::try_from((data, accounts))?.process(program_id)
+        }
+        Some((&discriminator::PAUSE_PROTOCOL_UNTIL, data)) => {
+            PauseProtocolUntil::try_from((data, accounts))?.process(program_id)
+        }
+        Some((&discriminator::CONFIGURE, data)) => {
+            Configure::try_from((data, accounts))?.process(program_id)
+        }
+        Some((&discriminator::COMPUTE_DOWNSIDE, data)) => {
+            ComputeDownside::try_from((data, accounts))?.process(program_id)
+        }
+        Some((&discriminator::PROPOSE_FEE_DESTINATION, data)) => {
+            ProposeFeeDestination::try_from((data, accounts))?.process(program_id)
+        }
+        Some((&discriminator::ACCEPT_FEE_DESTINATION, data)) => {
+            AcceptFeeDestination::try_from((data, accounts))?.process(program_id)
+        }
+        Some((&discriminator::DEPOSIT, data)) => {
+            Deposit::try_from((data, accounts))?.process(program_id)
+        }
+        Some((&discriminator::COMPARE_BENCHMARK, data)) => {
+            CompareBenchmark::try_from((data, accounts))?.process(program_id)
+        }
+        Some((&discriminator::MIGRATE, data)) => {
+            Migrate::try_from((data, accounts))?.process(program_id)
+        }
+        Some((&discriminator::REBALANCE_AND_SETTLE, data)) => {
+            RebalanceAndSettle::try_from((data, accounts))?.process(program_id)
+        }
+        Some((&discriminator::RECORD_PNL, data)) => {
+            RecordPnl::try_from((data, accounts))?.process(program_id)
+        }
+        Some((&discriminator::REQUIRE_NONCES, data)) => {
+            RequireNonces::try_from((data, accounts))?.process(program_id)
+        }
+        Some((&discriminator::REQUIRE_UPGRADE_AUTHORITY, data)) => {
+            RequireUpgradeAuthority::try_from((data, accounts))?.process(program_id)
+        }
+        Some((&discriminator::QUERY, data)) => {
+            Query::try_from((data, accounts))?.process(program_id)
+        }
+        Some((&discriminator::RESET_STATS, data)) => {
+            ResetStats::try_from((data, accounts))?.process(program_id)
+        }
+        Some((&discriminator::INITIALIZE_WITH_CREATE, data)) => {
+            InitializeWithCreate::try_from((data, accounts))?.process(program_id)
+        }
+        // Unknown discriminator
+        Some(_) => Err(ProgramError::InvalidInstructionData),
+        // No discriminator byte at all
+        None => Err(ProgramError::InvalidInstructionData),
+    }?;
+
+    // Re-check accounting once the instruction has run
+    #[cfg(feature = "strict")]
+    check_oracle_invariants(accounts)?;
+
+    Ok(())
+}
+
+/// Validate the oracle's internal accounting (oracle is always account 0)
+#[cfg(feature = "strict")]
+fn check_oracle_invariants(accounts: &[AccountView]) -> ProgramResult {
+    let [oracle, ..] = accounts else {
+        return Ok(());
+    };
+
+    let data = oracle.try_borrow()?;
+    match state::OracleState::from_bytes(&data) {
+        Ok(state) => state.check_invariants().map_err(Into::into),
+        // Not (or no longer) an oracle account
+        Err(_) => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rejects_missing_and_unknown_discriminators() {
+        assert_eq!(process_instruction(&ID, &[], &[]), Err(ProgramError::InvalidInstructionData));
+        assert_eq!(process_instruction(&ID, &[], &[99]), Err(ProgramError::InvalidInstructionData));
+        assert_eq!(
+            process_instruction(&ID, &[], &[99, 1, 2, 3]),
+            Err(ProgramError::InvalidInstructionData)
+        );
+    }
+
+    #[test]
+    fn test_dispatched_monitor_yields_needs_timestamp() {
+        // protocol + apy_bps + risk_score without the timestamp
+        assert_eq!(
+            MonitorYieldsBatch::try_from(&[0u8; 4][..]).err(),
+            Some(error::OracleError::DataTooShort.into())
+        );
+        assert_eq!(
+            MonitorYieldsBatch::try_from_legacy(&[0u8; 11][..]).err(),
+            Some(error::OracleError::DataTooShort.into())
+        );
+        assert!(MonitorYieldsBatch::try_from_legacy(&[0u8; 12][..]).is_ok());
+
+        // ...or with it, but without the nonce
+        assert_eq!(
+            MonitorYieldsBatch::try_from(&[0u8; 19][..]).err(),
+            Some(error::OracleError::DataTooShort.into())
+        );
+        assert!(MonitorYieldsBatch::try_from(&[0u8; 20][..]).is_ok());
     }
 }
@@ -0,0 +1,251 @@
+//! Structured program logs
+//!
+//! Every accepted decision emits a single pipe-separated line, so indexers
+//! can follow the oracle from transaction logs instead of diffing state:
+//!
+//! ```text
+//! AYO|MONITOR|protocol=4|apy=750|risk=10|ts=1770000000|apy_centi=75000
+//! AYO|PUBLISH|protocol=4|apy=750|risk=10|ts=1770000000|apy_centi=75000
+//! AYO|SWAP|protocol=0|amount_in=1000000000|min_out=790000000|amount_out=820000000
+//! AYO|REBALANCE|alloc=2000/2000/2000/2000/2000
+//! AYO|REBALANCE_PLAN|from=0|to=5|amount=250000
+//! AYO|VERIFY|mismatch=risk_score
+//! AYO|SCORE|score=97|ts=1770000000
+//! AYO|PNL|pnl=-4200|value=1000000000
+//! AYO|RETURN|annualized=2000|since=1770000000
+//! AYO|RESET_STATS|ts=1770000000|value=1000000000
+//! ```
+//!
+//! The format is stable: fields are never renamed or reordered, only
+//! appended. `amount_out` is 0 for swaps executed off-chain. A dry-run
+//! rebalance logs one `REBALANCE_PLAN` per leg, slot 5 being cash. `pnl`
+//! always carries its sign, `+` included, so it can't be mistaken for
+//! unsigned.
+
+use pinocchio_log::logger::Logger;
+
+use crate::instructions::protocol;
+
+/// Monitor Yields observation accepted as the new best
+pub const MONITOR: &str = "AYO|MONITOR";
+/// Strategy published as the new best
+pub const PUBLISH: &str = "AYO|PUBLISH";
+/// Swap recorded
+pub const SWAP: &str = "AYO|SWAP";
+/// Allocation changed by a rebalance
+pub const REBALANCE: &str = "AYO|REBALANCE";
+/// Leg a dry-run rebalance would swap
+pub const REBALANCE_PLAN: &str = "AYO|REBALANCE_PLAN";
+/// Verify found a mismatched field
+pub const VERIFY: &str = "AYO|VERIFY";
+/// Composite score after a published strategy
+pub const SCORE: &str = "AYO|SCORE";
+/// Cumulative PnL and value managed, after a published strategy or on read
+pub const PNL: &str = "AYO|PNL";
+
+/// Annualized return since the stats epoch began, on read
+pub const RETURN: &str = "AYO|RETURN";
+
+/// Stats zeroed for a new epoch
+pub const RESET_STATS: &str = "AYO|RESET_STATS";
+
+/// Buffer size of a formatted log line
+pub const LOG_LEN: usize = 128;
+
+/// Format a yield observation (`MONITOR` or `PUBLISH`)
+pub fn format_observation(
+    prefix: &str,
+    protocol: u8,
+    apy_bps: u16,
+    apy_centi_bps: u32,
+    risk_score: u8,
+    timestamp: i64,
+) -> Logger<LOG_LEN> {
+    let mut logger = Logger::default();
+    logger
+        .append(prefix)
+        .append("|protocol=")
+        .append(protocol)
+        .append("|apy=")
+        .append(apy_bps)
+        .append("|risk=")
+        .append(risk_score)
+        .append("|ts=")
+        .append(timestamp)
+        .append("|apy_centi=")
+        .append(apy_centi_bps);
+    logger
+}
+
+/// Format a recorded swap
+pub fn format_swap(protocol: u8, amount_in: u64, min_amount_out: u64, amount_out: u64) -> Logger<LOG_LEN> {
+    let mut logger = Logger::default();
+    logger
+        .append(SWAP)
+        .append("|protocol=")
+        .append(protocol)
+        .append("|amount_in=")
+        .append(amount_in)
+        .append("|min_out=")
+        .append(min_amount_out)
+        .append("|amount_out=")
+        .append(amount_out);
+    logger
+}
+
+/// Format a rebalanced allocation
+pub fn format_rebalance(allocation_bps: [u16; protocol::COUNT]) -> Logger<LOG_LEN> {
+    let mut logger = Logger::default();
+    logger.append(REBALANCE).append("|alloc=").append(allocation_bps[0]);
+    for bps in &allocation_bps[1..] {
+        logger.append("/").append(*bps);
+    }
+    logger
+}
+
+/// Format a planned rebalance leg, between slots
+pub fn format_rebalance_plan(from: u8, to: u8, amount_lamports: u64) -> Logger<LOG_LEN> {
+    let mut logger = Logger::default();
+    logger
+        .append(REBALANCE_PLAN)
+        .append("|from=")
+        .append(from)
+        .append("|to=")
+        .append(to)
+        .append("|amount=")
+        .append(amount_lamports);
+    logger
+}
+
+/// Format a Verify mismatch
+pub fn format_mismatch(field: &str) -> Logger<LOG_LEN> {
+    let mut logger = Logger::default();
+    logger.append(VERIFY).append("|mismatch=").append(field);
+    logger
+}
+
+/// Format a composite score
+pub fn format_score(score: u8, timestamp: i64) -> Logger<LOG_LEN> {
+    let mut logger = Logger::default();
+    logger
+        .append(SCORE)
+        .append("|score=")
+        .append(score)
+        .append("|ts=")
+        .append(timestamp);
+    logger
+}
+
+/// Format the cumulative PnL with an explicit sign, and the value managed
+pub fn format_pnl(pnl: i64, total_value_managed: u64) -> Logger<LOG_LEN> {
+    let mut logger = Logger::default();
+    logger.append(PNL).append("|pnl=");
+    if pnl >= 0 {
+        logger.append("+");
+    }
+    logger.append(pnl).append("|value=").append(total_value_managed);
+    logger
+}
+
+/// Format the annualized return (bps) since `since`
+pub fn format_return(annualized_bps: i64, since: i64) -> Logger<LOG_LEN> {
+    let mut logger = Logger::default();
+    logger
+        .append(RETURN)
+        .append("|annualized=")
+        .append(annualized_bps)
+        .append("|since=")
+        .append(since);
+    logger
+}
+
+/// Format a stats reset at `timestamp`, with the value managed it kept
+pub fn format_reset_stats(timestamp: i64, total_value_managed: u64) -> Logger<LOG_LEN> {
+    let mut logger = Logger::default();
+    logger
+        .append(RESET_STATS)
+        .append("|ts=")
+        .append(timestamp)
+        .append("|value=")
+        .append(total_value_managed);
+    logger
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_observation() {
+        let line = format_observation(MONITOR, 4, 750, 75_000, 10, 1_770_000_000);
+        assert_eq!(
+            &*line,
+            b"AYO|MONITOR|protocol=4|apy=750|risk=10|ts=1770000000|apy_centi=75000"
+        );
+
+        let line = format_observation(PUBLISH, 0, 2, 250, 20, -1);
+        assert_eq!(&*line, b"AYO|PUBLISH|protocol=0|apy=2|risk=20|ts=-1|apy_centi=250");
+    }
+
+    #[test]
+    fn test_format_swap() {
+        let line = format_swap(0, 1_000_000_000, 790_000_000, 820_000_000);
+        assert_eq!(
+            &*line,
+            b"AYO|SWAP|protocol=0|amount_in=1000000000|min_out=790000000|amount_out=820000000"
+        );
+    }
+
+    #[test]
+    fn test_format_rebalance() {
+        let line = format_rebalance([4000, 3000, 2000, 500, 500]);
+        assert_eq!(&*line, b"AYO|REBALANCE|alloc=4000/3000/2000/500/500");
+    }
+
+    #[test]
+    fn test_format_rebalance_plan() {
+        let line = format_rebalance_plan(0, 5, 250_000);
+        assert_eq!(&*line, b"AYO|REBALANCE_PLAN|from=0|to=5|amount=250000");
+    }
+
+    #[test]
+    fn test_format_mismatch() {
+        assert_eq!(&*format_mismatch("risk_score"), b"AYO|VERIFY|mismatch=risk_score");
+    }
+
+    #[test]
+    fn test_format_score() {
+        assert_eq!(&*format_score(97, 1_770_000_000), b"AYO|SCORE|score=97|ts=1770000000");
+    }
+
+    #[test]
+    fn test_format_pnl() {
+        assert_eq!(&*format_pnl(i64::MIN, 0), b"AYO|PNL|pnl=-9223372036854775808|value=0");
+        assert_eq!(&*format_pnl(0, 1_000), b"AYO|PNL|pnl=+0|value=1000");
+        assert_eq!(
+            &*format_pnl(i64::MAX, u64::MAX),
+            b"AYO|PNL|pnl=+9223372036854775807|value=18446744073709551615"
+        );
+    }
+
+    #[test]
+    fn test_format_return() {
+        let line = format_return(-2000, 1_770_000_000);
+        assert_eq!(&*line, b"AYO|RETURN|annualized=-2000|since=1770000000");
+    }
+
+    #[test]
+    fn test_format_reset_stats() {
+        let line = format_reset_stats(1_770_000_000, 1_000_000_000);
+        assert_eq!(&*line, b"AYO|RESET_STATS|ts=1770000000|value=1000000000");
+    }
+
+    #[test]
+    fn test_longest_lines_fit() {
+        let line = format_swap(u8::MAX, u64::MAX, u64::MAX, u64::MAX);
+        assert!(line.len() < LOG_LEN);
+
+        let line = format_observation(PUBLISH, u8::MAX, u16::MAX, u32::MAX, u8::MAX, i64::MIN);
+        assert!(line.len() < LOG_LEN);
+    }
+}
@@ -0,0 +1,215 @@
+//! Integer-only yield math
+//!
+//! Risk adjustment curves, keyed by protocol category: LP risk (impermanent
+//! loss, pool exploits) compounds faster than staking risk, so it can be
+//! penalized more steeply as the risk score rises.
+
+use crate::instructions::protocol;
+
+/// Kind of yield a protocol produces
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ProtocolCategory {
+    /// AMM pools and routes through them
+    Lp = 0,
+    /// Lending markets and vaults
+    Lending = 1,
+    /// Liquid staking
+    Staking = 2,
+}
+
+impl ProtocolCategory {
+    /// Number of categories
+    pub const COUNT: usize = 3;
+
+    /// Every category, in index order
+    pub const ALL: [ProtocolCategory; Self::COUNT] =
+        [ProtocolCategory::Lp, ProtocolCategory::Lending, ProtocolCategory::Staking];
+
+    /// Category of a protocol ID
+    pub fn of(protocol_id: u8) -> Self {
+        match protocol_id {
+            protocol::KAMINO => ProtocolCategory::Lending,
+            protocol::MARINADE | protocol::JITO => ProtocolCategory::Staking,
+            _ => ProtocolCategory::Lp,
+        }
+    }
+}
+
+/// How the risk score discounts APY
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RiskCurve {
+    /// `100 / (100 + risk)` is kept; gentler, never below half the APY.
+    /// The default, so even a risk of 100 keeps a great yield in the race
+    Hyperbolic = 0,
+    /// `(100 - risk)^2 / 100 %` is kept; harsher as risk rises
+    Quadratic = 1,
+    /// Flat tiers: 100% below 25, 75% below 50, 40% below 75, then nothing
+    Stepwise = 2,
+    /// `(100 - risk)%` of the APY is kept; nothing at risk 100
+    Linear = 3,
+}
+
+impl RiskCurve {
+    /// Highest valid stored byte
+    pub const MAX: u8 = RiskCurve::Linear as u8;
+
+    /// Curve for a stored byte (unknown values fall back to the default)
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            1 => RiskCurve::Quadratic,
+            2 => RiskCurve::Stepwise,
+            3 => RiskCurve::Linear,
+            _ => RiskCurve::Hyperbolic,
+        }
+    }
+
+    /// Percentage of the APY kept at `risk_score` (0-100)
+    pub fn multiplier(self, risk_score: u8) -> u64 {
+        let safety = 100u64.saturating_sub(risk_score as u64);
+        match self {
+            RiskCurve::Linear => safety,
+            RiskCurve::Quadratic => safety * safety / 100,
+            RiskCurve::Stepwise => match risk_score {
+                0..=24 => 100,
+                25..=49 => 75,
+                50..=74 => 40,
+                _ => 0,
+            },
+            RiskCurve::Hyperbolic => 10_000 / (100 + risk_score.min(100) as u64),
+        }
+    }
+}
+
+/// Risk-adjusted yield under `curve`, in the same unit as `apy`
+///
+/// `u64` intermediates can't overflow for any `u32` APY.
+pub fn risk_adjusted_apy(apy: u32, risk_score: u8, curve: RiskCurve) -> u32 {
+    match curve {
+        // Exact rather than through the floored percentage
+        RiskCurve::Hyperbolic => (apy as u64 * 100 / (100 + risk_score.min(100) as u64)) as u32,
+        _ => (apy as u64 * curve.multiplier(risk_score) / 100) as u32,
+    }
+}
+
+/// Integer square root, rounded down
+pub fn isqrt(n: u128) -> u128 {
+    if n < 2 {
+        return n;
+    }
+
+    // Newton's method from above converges onto the floor
+    let mut x = n / 2 + 1;
+    let mut y = (x + n / x) / 2;
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+    x
+}
+
+/// Downside deviation of `samples` against a target of zero
+///
+/// `sqrt(sum(min(x, 0)^2) / n)`, rounded down, in the samples' unit. Only
+/// losses add to the sum, but every sample counts towards `n`. `None` for
+/// an empty series.
+pub fn downside_deviation(samples: impl Iterator<Item = i64>) -> Option<u64> {
+    let mut sum_sq = 0u128;
+    let mut n = 0u128;
+    for sample in samples {
+        let loss = sample.min(0).unsigned_abs() as u128;
+        sum_sq = sum_sq.saturating_add(loss * loss);
+        n += 1;
+    }
+
+    if n == 0 {
+        return None;
+    }
+    Some(isqrt(sum_sq / n) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_penalty_at_half_risk() {
+        // 10% APY at risk 50
+        assert_eq!(risk_adjusted_apy(1000, 50, RiskCurve::Linear), 500);
+        assert_eq!(risk_adjusted_apy(1000, 50, RiskCurve::Quadratic), 250);
+        assert_eq!(risk_adjusted_apy(1000, 50, RiskCurve::Stepwise), 400);
+        assert_eq!(risk_adjusted_apy(1000, 50, RiskCurve::Hyperbolic), 666);
+    }
+
+    #[test]
+    fn test_hyperbolic_curve() {
+        // (apy, risk, adjusted)
+        let table = [
+            (1500, 0, 1500),
+            (1500, 20, 1250),
+            (1500, 100, 750),
+            (0, 50, 0),
+            (65535, 0, 65535),
+            (65535, 1, 64886),
+            (65535, 100, 32767),
+            (u32::MAX, 0, u32::MAX),
+            (u32::MAX, 100, u32::MAX / 2),
+        ];
+        for (apy, risk, adjusted) in table {
+            assert_eq!(risk_adjusted_apy(apy, risk, RiskCurve::Hyperbolic), adjusted, "{apy} at {risk}");
+        }
+
+        // Out-of-range scores count as 100
+        assert_eq!(risk_adjusted_apy(1500, 255, RiskCurve::Hyperbolic), 750);
+
+        // Never harsher than linear
+        for risk in 0..=100 {
+            assert!(
+                risk_adjusted_apy(65535, risk, RiskCurve::Hyperbolic)
+                    >= risk_adjusted_apy(65535, risk, RiskCurve::Linear)
+            );
+        }
+    }
+
+    #[test]
+    fn test_curves_agree_at_the_ends() {
+        for curve in [RiskCurve::Linear, RiskCurve::Quadratic, RiskCurve::Stepwise] {
+            assert_eq!(risk_adjusted_apy(1000, 0, curve), 1000);
+            assert_eq!(risk_adjusted_apy(1000, 100, curve), 0);
+        }
+    }
+
+    #[test]
+    fn test_isqrt() {
+        assert_eq!(isqrt(0), 0);
+        assert_eq!(isqrt(1), 1);
+        assert_eq!(isqrt(2), 1);
+        assert_eq!(isqrt(15), 3);
+        assert_eq!(isqrt(16), 4);
+        assert_eq!(isqrt(u64::MAX as u128 * u64::MAX as u128), u64::MAX as u128);
+    }
+
+    #[test]
+    fn test_downside_deviation() {
+        // Gains carry no downside
+        assert_eq!(downside_deviation([100, 250, 0, 75].into_iter()), Some(0));
+
+        // (300^2 + 400^2) / 4 = 62500 -> 250
+        assert_eq!(downside_deviation([100, -300, 200, -400].into_iter()), Some(250));
+
+        // The largest possible loss still fits
+        assert_eq!(downside_deviation([i64::MIN].into_iter()), Some(1 << 63));
+
+        assert_eq!(downside_deviation(core::iter::empty()), None);
+    }
+
+    #[test]
+    fn test_protocol_categories() {
+        assert_eq!(ProtocolCategory::of(protocol::RAYDIUM_CPMM), ProtocolCategory::Lp);
+        assert_eq!(ProtocolCategory::of(protocol::JUPITER_ROUTE), ProtocolCategory::Lp);
+        assert_eq!(ProtocolCategory::of(protocol::KAMINO), ProtocolCategory::Lending);
+        assert_eq!(ProtocolCategory::of(protocol::MARINADE), ProtocolCategory::Staking);
+        assert_eq!(ProtocolCategory::of(protocol::JITO), ProtocolCategory::Staking);
+    }
+}
@@ -0,0 +1,86 @@
+//! External notifier events
+//!
+//! On significant events the oracle can CPI an operator-supplied notifier
+//! program, so alerts are pushed instead of polled. The CPI is only made
+//! with the `notifier` feature enabled and a notifier account provided.
+//!
+//! Notifier instruction layout: event code (1) + payload (8, le) = 9 bytes,
+//! with the oracle passed as the single read-only account.
+
+use pinocchio::{AccountView, ProgramResult};
+
+/// Events pushed to the notifier program
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EventCode {
+    /// Emergency withdrawal triggered
+    EmergencyTrip = 0,
+    /// A risk circuit breaker tripped
+    BreakerTripped = 1,
+    /// Urgent exit from a position
+    UrgentExit = 2,
+    /// Protocol or oracle deprecated
+    Deprecated = 3,
+}
+
+/// Size of the notifier instruction data
+pub const EVENT_LEN: usize = 9;
+
+/// Encode an event and its payload into notifier instruction data
+pub fn encode_event(event: EventCode, payload: u64) -> [u8; EVENT_LEN] {
+    let mut data = [0u8; EVENT_LEN];
+    data[0] = event as u8;
+    data[1..9].copy_from_slice(&payload.to_le_bytes());
+    data
+}
+
+/// Notify the external program, if one was provided
+#[cfg(feature = "notifier")]
+pub fn notify(
+    notifier: Option<&AccountView>,
+    oracle: &AccountView,
+    event: EventCode,
+    payload: u64,
+) -> ProgramResult {
+    use pinocchio::cpi::invoke;
+    use pinocchio::instruction::{InstructionAccount, InstructionView};
+
+    let Some(notifier) = notifier else {
+        return Ok(());
+    };
+
+    let data = encode_event(event, payload);
+    let accounts = [InstructionAccount::readonly(oracle.address())];
+    let instruction = InstructionView {
+        program_id: notifier.address(),
+        accounts: &accounts,
+        data: &data,
+    };
+
+    invoke(&instruction, &[oracle])
+}
+
+/// Notify the external program (disabled without the `notifier` feature)
+#[cfg(not(feature = "notifier"))]
+pub fn notify(
+    _notifier: Option<&AccountView>,
+    _oracle: &AccountView,
+    _event: EventCode,
+    _payload: u64,
+) -> ProgramResult {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_event() {
+        let data = encode_event(EventCode::EmergencyTrip, 1_000_000_000);
+        assert_eq!(data[0], 0);
+        assert_eq!(u64::from_le_bytes(data[1..9].try_into().unwrap()), 1_000_000_000);
+
+        assert_eq!(encode_event(EventCode::Deprecated, 0)[0], 3);
+    }
+}
@@ -0,0 +1,470 @@
+//! Protocol selection policy
+//!
+//! Decides what an observation does to the oracle's selection, as a pure
+//! function of the current state, so the policy can be tested without
+//! accounts. `MonitorYields` and `PublishStrategy` evaluate first, then
+//! write the decision.
+
+use solana_program_error::ProgramError;
+
+use crate::instructions::ProtocolId;
+use crate::state::{ApyPrecision, OracleState};
+
+/// Where an observation comes from
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Source {
+    /// A monitored yield, which has to beat the best protocol
+    Monitor,
+    /// A strategy published by the authority, taken unless below the
+    /// actionable floor
+    Strategy,
+}
+
+/// A yield observation to score
+#[derive(Clone, Copy, Debug)]
+pub struct Observation {
+    /// Monitored yield or published strategy
+    pub source: Source,
+    /// Protocol observed
+    pub protocol: ProtocolId,
+    /// APY in basis points
+    pub apy_bps: u16,
+    /// APY in hundredths of a bps, used under `ApyPrecision::CentiBps`
+    pub apy_centi_bps: u32,
+    /// Risk score (0-100, lower is safer)
+    pub risk_score: u8,
+    /// Liquidity (lamports) behind the yield, 0 if not reported; only
+    /// monitored yields are held to `min_liquidity`
+    pub liquidity: u64,
+}
+
+/// What an observation should do to the oracle
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum UpdateDecision {
+    /// Becomes the new best protocol
+    Switch,
+    /// Tracked only: history and update time move forward, the best
+    /// protocol doesn't change
+    RefreshOnly,
+    /// Leaves the selection untouched
+    Ignore,
+}
+
+impl Observation {
+    /// Observed APY in `precision`'s unit
+    pub fn apy(&self, precision: ApyPrecision) -> u32 {
+        match precision {
+            ApyPrecision::Bps => self.apy_bps as u32,
+            ApyPrecision::CentiBps => self.apy_centi_bps,
+        }
+    }
+
+    /// Does `new_adjusted_apy` beat `best`, the best protocol?
+    ///
+    /// A protocol other than the best has to win by `switch_margin_bps`.
+    pub fn beats_best(
+        &self,
+        state: &OracleState,
+        best: ProtocolId,
+        precision: ApyPrecision,
+        new_adjusted_apy: u32,
+    ) -> Result<bool, ProgramError> {
+        let current_adjusted_apy = match precision {
+            ApyPrecision::Bps => state.protocol_adjusted_apy_bps(best as u8)?,
+            ApyPrecision::CentiBps => {
+                let current_apy = state.net_apy(best as u8, state.current_apy())?;
+                state.risk_adjusted_apy(best as u8, current_apy, state.risk_score)
+            }
+        };
+
+        Ok(new_adjusted_apy > current_adjusted_apy
+            && (self.protocol == best
+                || new_adjusted_apy - current_adjusted_apy
+                    >= precision.from_bps(state.switch_margin_bps() as u32)))
+    }
+
+    /// Can `beats_best` be ruled out without computing it?
+    ///
+    /// Only for the best protocol itself, scored on the same risk curve and
+    /// fee as the incumbent, at no more yield and no less risk: every curve
+    /// is non-decreasing in APY and non-increasing in risk.
+    pub fn cannot_beat_best(
+        &self,
+        state: &OracleState,
+        best: ProtocolId,
+        precision: ApyPrecision,
+    ) -> bool {
+        if self.protocol != best {
+            return false;
+        }
+
+        // The incumbent as `beats_best` scores it
+        let (current_apy, current_risk) = match precision {
+            ApyPrecision::Bps => {
+                let index = best.index();
+                (state.protocol_apy_bps()[index] as u32, state.protocol_risk()[index])
+            }
+            ApyPrecision::CentiBps => (state.current_apy(), state.risk_score),
+        };
+        self.apy(precision) <= current_apy && self.risk_score >= current_risk
+    }
+}
+
+/// `obs`'s APY net of its protocol's fee, then risk adjusted, in the
+/// configured precision: the figure observations are ranked by
+pub fn adjusted_apy(current: &OracleState, obs: &Observation) -> Result<u32, ProgramError> {
+    let net_apy = current.net_apy(obs.protocol as u8, obs.apy(current.apy_precision()))?;
+    Ok(current.risk_adjusted_apy(obs.protocol as u8, net_apy, obs.risk_score))
+}
+
+/// Decide what `obs`, judged at time `now`, does to `current`
+///
+/// APYs are scored net of their protocol's `protocol_fee_bps`, then risk
+/// adjusted. The incumbent is scored from its own latest snapshot entry, so
+/// a best protocol whose yield has since dropped is easier to beat; under
+/// `ApyPrecision::CentiBps` the stored precise APY is used instead, since
+/// the snapshot only holds whole bps.
+///
+/// For monitored yields, in order of precedence:
+///
+/// 1. Riskier than `max_acceptable_risk`, or the protocol is paused at
+///    `now`: `Ignore`.
+/// 2. Neither better (risk-adjusted, by at least `switch_margin_bps` for a
+///    protocol other than the best) nor replacing data stale at `now`:
+///    `Ignore`.
+/// 3. Below `min_actionable_adjusted_apy_bps`: `RefreshOnly`.
+/// 4. Not better, only replacing stale data, and below
+///    `stale_override_floor_bps`: `RefreshOnly`, so staleness never
+///    downgrades the oracle into a poor yield.
+/// 5. Reporting less liquidity than `min_liquidity` (including none):
+///    `RefreshOnly`, as a pool too thin to deploy into is a trap.
+/// 6. Otherwise `Switch`.
+///
+/// Published strategies skip the comparison: `RefreshOnly` below
+/// `min_actionable_adjusted_apy_bps`, `Switch` otherwise.
+///
+/// Fails with `ImplausibleApy` above `max_apy_bps`, and, for monitored
+/// yields, with `InvalidProtocol` if the stored best protocol is not a
+/// valid ID (publishing a strategy overwrites it).
+pub fn evaluate_update(
+    current: &OracleState,
+    obs: &Observation,
+    now: i64,
+) -> Result<UpdateDecision, ProgramError> {
+    // Compare in the configured precision, net of fees
+    let precision = current.apy_precision();
+    let adjusted_apy = adjusted_apy(current, obs)?;
+    let below_actionable =
+        adjusted_apy < precision.from_bps(current.min_actionable_adjusted_apy_bps() as u32);
+
+    if obs.source == Source::Strategy {
+        current.check_apy_plausible(obs.apy_bps)?;
+        return Ok(if below_actionable {
+            UpdateDecision::RefreshOnly
+        } else {
+            UpdateDecision::Switch
+        });
+    }
+
+    // Never score against an incumbent we can't identify
+    let best = current.best_protocol()?;
+
+    // The common repeat of the best protocol at no gain skips scoring the
+    // incumbent
+    let is_stale = current.is_stale(now);
+    let is_better = !obs.cannot_beat_best(current, best, precision)
+        && obs.beats_best(current, best, precision, adjusted_apy)?;
+
+    // A glitching feed would otherwise win every comparison
+    current.check_apy_plausible(obs.apy_bps)?;
+
+    // Never switch into something riskier than the operator tolerates,
+    // however good or overdue, nor route to a paused protocol
+    if obs.risk_score > current.max_acceptable_risk()
+        || current.is_protocol_paused(obs.protocol as u8, now)?
+    {
+        return Ok(UpdateDecision::Ignore);
+    }
+
+    if !(is_better || is_stale) {
+        return Ok(UpdateDecision::Ignore);
+    }
+
+    // A downgrade forced only by staleness must still be worth holding
+    if below_actionable
+        || (!is_better
+            && adjusted_apy < precision.from_bps(current.stale_override_floor_bps() as u32))
+    {
+        return Ok(UpdateDecision::RefreshOnly);
+    }
+
+    // The best yield is a trap if the pool is too thin to deploy into; it
+    // is tracked, but a batch's other observations still count
+    if !current.meets_min_liquidity(obs.liquidity) {
+        return Ok(UpdateDecision::RefreshOnly);
+    }
+
+    Ok(UpdateDecision::Switch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::OracleError;
+    use crate::instructions::protocol;
+    use crate::math::{ProtocolCategory, RiskCurve};
+
+    fn observation(
+        source: Source,
+        protocol: ProtocolId,
+        apy_bps: u16,
+        risk_score: u8,
+    ) -> Observation {
+        Observation {
+            source,
+            protocol,
+            apy_bps,
+            apy_centi_bps: apy_bps as u32 * 100,
+            risk_score,
+            liquidity: 0,
+        }
+    }
+
+    #[test]
+    fn test_decision_table() {
+        let state = &mut OracleState::test_state();
+        state.set_max_acceptable_risk(50);
+        state.set_min_actionable_adjusted_apy_bps(300);
+        state.set_stale_override_floor_bps(600);
+        state.set_switch_margin_bps(10);
+        state.set_stale_after_secs(OracleState::STALE_AFTER_SECS as u32);
+        state.set_protocol_paused_until(protocol::MARINADE, 2_000).unwrap();
+
+        // Kamino is best at 900 bps, risk 10: 818 adjusted
+        state.best_protocol = protocol::KAMINO;
+        state.set_current_apy_bps(900);
+        state.risk_score = 10;
+        state.record_protocol_observation(protocol::KAMINO, 900, 10).unwrap();
+        state.set_last_update(1_000);
+
+        let fresh = 1_060;
+        let stale = 1_000 + OracleState::STALE_AFTER_SECS + 1;
+        let cases = [
+            // Better by more than the margin
+            (ProtocolId::Jito, 1000, 10, fresh, UpdateDecision::Switch),
+            // Better, but within the margin
+            (ProtocolId::Jito, 905, 10, fresh, UpdateDecision::Ignore),
+            // The best protocol itself isn't held to the margin
+            (ProtocolId::Kamino, 905, 10, fresh, UpdateDecision::Switch),
+            // Worse
+            (ProtocolId::Jito, 800, 10, fresh, UpdateDecision::Ignore),
+            (ProtocolId::Kamino, 900, 20, fresh, UpdateDecision::Ignore),
+            // Worse, but the current data is stale
+            (ProtocolId::Jito, 800, 10, stale, UpdateDecision::Switch),
+            // Stale and below the stale override floor
+            (ProtocolId::Jito, 500, 10, stale, UpdateDecision::RefreshOnly),
+            // Below the actionable floor, however stale
+            (ProtocolId::Jito, 300, 10, stale, UpdateDecision::RefreshOnly),
+            // Risk-capped, however good or stale
+            (ProtocolId::Jito, 5000, 51, fresh, UpdateDecision::Ignore),
+            (ProtocolId::Jito, 5000, 51, stale, UpdateDecision::Ignore),
+            (ProtocolId::Jito, 5000, 50, fresh, UpdateDecision::Switch),
+            // Paused until 2_000
+            (ProtocolId::Marinade, 5000, 0, fresh, UpdateDecision::Ignore),
+            (ProtocolId::Marinade, 5000, 0, 2_000, UpdateDecision::Switch),
+        ];
+        for (protocol, apy_bps, risk_score, now, expected) in cases {
+            let obs = observation(Source::Monitor, protocol, apy_bps, risk_score);
+            assert_eq!(
+                evaluate_update(state, &obs, now),
+                Ok(expected),
+                "{protocol:?} {apy_bps} {risk_score} {now}"
+            );
+        }
+
+        // Published strategies only have to clear the actionable floor
+        let cases = [
+            (ProtocolId::Jito, 800, 10, UpdateDecision::Switch),
+            (ProtocolId::Jito, 500, 95, UpdateDecision::RefreshOnly),
+            (ProtocolId::Marinade, 400, 0, UpdateDecision::Switch),
+            (ProtocolId::Jito, 300, 10, UpdateDecision::RefreshOnly),
+        ];
+        for (protocol, apy_bps, risk_score, expected) in cases {
+            let obs = observation(Source::Strategy, protocol, apy_bps, risk_score);
+            assert_eq!(evaluate_update(state, &obs, fresh), Ok(expected));
+        }
+    }
+
+    #[test]
+    fn test_fees_rank_equal_yields() {
+        let state = &mut OracleState::test_state();
+        state.set_max_acceptable_risk(100);
+        // Jito's fee costs it 50 bps, Marinade has none
+        state.set_protocol_fee_bps([0, 0, 10, 0, 50]);
+
+        for precision in [ApyPrecision::Bps, ApyPrecision::CentiBps] {
+            state.set_apy_precision(precision);
+
+            // Jito is best at 900 bps, 850 net
+            state.best_protocol = protocol::JITO;
+            state.set_current_apy_bps(900);
+            state.set_current_apy_centi_bps(90_000);
+            state.risk_score = 0;
+            state.record_protocol_observation(protocol::JITO, 900, 0).unwrap();
+            state.set_last_update(1_000);
+
+            // The same raw yield on Marinade nets more, so it wins
+            let obs = observation(Source::Monitor, ProtocolId::Marinade, 900, 0);
+            assert_eq!(evaluate_update(state, &obs, 1_060), Ok(UpdateDecision::Switch));
+
+            // Marinade is best at 900 bps, no fee
+            state.best_protocol = protocol::MARINADE;
+            state.record_protocol_observation(protocol::MARINADE, 900, 0).unwrap();
+
+            // Jito has to make up its whole fee just to tie
+            let obs = observation(Source::Monitor, ProtocolId::Jito, 900, 0);
+            assert_eq!(evaluate_update(state, &obs, 1_060), Ok(UpdateDecision::Ignore));
+            let obs = observation(Source::Monitor, ProtocolId::Jito, 950, 0);
+            assert_eq!(evaluate_update(state, &obs, 1_060), Ok(UpdateDecision::Ignore));
+            let obs = observation(Source::Monitor, ProtocolId::Jito, 951, 0);
+            assert_eq!(evaluate_update(state, &obs, 1_060), Ok(UpdateDecision::Switch));
+        }
+    }
+
+    #[test]
+    fn test_default_curve_keeps_risky_yield() {
+        let state = &mut OracleState::test_state();
+        state.set_max_acceptable_risk(100);
+
+        // Kamino is best at 900 bps, risk 0
+        state.best_protocol = protocol::KAMINO;
+        state.set_current_apy_bps(900);
+        state.record_protocol_observation(protocol::KAMINO, 900, 0).unwrap();
+        state.set_last_update(1_000);
+
+        // The default curve keeps half of 2000 at risk 100, so it wins
+        let obs = observation(Source::Monitor, ProtocolId::Jito, 2000, 100);
+        assert_eq!(state.risk_curve(ProtocolCategory::Staking), RiskCurve::Hyperbolic);
+        assert_eq!(evaluate_update(state, &obs, 1_060), Ok(UpdateDecision::Switch));
+
+        // A linear curve would zero it
+        state.set_risk_curve(ProtocolCategory::Staking, RiskCurve::Linear);
+        assert_eq!(evaluate_update(state, &obs, 1_060), Ok(UpdateDecision::Ignore));
+    }
+
+    #[test]
+    fn test_thin_liquidity_is_not_selected() {
+        let state = &mut OracleState::test_state();
+        state.set_max_acceptable_risk(100);
+        state.set_min_liquidity(1_000_000);
+
+        // Kamino is best at 900 bps
+        state.best_protocol = protocol::KAMINO;
+        state.set_current_apy_bps(900);
+        state.record_protocol_observation(protocol::KAMINO, 900, 0).unwrap();
+        state.set_last_update(1_000);
+
+        // Far better, but on a pool too thin to deploy into, or of unknown
+        // depth
+        let mut obs = observation(Source::Monitor, ProtocolId::Jito, 5000, 0);
+        for liquidity in [0, 999_999] {
+            obs.liquidity = liquidity;
+            assert_eq!(evaluate_update(state, &obs, 1_060), Ok(UpdateDecision::RefreshOnly));
+        }
+
+        // Deep enough
+        obs.liquidity = 1_000_000;
+        assert_eq!(evaluate_update(state, &obs, 1_060), Ok(UpdateDecision::Switch));
+
+        // Only a would-be switch is held to the minimum
+        let obs = observation(Source::Monitor, ProtocolId::Jito, 800, 0);
+        assert_eq!(evaluate_update(state, &obs, 1_060), Ok(UpdateDecision::Ignore));
+        let obs = observation(Source::Strategy, ProtocolId::Jito, 5000, 0);
+        assert_eq!(evaluate_update(state, &obs, 1_060), Ok(UpdateDecision::Switch));
+    }
+
+    #[test]
+    fn test_evaluate_errors() {
+        let state = &mut OracleState::test_state();
+        state.set_max_apy_bps(20_000);
+
+        for source in [Source::Monitor, Source::Strategy] {
+            let obs = observation(source, ProtocolId::Jito, 20_001, 0);
+            assert_eq!(
+                evaluate_update(state, &obs, 1_000),
+                Err(OracleError::ImplausibleApy.into())
+            );
+        }
+
+        // An unknown incumbent can't be scored, but can be replaced
+        state.best_protocol = 9;
+        let obs = observation(Source::Monitor, ProtocolId::Jito, 900, 0);
+        assert_eq!(
+            evaluate_update(state, &obs, 1_000),
+            Err(OracleError::InvalidProtocol.into())
+        );
+        let obs = observation(Source::Strategy, ProtocolId::Jito, 900, 0);
+        assert_eq!(evaluate_update(state, &obs, 1_000), Ok(UpdateDecision::Switch));
+    }
+
+    #[test]
+    fn test_fast_reject_matches_full_comparison() {
+        let state = &mut OracleState::test_state();
+
+        let curves = [
+            RiskCurve::Linear,
+            RiskCurve::Quadratic,
+            RiskCurve::Stepwise,
+            RiskCurve::Hyperbolic,
+        ];
+        let categories = [
+            ProtocolCategory::Lp,
+            ProtocolCategory::Lending,
+            ProtocolCategory::Staking,
+        ];
+
+        let mut fast_rejects = 0;
+        for precision in [ApyPrecision::Bps, ApyPrecision::CentiBps] {
+            for curve in curves {
+                for category in categories {
+                    state.set_risk_curve(category, curve);
+                }
+                state.set_apy_precision(precision);
+                state.set_switch_margin_bps(25);
+                state.best_protocol = protocol::KAMINO;
+                state.set_current_apy_bps(800);
+                state.set_current_apy_centi_bps(80_000);
+                state.risk_score = 30;
+                state.record_protocol_observation(protocol::KAMINO, 800, 30).unwrap();
+
+                for protocol in [ProtocolId::Kamino, ProtocolId::Jito] {
+                    for apy_bps in [0, 700, 799, 800, 801, 900] {
+                        for risk_score in [0, 20, 29, 30, 31, 60, 100] {
+                            let obs = observation(Source::Monitor, protocol, apy_bps, risk_score);
+                            let new_adjusted_apy = state.risk_adjusted_apy(
+                                protocol as u8,
+                                obs.apy(precision),
+                                risk_score,
+                            );
+                            let slow = obs.beats_best(
+                                state,
+                                ProtocolId::Kamino,
+                                precision,
+                                new_adjusted_apy,
+                            )
+                            .unwrap();
+                            let skipped =
+                                obs.cannot_beat_best(state, ProtocolId::Kamino, precision);
+                            let fast = !skipped && slow;
+                            assert_eq!(fast, slow, "{protocol:?} {apy_bps} {risk_score} {curve:?}");
+                            fast_rejects += skipped as u32;
+                        }
+                    }
+                }
+            }
+        }
+
+        // The fast path did get taken
+        assert!(fast_rejects > 0);
+    }
+}
@@ -0,0 +1,136 @@
+//! Oracle settings
+//!
+//! The operator-tunable thresholds of an oracle, parsed and validated as a
+//! single blob so they can be applied all at once.
+
+use solana_program_error::ProgramError;
+
+use super::{ApyPrecision, OracleState};
+use crate::error::OracleError;
+use crate::math::{ProtocolCategory, RiskCurve};
+
+/// Full set of oracle settings
+///
+/// Layout: max_acceptable_risk (1) + min_update_interval_secs (4) +
+/// lot_size_bps (2) + min_actionable_adjusted_apy_bps (2) +
+/// max_drawdown_lamports (8) + phase_one_fraction_bps (2) +
+/// apy_precision (1) + stale_override_floor_bps (2) +
+/// risk_curves (1 per `ProtocolCategory`) = 25 bytes
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct OracleConfig {
+    /// Highest risk score the oracle will switch into (0-100)
+    pub max_acceptable_risk: u8,
+    /// Minimum seconds between accepted `MonitorYields` updates
+    pub min_update_interval_secs: u32,
+    /// Lot size allocations are rounded to, in bps (0 = no rounding)
+    pub lot_size_bps: u16,
+    /// Risk-adjusted APY (bps) an observation must reach to become actionable
+    pub min_actionable_adjusted_apy_bps: u16,
+    /// Drawdown at which deployment is fully wound down (0 = no soft-landing)
+    pub max_drawdown_lamports: u64,
+    /// Share of a move (bps) done in phase one (0 = single-phase rebalance)
+    pub phase_one_fraction_bps: u16,
+    /// Unit APYs are compared in
+    pub apy_precision: ApyPrecision,
+    /// Risk-adjusted APY (bps) a worse observation must reach to replace
+    /// stale data (0 = any)
+    pub stale_override_floor_bps: u16,
+    /// Risk curve per protocol category, indexed by `ProtocolCategory`
+    pub risk_curves: [RiskCurve; ProtocolCategory::COUNT],
+}
+
+impl OracleConfig {
+    /// Size of the serialized settings
+    pub const LEN: usize = 25;
+
+    /// Reject out-of-range or conflicting settings
+    pub fn validate(&self) -> Result<(), ProgramError> {
+        if self.max_acceptable_risk > 100 {
+            return Err(OracleError::InvalidRiskScore.into());
+        }
+
+        // Lots must tile 10000 bps, or rounded allocations can't sum to it
+        if self.lot_size_bps != 0 && 10000 % self.lot_size_bps != 0 {
+            return Err(OracleError::InvalidConfig.into());
+        }
+
+        // A first leg covering the whole move leaves nothing for phase two
+        if self.phase_one_fraction_bps >= 10000 {
+            return Err(OracleError::InvalidConfig.into());
+        }
+
+        // A floor above the largest representable APY blocks every update
+        if self.min_actionable_adjusted_apy_bps == u16::MAX {
+            return Err(OracleError::InvalidConfig.into());
+        }
+
+        Ok(())
+    }
+
+    /// Settings currently held by the oracle
+    pub fn from_state(state: &OracleState) -> Self {
+        Self {
+            max_acceptable_risk: state.max_acceptable_risk(),
+            min_update_interval_secs: state.min_update_interval_secs(),
+            lot_size_bps: state.lot_size_bps(),
+            min_actionable_adjusted_apy_bps: state.min_actionable_adjusted_apy_bps(),
+            max_drawdown_lamports: state.max_drawdown_lamports(),
+            phase_one_fraction_bps: state.phase_one_fraction_bps(),
+            apy_precision: state.apy_precision(),
+            stale_override_floor_bps: state.stale_override_floor_bps(),
+            risk_curves: ProtocolCategory::ALL.map(|category| state.risk_curve(category)),
+        }
+    }
+
+    /// Write every setting into the oracle state
+    pub fn apply(&self, state: &mut OracleState) {
+        state.set_max_acceptable_risk(self.max_acceptable_risk);
+        state.set_min_update_interval_secs(self.min_update_interval_secs);
+        state.set_lot_size_bps(self.lot_size_bps);
+        state.set_min_actionable_adjusted_apy_bps(self.min_actionable_adjusted_apy_bps);
+        state.set_max_drawdown_lamports(self.max_drawdown_lamports);
+        state.set_phase_one_fraction_bps(self.phase_one_fraction_bps);
+        state.set_apy_precision(self.apy_precision);
+        state.set_stale_override_floor_bps(self.stale_override_floor_bps);
+
+        for (category, curve) in ProtocolCategory::ALL.into_iter().zip(self.risk_curves) {
+            state.set_risk_curve(category, curve);
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for OracleConfig {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() < Self::LEN {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let apy_precision = match data[19] {
+            0 => ApyPrecision::Bps,
+            1 => ApyPrecision::CentiBps,
+            _ => return Err(OracleError::InvalidConfig.into()),
+        };
+
+        let mut risk_curves = [RiskCurve::Linear; ProtocolCategory::COUNT];
+        for (curve, &value) in risk_curves.iter_mut().zip(&data[22..25]) {
+            if value > RiskCurve::MAX {
+                return Err(OracleError::InvalidConfig.into());
+            }
+            *curve = RiskCurve::from_u8(value);
+        }
+
+        Ok(Self {
+            max_acceptable_risk: data[0],
+            min_update_interval_secs: u32::from_le_bytes(data[1..5].try_into().unwrap()),
+            lot_size_bps: u16::from_le_bytes([data[5], data[6]]),
+            min_actionable_adjusted_apy_bps: u16::from_le_bytes([data[7], data[8]]),
+            max_drawdown_lamports: u64::from_le_bytes(data[9..17].try_into().unwrap()),
+            phase_one_fraction_bps: u16::from_le_bytes([data[17], data[18]]),
+            apy_precision,
+            stale_override_floor_bps: u16::from_le_bytes([data[20], data[21]]),
+            risk_curves,
+        })
+    }
+}
@@ -1,5 +1,11 @@
 //! State module - Account data structures
 
+mod config;
 mod oracle;
+#[cfg(feature = "client")]
+mod view;
 
+pub use config::*;
 pub use oracle::*;
+#[cfg(feature = "client")]
+pub use view::*;
@@ -2,16 +2,97 @@
 //!
 //! Stores yield data, strategy recommendations, and autonomous decision tracking.
 
+use core::mem::offset_of;
+
+use bytemuck::{Pod, Zeroable};
+use pinocchio::{cpi::Seed, Address};
 use solana_program_error::ProgramError;
 
+use crate::error::OracleError;
+use crate::instructions::{protocol, protocol_index, ProtocolId};
+use crate::math::{self, ProtocolCategory, RiskCurve};
+
+/// Number of APY samples kept in the history ring buffer
+const HISTORY_LEN: usize = 16;
+
+/// Number of booked PnL amounts kept in the PnL ring buffer
+const PNL_HISTORY_LEN: usize = 16;
+
+/// Progress of a two-phase rebalance
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RebalancePhase {
+    /// No rebalance underway
+    Idle = 0,
+    /// First leg done; waiting for the completing call
+    PhaseOne = 1,
+}
+
+/// Unit APYs are compared and reported in
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ApyPrecision {
+    /// Basis points (default)
+    Bps = 0,
+    /// Hundredths of a basis point, for low-yield stable strategies
+    CentiBps = 1,
+}
+
+impl ApyPrecision {
+    /// Convert basis points into this precision's unit
+    pub fn from_bps(self, bps: u32) -> u32 {
+        match self {
+            ApyPrecision::Bps => bps,
+            ApyPrecision::CentiBps => bps.saturating_mul(100),
+        }
+    }
+}
+
+/// Bits of `OracleState::status_flags`
+pub mod status {
+    /// Trading and rebalancing are halted (bit 0, as the byte's former
+    /// `paused` 0/1 value)
+    pub const PAUSED: u8 = 1 << 0;
+    /// Set by `Initialize`, or by `Migrate` for accounts that predate it
+    pub const INITIALIZED: u8 = 1 << 1;
+    /// `EmergencyWithdraw` is armed and waiting out its delay
+    pub const EMERGENCY_ARMED: u8 = 1 << 2;
+    /// A performance fee is charged (kept in step with `fee_bps`)
+    pub const FEE_ENABLED: u8 = 1 << 3;
+    /// `MonitorYields` takes observations without a nonce, unprotected from
+    /// replays; set by `Migrate` for accounts that predate nonces, cleared
+    /// by `RequireNonces`
+    pub const LEGACY_OBSERVATIONS: u8 = 1 << 4;
+    /// Safety-critical settings also need the program upgrade authority's
+    /// signature (see `RequireUpgradeAuthority`)
+    pub const REQUIRE_UPGRADE_AUTHORITY: u8 = 1 << 5;
+
+    /// Every defined bit
+    pub const ALL: u8 = PAUSED
+        | INITIALIZED
+        | EMERGENCY_ARMED
+        | FEE_ENABLED
+        | LEGACY_OBSERVATIONS
+        | REQUIRE_UPGRADE_AUTHORITY;
+}
+
 /// Oracle state storing current yield data and strategy recommendations
+///
+/// Every field is a byte or byte array (multi-byte values are stored le
+/// behind getters/setters), so the struct has alignment 1, no padding, and
+/// is `Pod`: it can be cast from account data without `unsafe`.
 #[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
 pub struct OracleState {
-    /// Is this oracle initialized? (0 = no, 1 = yes)
-    pub is_initialized: u8,
+    /// Layout version, doubling as the account discriminator and the
+    /// initialized flag (0 = uninitialized, see `VERSION_LENS`)
+    pub version: u8,
     /// Authority that can update the oracle (32 bytes)
     pub authority: [u8; 32],
     /// Current best yield protocol (0 = Raydium, 1 = Jupiter route, etc.)
+    ///
+    /// Public for compatibility; read it through `best_protocol()`, which
+    /// rejects bytes no `ProtocolId` matches.
     pub best_protocol: u8,
     /// Current APY in basis points (e.g., 1500 = 15%)
     current_apy_bps: [u8; 2],
@@ -25,36 +106,561 @@ pub struct OracleState {
     decisions_count: [u8; 8],
     /// Cumulative profit/loss in lamports (8 bytes as le, signed)
     cumulative_pnl: [u8; 8],
+    /// Lot size allocations are rounded to, in bps (0 = no rounding; a
+    /// divisor of 10000 otherwise)
+    lot_size_bps: [u8; 2],
+    /// Status bits (see `status`); formerly the `paused` byte
+    status_flags: u8,
+    /// Risk-adjusted APY (bps) an observation must reach to become actionable
+    min_actionable_adjusted_apy_bps: [u8; 2],
+    /// Authority proposed by `TransferAuthority`, awaiting acceptance (32 bytes)
+    pub pending_authority: [u8; 32],
+    /// Unix timestamp the oracle was initialized at (8 bytes as le)
+    created_at: [u8; 8],
+    /// Realized APY in basis points from booked PnL (4 bytes as le, signed)
+    realized_apy_bps: [u8; 4],
+    /// Index the next APY history sample is written to
+    history_head: u8,
+    /// Number of valid APY history samples (up to `HISTORY_LEN`)
+    history_len: u8,
+    /// Ring buffer of (timestamp i64 le, apy_bps u16 le) samples
+    apy_history: [[u8; 10]; HISTORY_LEN],
+    /// Highest cumulative PnL reached in lamports (8 bytes as le, signed)
+    peak_pnl: [u8; 8],
+    /// Drawdown from peak PnL at which deployment is fully wound down
+    /// (8 bytes as le, 0 = no soft-landing)
+    max_drawdown_lamports: [u8; 8],
+    /// Are settings frozen? (0 = no, 1 = yes)
+    config_locked: u8,
+    /// When a requested config unlock takes effect (8 bytes as le, 0 = none)
+    config_unlock_at: [u8; 8],
+    /// Two-phase rebalance progress (see `RebalancePhase`)
+    rebalance_phase: u8,
+    /// Share of a move (bps) done in phase one (0 = single-phase rebalance)
+    phase_one_fraction_bps: [u8; 2],
+    /// Have lifetime metrics been seeded by `SeedHistory`? (0 = no, 1 = yes)
+    history_seeded: u8,
+    /// Minimum seconds between accepted `MonitorYields` updates (4 bytes as le)
+    min_update_interval_secs: [u8; 4],
+    /// Unit APYs are compared in (see `ApyPrecision`)
+    apy_precision: u8,
+    /// Current APY in hundredths of a bps (4 bytes as le)
+    current_apy_centi_bps: [u8; 4],
+    /// Highest risk score the oracle will switch into (0-100)
+    max_acceptable_risk: u8,
+    /// Risk-adjusted APY (bps) a worse observation must reach to replace
+    /// stale data (2 bytes as le, 0 = any)
+    stale_override_floor_bps: [u8; 2],
+    /// Risk curve per protocol category (see `RiskCurve`, 0 = hyperbolic)
+    risk_curves: [u8; ProtocolCategory::COUNT],
+    /// Highest priority fee for non-urgent trades (8 bytes as le, 0 = no cap)
+    max_priority_fee_lamports: [u8; 8],
+    /// Latest observed APY per protocol ID, best or not (5 x u16 le)
+    protocol_apy_bps: [[u8; 2]; protocol::COUNT],
+    /// Latest observed risk score per protocol ID
+    protocol_risk: [u8; protocol::COUNT],
+    /// Per protocol ID, time until which routing to it is paused
+    /// (5 x i64 le, 0 = not paused)
+    paused_until: [[u8; 8]; protocol::COUNT],
+    /// Age (seconds) after which current data is replaced regardless of
+    /// yield (4 bytes as le, 0 = never stale; `STALE_AFTER_SECS` from
+    /// `Initialize`)
+    stale_after_secs: [u8; 4],
+    /// Ring buffer of booked PnL amounts (i64 le each), one per `add_pnl`
+    pnl_history: [[u8; 8]; PNL_HISTORY_LEN],
+    /// Index the next PnL sample is written to
+    pnl_history_head: u8,
+    /// Number of valid PnL samples (up to `PNL_HISTORY_LEN`)
+    pnl_history_len: u8,
+    /// Downside deviation of booked PnL in lamports, from `ComputeDownside`
+    /// (8 bytes as le)
+    downside_dev_lamports: [u8; 8],
+    /// Account protocol fees are paid to (32 bytes, zeros = none set)
+    pub fee_destination: [u8; 32],
+    /// Fee destination proposed by `ProposeFeeDestination` (32 bytes)
+    pub pending_fee_destination: [u8; 32],
+    /// When the pending fee destination may be accepted (8 bytes as le)
+    fee_destination_change_at: [u8; 8],
+    /// When `Migrate` last upgraded the layout (8 bytes as le, 0 = never)
+    migrated_at: [u8; 8],
+    /// When a rebalance was last settled (8 bytes as le, 0 = never)
+    last_rebalance_at: [u8; 8],
+    /// Performance fee on positive PnL, in bps of the PnL (2 bytes as le,
+    /// up to `MAX_FEE_BPS`), paid to `fee_destination`
+    fee_bps: [u8; 2],
+    /// Start of the current decision window (8 bytes as le)
+    decisions_window_start: [u8; 8],
+    /// Decisions made in the current window (2 bytes as le)
+    decisions_in_window: [u8; 2],
+    /// Most decisions per `DECISION_WINDOW_SECS` (2 bytes as le, 0 = no cap)
+    max_decisions_per_day: [u8; 2],
+    /// Highest plausible observed APY (2 bytes as le, 0 = `MAX_APY_BPS`)
+    max_apy_bps: [u8; 2],
+    /// Risk-adjusted bps another protocol must beat the best one by to
+    /// replace it (2 bytes as le, 0 = any improvement)
+    switch_margin_bps: [u8; 2],
+    /// Highest `total_value_managed` seen (8 bytes as le)
+    peak_value_managed: [u8; 8],
+    /// Worst drawdown of `total_value_managed` from its peak, in bps
+    /// (2 bytes as le)
+    max_drawdown_bps: [u8; 2],
+    /// Exponential moving average of the best protocol's APY (2 bytes as
+    /// le, 0 = not seeded)
+    apy_ema_bps: [u8; 2],
+    /// Weight of a new APY in the EMA, in bps (2 bytes as le,
+    /// 0 = `EMA_ALPHA_BPS`)
+    ema_alpha_bps: [u8; 2],
+    /// Canonical bump of the oracle PDA, stored by `Initialize` (or `Migrate`)
+    bump: u8,
+    /// Delay between arming and executing `EmergencyWithdraw` (4 bytes as
+    /// le, 0 = immediate)
+    emergency_delay_secs: [u8; 4],
+    /// When `EmergencyWithdraw` was armed (8 bytes as le, 0 = not armed)
+    emergency_armed_at: [u8; 8],
+    /// Swaps executed, also counted in `decisions_count` (8 bytes as le)
+    swaps_count: [u8; 8],
+    /// Rebalances applied, also counted in `decisions_count` (8 bytes as le)
+    rebalances_count: [u8; 8],
+    /// Timestamp of the last `RecordPnl` (8 bytes as le, 0 = never)
+    last_pnl_record_at: [u8; 8],
+    /// Drift from the target (bps, any bucket) that flags a rebalance
+    /// (2 bytes as le, 0 = never flag)
+    drift_threshold_bps: [u8; 2],
+    /// Allocation the last rebalance aimed for, after lot rounding and the
+    /// risk budget (5 x u16 le)
+    target_allocation_bps: [[u8; 2]; protocol::COUNT],
+    /// Most value `add_value` will take the oracle to (8 bytes as le,
+    /// 0 = no cap)
+    max_value_managed: [u8; 8],
+    /// May pause the oracle, but not resume it or move funds (32 bytes,
+    /// zeros = none set)
+    pub guardian: [u8; 32],
+    /// Nonce of the last accepted `MonitorYields` observation (8 bytes as le)
+    last_nonce: [u8; 8],
+    /// Fee drag (bps) taken off each protocol's APY before it is scored
+    /// (5 x u16 le)
+    protocol_fee_bps: [[u8; 2]; protocol::COUNT],
+    /// Liquidity (lamports) a monitored protocol must report to be selected
+    /// (8 bytes as le, 0 = no minimum)
+    min_liquidity: [u8; 8],
+    /// Liquidity reported with the current best protocol, for display
+    /// (8 bytes as le, 0 = not reported)
+    best_liquidity: [u8; 8],
+    /// Deposits less withdrawals, the principal `true_pnl` is measured
+    /// against (8 bytes as le, signed)
+    net_deposits: [u8; 8],
+    /// Number of `recommendations` in use (up to 5)
+    recommendations_len: u8,
+    /// Last published ranking, best first: protocol (1) + apy_bps (2, le) +
+    /// risk_score (1) per entry
+    recommendations: [[u8; 4]; protocol::COUNT],
+    /// Protocols the oracle may act on, bit i for protocol i (0 = all)
+    protocol_allowlist: u8,
+    /// When `ResetStats` last started a new stats epoch (0 = never)
+    last_reset_ts: [u8; 8],
+    /// Authority the oracle PDA was derived from (32 bytes), kept for
+    /// `signer_seeds` after `AcceptAuthority` hands the oracle over
+    seed_authority: [u8; 32],
+    /// Current allocation per bucket in bps (5 x u16 le)
+    current_allocation_bps: [[u8; 2]; protocol::COUNT],
+    /// Requested target of an in-progress rebalance (5 x u16 le)
+    rebalance_target: [[u8; 2]; protocol::COUNT],
 }
 
 impl OracleState {
     /// Size of the oracle state in bytes
-    pub const LEN: usize = 1 + 32 + 1 + 2 + 1 + 8 + 8 + 8 + 8; // 69 bytes
+    pub const LEN: usize = 1 + 32 + 1 + 2 + 1 + 8 + 8 + 8 + 8 // 69 bytes, version 1
+        + 2 + 1 + 2 + 32 + 8 + 4
+        + 1 + 1 + 10 * Self::HISTORY_LEN
+        + 8 + 8 + 1 + 8
+        + 1 + 2 + 1 + 4
+        + 1 + 4 + 1 + 2 + 3 + 8
+        + 10 + 5 + 40 + 4
+        + 8 * Self::PNL_HISTORY_LEN + 1 + 1 + 8
+        + 32 + 32 + 8
+        + 8 + 8 + 2 + 8 + 2 + 2 + 2 + 2 + 8 + 2
+        + 2 + 2 + 1 + 4 + 8 + 8 + 8 + 8
+        + 2 + 10 + 8 + 32 + 8 + 10 + 8 + 8 + 8
+        + 1 + 4 * protocol::COUNT + 1 + 8
+        + 32 + 2 * protocol::COUNT + 2 * protocol::COUNT; // 862 bytes, version 2
+
+    /// Current layout version
+    ///
+    /// Bump it, and add the new size to `VERSION_LENS`, whenever fields are
+    /// appended after a release, so `Migrate` can upgrade deployed accounts.
+    pub const VERSION: u8 = 2;
+
+    /// Size of each layout version, indexed by version (0 = uninitialized)
+    ///
+    /// Version 1 is the original 69-byte layout, whose fields the current
+    /// one starts with unchanged; its `is_initialized` byte of 1 is read as
+    /// the version.
+    pub const VERSION_LENS: [usize; Self::VERSION as usize + 1] = [0, 69, Self::LEN];
+
+    /// First version whose `status::INITIALIZED` bit is kept; older
+    /// layouts were marked initialized by the version byte alone
+    pub const STATUS_FLAGS_VERSION: u8 = 2;
+
+    /// Discriminator for account identification: the current version
+    pub const DISCRIMINATOR: u8 = Self::VERSION;
+
+    /// Number of APY samples kept in the history ring buffer
+    pub const HISTORY_LEN: usize = HISTORY_LEN;
+
+    /// Number of booked PnL amounts kept in the PnL ring buffer
+    pub const PNL_HISTORY_LEN: usize = PNL_HISTORY_LEN;
+
+    /// Minimum booked PnL samples before a downside deviation is meaningful
+    pub const MIN_DOWNSIDE_SAMPLES: usize = 2;
+
+    /// Seconds in a (365-day) year, for annualizing returns
+    pub const SECONDS_PER_YEAR: i64 = 31_536_000;
+
+    /// Minimum history before a realized APY is meaningful (1 day)
+    pub const MIN_REALIZED_APY_WINDOW_SECS: i64 = 86_400;
+
+    /// Delay between requesting and completing a config unlock (1 day)
+    pub const CONFIG_UNLOCK_DELAY_SECS: i64 = 86_400;
+
+    /// Delay between proposing and accepting a fee destination (1 day)
+    pub const FEE_DESTINATION_TIMELOCK_SECS: i64 = 86_400;
+
+    /// Length of the window `max_decisions_per_day` applies to (1 day)
+    pub const DECISION_WINDOW_SECS: i64 = 86_400;
+
+    /// Age (seconds) after which current data is replaced regardless of
+    /// yield, as set by `Initialize`
+    pub const STALE_AFTER_SECS: i64 = 3600;
+
+    /// Default highest plausible APY (500%); anything above is a feed glitch
+    pub const MAX_APY_BPS: u16 = 50_000;
+
+    /// Default weight of a new APY in the EMA (20%)
+    pub const EMA_ALPHA_BPS: u16 = 2000;
+
+    /// Highest performance fee, in bps of positive PnL (10%)
+    pub const MAX_FEE_BPS: u16 = 1000;
+
+    /// Composite score weights (points out of 100): risk-adjusted APY,
+    /// risk, freshness and drawdown
+    pub const SCORE_WEIGHTS: [u32; 4] = [40, 30, 20, 10];
 
-    /// Discriminator for account identification
-    pub const DISCRIMINATOR: u8 = 1;
+    /// Risk-adjusted APY earning the full yield weight (20%)
+    pub const SCORE_APY_TARGET_BPS: u32 = 2000;
 
     /// Seeds for PDA derivation
     pub const SEED_PREFIX: &'static [u8] = b"oracle";
 
-    /// Read oracle state from account data
-    pub fn from_bytes(data: &[u8]) -> Result<&Self, ProgramError> {
-        if data.len() < Self::LEN {
+    /// Field names and byte offsets of the canonical layout, in order
+    const FIELDS: &'static [(&'static str, usize)] = &[
+        ("version", offset_of!(OracleState, version)),
+        ("authority", offset_of!(OracleState, authority)),
+        ("best_protocol", offset_of!(OracleState, best_protocol)),
+        ("current_apy_bps", offset_of!(OracleState, current_apy_bps)),
+        ("risk_score", offset_of!(OracleState, risk_score)),
+        ("last_update", offset_of!(OracleState, last_update)),
+        ("total_value_managed", offset_of!(OracleState, total_value_managed)),
+        ("decisions_count", offset_of!(OracleState, decisions_count)),
+        ("cumulative_pnl", offset_of!(OracleState, cumulative_pnl)),
+        ("lot_size_bps", offset_of!(OracleState, lot_size_bps)),
+        ("status_flags", offset_of!(OracleState, status_flags)),
+        (
+            "min_actionable_adjusted_apy_bps",
+            offset_of!(OracleState, min_actionable_adjusted_apy_bps),
+        ),
+        ("pending_authority", offset_of!(OracleState, pending_authority)),
+        ("created_at", offset_of!(OracleState, created_at)),
+        ("realized_apy_bps", offset_of!(OracleState, realized_apy_bps)),
+        ("history_head", offset_of!(OracleState, history_head)),
+        ("history_len", offset_of!(OracleState, history_len)),
+        ("apy_history", offset_of!(OracleState, apy_history)),
+        ("peak_pnl", offset_of!(OracleState, peak_pnl)),
+        ("max_drawdown_lamports", offset_of!(OracleState, max_drawdown_lamports)),
+        ("config_locked", offset_of!(OracleState, config_locked)),
+        ("config_unlock_at", offset_of!(OracleState, config_unlock_at)),
+        ("rebalance_phase", offset_of!(OracleState, rebalance_phase)),
+        ("phase_one_fraction_bps", offset_of!(OracleState, phase_one_fraction_bps)),
+        ("history_seeded", offset_of!(OracleState, history_seeded)),
+        ("min_update_interval_secs", offset_of!(OracleState, min_update_interval_secs)),
+        ("apy_precision", offset_of!(OracleState, apy_precision)),
+        ("current_apy_centi_bps", offset_of!(OracleState, current_apy_centi_bps)),
+        ("max_acceptable_risk", offset_of!(OracleState, max_acceptable_risk)),
+        ("stale_override_floor_bps", offset_of!(OracleState, stale_override_floor_bps)),
+        ("risk_curves", offset_of!(OracleState, risk_curves)),
+        ("max_priority_fee_lamports", offset_of!(OracleState, max_priority_fee_lamports)),
+        ("protocol_apy_bps", offset_of!(OracleState, protocol_apy_bps)),
+        ("protocol_risk", offset_of!(OracleState, protocol_risk)),
+        ("paused_until", offset_of!(OracleState, paused_until)),
+        ("stale_after_secs", offset_of!(OracleState, stale_after_secs)),
+        ("pnl_history", offset_of!(OracleState, pnl_history)),
+        ("pnl_history_head", offset_of!(OracleState, pnl_history_head)),
+        ("pnl_history_len", offset_of!(OracleState, pnl_history_len)),
+        ("downside_dev_lamports", offset_of!(OracleState, downside_dev_lamports)),
+        ("fee_destination", offset_of!(OracleState, fee_destination)),
+        ("pending_fee_destination", offset_of!(OracleState, pending_fee_destination)),
+        ("fee_destination_change_at", offset_of!(OracleState, fee_destination_change_at)),
+        ("migrated_at", offset_of!(OracleState, migrated_at)),
+        ("last_rebalance_at", offset_of!(OracleState, last_rebalance_at)),
+        ("fee_bps", offset_of!(OracleState, fee_bps)),
+        ("decisions_window_start", offset_of!(OracleState, decisions_window_start)),
+        ("decisions_in_window", offset_of!(OracleState, decisions_in_window)),
+        ("max_decisions_per_day", offset_of!(OracleState, max_decisions_per_day)),
+        ("max_apy_bps", offset_of!(OracleState, max_apy_bps)),
+        ("switch_margin_bps", offset_of!(OracleState, switch_margin_bps)),
+        ("peak_value_managed", offset_of!(OracleState, peak_value_managed)),
+        ("max_drawdown_bps", offset_of!(OracleState, max_drawdown_bps)),
+        ("apy_ema_bps", offset_of!(OracleState, apy_ema_bps)),
+        ("ema_alpha_bps", offset_of!(OracleState, ema_alpha_bps)),
+        ("bump", offset_of!(OracleState, bump)),
+        ("emergency_delay_secs", offset_of!(OracleState, emergency_delay_secs)),
+        ("emergency_armed_at", offset_of!(OracleState, emergency_armed_at)),
+        ("swaps_count", offset_of!(OracleState, swaps_count)),
+        ("rebalances_count", offset_of!(OracleState, rebalances_count)),
+        ("last_pnl_record_at", offset_of!(OracleState, last_pnl_record_at)),
+        ("drift_threshold_bps", offset_of!(OracleState, drift_threshold_bps)),
+        ("target_allocation_bps", offset_of!(OracleState, target_allocation_bps)),
+        ("max_value_managed", offset_of!(OracleState, max_value_managed)),
+        ("guardian", offset_of!(OracleState, guardian)),
+        ("last_nonce", offset_of!(OracleState, last_nonce)),
+        ("protocol_fee_bps", offset_of!(OracleState, protocol_fee_bps)),
+        ("min_liquidity", offset_of!(OracleState, min_liquidity)),
+        ("best_liquidity", offset_of!(OracleState, best_liquidity)),
+        ("net_deposits", offset_of!(OracleState, net_deposits)),
+        ("recommendations_len", offset_of!(OracleState, recommendations_len)),
+        ("recommendations", offset_of!(OracleState, recommendations)),
+        ("protocol_allowlist", offset_of!(OracleState, protocol_allowlist)),
+        ("last_reset_ts", offset_of!(OracleState, last_reset_ts)),
+        ("seed_authority", offset_of!(OracleState, seed_authority)),
+        ("current_allocation_bps", offset_of!(OracleState, current_allocation_bps)),
+        ("rebalance_target", offset_of!(OracleState, rebalance_target)),
+    ];
+
+    /// Size of layout `version`, if it is a known version
+    pub fn layout_len(version: u8) -> Option<usize> {
+        Self::VERSION_LENS
+            .get(version as usize)
+            .copied()
+            .filter(|&len| len > 0)
+    }
+
+    /// Check account data can hold an initialized oracle
+    ///
+    /// A zero first byte is an account never initialized (or closed) and
+    /// fails with `NotInitialized`; an older known version fails with
+    /// `MigrationRequired`; any other byte is an account of another type.
+    fn check_discriminator(data: &[u8]) -> Result<(), ProgramError> {
+        if data.len() < Self::VERSION_LENS[1] {
             return Err(ProgramError::InvalidAccountData);
         }
-        Ok(unsafe { &*(data.as_ptr() as *const Self) })
+        match data[0] {
+            Self::VERSION if data.len() >= Self::LEN => Ok(()),
+            0 => Err(OracleError::NotInitialized.into()),
+            version => match Self::layout_len(version) {
+                Some(len) if version < Self::VERSION && data.len() >= len => {
+                    Err(OracleError::MigrationRequired.into())
+                }
+                _ => Err(ProgramError::InvalidAccountData),
+            },
+        }
+    }
+
+    /// Copy out the state of an account of any known version
+    ///
+    /// Fields the account's layout predates read as zero, their default;
+    /// check `version` before relying on one. For reads only: writes need
+    /// the account migrated and `from_bytes_mut`.
+    pub fn load_any_version(data: &[u8]) -> Result<Self, ProgramError> {
+        match Self::check_discriminator(data) {
+            Ok(()) => {}
+            Err(e) if e == OracleError::MigrationRequired.into() => {}
+            Err(e) => return Err(e),
+        }
+
+        let len = Self::VERSION_LENS[data[0] as usize];
+        let mut state = Self::zeroed();
+        bytemuck::bytes_of_mut(&mut state)[..len].copy_from_slice(&data[..len]);
+        Ok(state)
+    }
+
+    /// Upgrade account data of an older version to the current layout
+    ///
+    /// `data` must already be resized to `LEN`. Fields the old layout lacks
+    /// are zeroed, which is each field's default. Returns the version
+    /// migrated from; current accounts are left untouched.
+    pub fn migrate(data: &mut [u8], now: i64) -> Result<u8, ProgramError> {
+        match Self::check_discriminator(data) {
+            Ok(()) => return Ok(Self::VERSION),
+            Err(e) if e == OracleError::MigrationRequired.into() => {}
+            Err(e) => return Err(e),
+        }
+
+        let from = data[0];
+        let old_len = Self::VERSION_LENS[from as usize];
+        if data.len() < Self::LEN {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+
+        data[old_len..Self::LEN].fill(0);
+        let state = Self::cast_mut(data)?;
+        state.version = Self::VERSION;
+        state.set_migrated_at(now);
+
+        // Version 1, the original layout, had no status bits, nonces,
+        // staleness window, or net deposits
+        if from < 2 {
+            state.set(status::INITIALIZED);
+            // Existing clients keep working until the authority requires nonces
+            state.set(status::LEGACY_OBSERVATIONS);
+            state.set_stale_after_secs(Self::STALE_AFTER_SECS as u32);
+            // Whatever is managed beyond the booked PnL was deposited
+            let principal = state.total_value_managed() as i128 - state.cumulative_pnl() as i128;
+            state.set_net_deposits(principal.clamp(i64::MIN as i128, i64::MAX as i128) as i64);
+            // No handover was possible before, so the authority is the seed
+            state.seed_authority = state.authority;
+        }
+        Ok(from)
+    }
+
+    /// A zeroed oracle of the current version, for unit tests
+    #[cfg(test)]
+    pub fn test_state() -> Self {
+        let mut state = Self::zeroed();
+        state.version = Self::DISCRIMINATOR;
+        state
+    }
+
+    /// Read oracle state from account data
+    ///
+    /// Rejects buffers whose first byte is not `DISCRIMINATOR`, so accounts
+    /// of another type can't be reinterpreted as an oracle, and accounts of
+    /// older versions (see `load_any_version`).
+    ///
+    /// Buffers longer than `LEN` are accepted and read through their first
+    /// `LEN` bytes, so an account grown ahead of a layout upgrade keeps
+    /// working. Use `from_bytes_exact` where a size mismatch is a bug.
+    pub fn from_bytes(data: &[u8]) -> Result<&Self, ProgramError> {
+        Self::check_discriminator(data)?;
+        bytemuck::try_from_bytes(&data[..Self::LEN]).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    /// Read oracle state from account data of exactly `LEN` bytes
+    ///
+    /// As `from_bytes`, but fails with `InvalidAccountData` on any other
+    /// size.
+    pub fn from_bytes_exact(data: &[u8]) -> Result<&Self, ProgramError> {
+        Self::require_exact_len(data)?;
+        Self::from_bytes(data)
     }
 
     /// Get mutable reference to oracle state from account data
     pub fn from_bytes_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
-        if data.len() < Self::LEN {
+        Self::check_discriminator(data)?;
+        Self::cast_mut(data)
+    }
+
+    /// Get mutable reference without checking the discriminator
+    ///
+    /// Only for `Initialize`, which writes the discriminator itself. Like
+    /// `from_bytes_exact`, only an account of exactly `LEN` bytes is
+    /// accepted, so an oddly-sized one is never initialized.
+    pub(crate) fn from_bytes_mut_unchecked(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        Self::require_exact_len(data)?;
+        Self::cast_mut(data)
+    }
+
+    /// Fail with `InvalidAccountData` unless `data` is exactly `LEN` bytes
+    fn require_exact_len(data: &[u8]) -> Result<(), ProgramError> {
+        if data.len() != Self::LEN {
             return Err(ProgramError::InvalidAccountData);
         }
-        Ok(unsafe { &mut *(data.as_mut_ptr() as *mut Self) })
+        Ok(())
+    }
+
+    /// Checked cast of the first `LEN` bytes
+    fn cast_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        bytemuck::try_from_bytes_mut(&mut data[..Self::LEN])
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    /// Canonical snapshot of the state, identical to the on-disk layout
+    pub fn as_bytes(&self) -> &[u8] {
+        bytemuck::bytes_of(self)
+    }
+
+    /// Name of the first field whose bytes differ from `expected`
+    ///
+    /// Returns `None` when `expected` matches the canonical snapshot exactly.
+    pub fn first_mismatch(&self, expected: &[u8]) -> Option<&'static str> {
+        let actual = self.as_bytes();
+        if expected.len() != actual.len() {
+            return Some("length");
+        }
+
+        let index = actual.iter().zip(expected).position(|(a, e)| a != e)?;
+        Self::FIELDS
+            .iter()
+            .rev()
+            .find(|(_, offset)| *offset <= index)
+            .map(|(name, _)| *name)
+    }
+
+    /// Derive the oracle PDA for an authority, returning the address and bump
+    pub fn find_address(authority: &Address, program_id: &Address) -> (Address, u8) {
+        Address::find_program_address(&[Self::SEED_PREFIX, authority.as_ref()], program_id)
+    }
+
+    /// Verify `oracle` is the PDA derived from `[SEED_PREFIX, authority]`
+    pub fn verify_address(
+        oracle: &Address,
+        authority: &Address,
+        program_id: &Address,
+    ) -> Result<u8, ProgramError> {
+        let (expected, bump) = Self::find_address(authority, program_id);
+        if *oracle != expected {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        Ok(bump)
+    }
+
+    /// Seeds the oracle PDA signs CPIs with, from the stored bump
+    ///
+    /// Saves the bump search of `find_address` on every signed CPI. Derived
+    /// from `seed_authority`, not the current authority, since the address
+    /// doesn't move when the oracle is handed over.
+    pub fn signer_seeds(&self) -> [Seed<'_>; 3] {
+        [
+            Seed::from(Self::SEED_PREFIX),
+            Seed::from(self.seed_authority.as_slice()),
+            Seed::from(core::slice::from_ref(&self.bump)),
+        ]
+    }
+
+    /// Check the stored bump derives `oracle`, with a single hash
+    ///
+    /// Fails with `InvalidSeeds` when it doesn't, e.g. an account migrated
+    /// without its bump.
+    pub fn verify_bump(&self, oracle: &Address, program_id: &Address) -> Result<(), ProgramError> {
+        let derived = Address::create_program_address(
+            &[Self::SEED_PREFIX, self.seed_authority.as_slice(), &[self.bump]],
+            program_id,
+        )
+        .map_err(|_| ProgramError::InvalidSeeds)?;
+        if derived != *oracle {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        Ok(())
     }
 
     // ========== Getters ==========
 
+    /// Current best yield protocol, validated
+    ///
+    /// An account written by an older or buggy version may hold any byte;
+    /// those fail with `InvalidProtocol` rather than being trusted.
+    pub fn best_protocol(&self) -> Result<ProtocolId, ProgramError> {
+        ProtocolId::try_from(self.best_protocol)
+    }
+
     pub fn current_apy_bps(&self) -> u16 {
         u16::from_le_bytes(self.current_apy_bps)
     }
@@ -75,38 +681,2564 @@ impl OracleState {
         i64::from_le_bytes(self.cumulative_pnl)
     }
 
-    // ========== Setters ==========
+    pub fn lot_size_bps(&self) -> u16 {
+        u16::from_le_bytes(self.lot_size_bps)
+    }
 
-    pub fn set_current_apy_bps(&mut self, apy: u16) {
-        self.current_apy_bps = apy.to_le_bytes();
+    pub fn is_paused(&self) -> bool {
+        self.is_set(status::PAUSED)
     }
 
-    pub fn set_last_update(&mut self, ts: i64) {
-        self.last_update = ts.to_le_bytes();
+    /// Are all bits of `flags` set? (see `status`)
+    pub fn is_set(&self, flags: u8) -> bool {
+        self.status_flags & flags == flags
     }
 
-    pub fn set_total_value_managed(&mut self, val: u64) {
-        self.total_value_managed = val.to_le_bytes();
+    /// Has the oracle been initialized?
+    ///
+    /// Accounts of a layout older than `STATUS_FLAGS_VERSION` count as
+    /// initialized by their version byte, until `Migrate` sets the bit.
+    pub fn is_initialized(&self) -> bool {
+        match Self::layout_len(self.version) {
+            None => false,
+            Some(_) if self.version < Self::STATUS_FLAGS_VERSION => true,
+            Some(_) => self.is_set(status::INITIALIZED),
+        }
     }
 
-    pub fn increment_decisions(&mut self) {
-        let count = self.decisions_count().saturating_add(1);
-        self.decisions_count = count.to_le_bytes();
+    pub fn min_actionable_adjusted_apy_bps(&self) -> u16 {
+        u16::from_le_bytes(self.min_actionable_adjusted_apy_bps)
     }
 
-    pub fn add_pnl(&mut self, pnl: i64) {
-        let current = self.cumulative_pnl();
-        let new_pnl = current.saturating_add(pnl);
-        self.cumulative_pnl = new_pnl.to_le_bytes();
+    pub fn created_at(&self) -> i64 {
+        i64::from_le_bytes(self.created_at)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    pub fn last_reset_ts(&self) -> i64 {
+        i64::from_le_bytes(self.last_reset_ts)
+    }
 
-    #[test]
-    fn test_oracle_state_size() {
-        assert_eq!(OracleState::LEN, 69);
+    /// Start of the current stats epoch: the last `ResetStats`, or
+    /// Initialize if there was none
+    pub fn stats_since(&self) -> i64 {
+        self.created_at().max(self.last_reset_ts())
+    }
+
+    pub fn realized_apy_bps(&self) -> i32 {
+        i32::from_le_bytes(self.realized_apy_bps)
+    }
+
+    pub fn peak_pnl(&self) -> i64 {
+        i64::from_le_bytes(self.peak_pnl)
+    }
+
+    pub fn max_drawdown_lamports(&self) -> u64 {
+        u64::from_le_bytes(self.max_drawdown_lamports)
+    }
+
+    /// Lamports lost since the PnL peak
+    pub fn current_drawdown_lamports(&self) -> u64 {
+        self.peak_pnl().saturating_sub(self.cumulative_pnl()).max(0) as u64
+    }
+
+    /// Share of capital (bps) that may stay deployed given the drawdown
+    ///
+    /// Scales linearly from 10000 with no drawdown down to 0 once the
+    /// drawdown reaches `max_drawdown_lamports`; the rest sits in cash.
+    pub fn risk_budget_bps(&self) -> u16 {
+        let max = self.max_drawdown_lamports();
+        if max == 0 {
+            return 10000;
+        }
+
+        let drawdown = self.current_drawdown_lamports().min(max);
+        (10000 - (drawdown as u128 * 10000 / max as u128)) as u16
+    }
+
+    pub fn is_config_locked(&self) -> bool {
+        self.config_locked != 0
+    }
+
+    pub fn config_unlock_at(&self) -> i64 {
+        i64::from_le_bytes(self.config_unlock_at)
+    }
+
+    /// Current allocation per protocol bucket in bps (bucket `i` holds
+    /// protocol ID `i`)
+    pub fn current_allocation_bps(&self) -> [u16; protocol::COUNT] {
+        self.current_allocation_bps.map(u16::from_le_bytes)
+    }
+
+    /// Current allocation in lamports: one per bucket, then cash
+    ///
+    /// Each bucket is rounded down; the rounding dust lands in cash, so the
+    /// amounts always sum to `total_value_managed`.
+    pub fn allocation_lamports(&self) -> [u64; protocol::COUNT + 1] {
+        let tvl = self.total_value_managed();
+        let mut lamports = [0u64; protocol::COUNT + 1];
+        let mut deployed = 0u64;
+        for (amount, bps) in lamports.iter_mut().zip(self.current_allocation_bps()) {
+            *amount = (tvl as u128 * bps as u128 / 10000) as u64;
+            deployed += *amount;
+        }
+        lamports[protocol::COUNT] = tvl - deployed;
+        lamports
+    }
+
+    /// Has any bucket drifted from the last rebalance target by more than
+    /// `drift_threshold_bps`? Never without a threshold.
+    pub fn needs_rebalance(&self) -> bool {
+        let threshold = self.drift_threshold_bps();
+        threshold > 0
+            && self
+                .current_allocation_bps()
+                .iter()
+                .zip(self.target_allocation_bps())
+                .any(|(&current, target)| current.abs_diff(target) > threshold)
+    }
+
+    pub fn rebalance_phase(&self) -> RebalancePhase {
+        match self.rebalance_phase {
+            1 => RebalancePhase::PhaseOne,
+            _ => RebalancePhase::Idle,
+        }
+    }
+
+    pub fn rebalance_target(&self) -> [u16; protocol::COUNT] {
+        self.rebalance_target.map(u16::from_le_bytes)
+    }
+
+    pub fn phase_one_fraction_bps(&self) -> u16 {
+        u16::from_le_bytes(self.phase_one_fraction_bps)
+    }
+
+    pub fn is_history_seeded(&self) -> bool {
+        self.history_seeded != 0
+    }
+
+    pub fn min_update_interval_secs(&self) -> u32 {
+        u32::from_le_bytes(self.min_update_interval_secs)
+    }
+
+    pub fn apy_precision(&self) -> ApyPrecision {
+        match self.apy_precision {
+            1 => ApyPrecision::CentiBps,
+            _ => ApyPrecision::Bps,
+        }
+    }
+
+    pub fn current_apy_centi_bps(&self) -> u32 {
+        u32::from_le_bytes(self.current_apy_centi_bps)
+    }
+
+    pub fn max_acceptable_risk(&self) -> u8 {
+        self.max_acceptable_risk
+    }
+
+    pub fn stale_override_floor_bps(&self) -> u16 {
+        u16::from_le_bytes(self.stale_override_floor_bps)
+    }
+
+    pub fn risk_curve(&self, category: ProtocolCategory) -> RiskCurve {
+        RiskCurve::from_u8(self.risk_curves[category as usize])
+    }
+
+    /// Risk-adjusted `apy` of a protocol, under its category's curve
+    pub fn risk_adjusted_apy(&self, protocol_id: u8, apy: u32, risk_score: u8) -> u32 {
+        let curve = self.risk_curve(ProtocolCategory::of(protocol_id));
+        math::risk_adjusted_apy(apy, risk_score, curve)
+    }
+
+    pub fn max_priority_fee_lamports(&self) -> u64 {
+        u64::from_le_bytes(self.max_priority_fee_lamports)
+    }
+
+    /// Latest observed APY (bps) of every protocol, indexed by protocol ID
+    pub fn protocol_apy_bps(&self) -> [u16; protocol::COUNT] {
+        self.protocol_apy_bps.map(u16::from_le_bytes)
+    }
+
+    /// Latest observed risk score of every protocol, indexed by protocol ID
+    pub fn protocol_risk(&self) -> [u8; protocol::COUNT] {
+        self.protocol_risk
+    }
+
+    pub fn fee_destination_change_at(&self) -> i64 {
+        i64::from_le_bytes(self.fee_destination_change_at)
+    }
+
+    pub fn migrated_at(&self) -> i64 {
+        i64::from_le_bytes(self.migrated_at)
+    }
+
+    pub fn last_rebalance_at(&self) -> i64 {
+        i64::from_le_bytes(self.last_rebalance_at)
+    }
+
+    pub fn fee_bps(&self) -> u16 {
+        u16::from_le_bytes(self.fee_bps)
+    }
+
+    pub fn decisions_window_start(&self) -> i64 {
+        i64::from_le_bytes(self.decisions_window_start)
+    }
+
+    pub fn decisions_in_window(&self) -> u16 {
+        u16::from_le_bytes(self.decisions_in_window)
+    }
+
+    pub fn max_decisions_per_day(&self) -> u16 {
+        u16::from_le_bytes(self.max_decisions_per_day)
+    }
+
+    /// Performance fee (lamports) owed on booking `pnl`
+    ///
+    /// Only profit is charged, and nothing while fees are disabled or no
+    /// fee destination is set.
+    pub fn performance_fee(&self, pnl: i64) -> u64 {
+        if pnl <= 0 || !self.is_set(status::FEE_ENABLED) || self.fee_destination == [0u8; 32] {
+            return 0;
+        }
+        (pnl as u128 * self.fee_bps() as u128 / 10000) as u64
+    }
+
+    pub fn downside_dev_lamports(&self) -> u64 {
+        u64::from_le_bytes(self.downside_dev_lamports)
+    }
+
+    /// Booked PnL amounts, oldest first
+    pub fn pnl_history(&self) -> impl Iterator<Item = i64> + '_ {
+        let len = self.pnl_history_len as usize;
+        let start =
+            (self.pnl_history_head as usize + Self::PNL_HISTORY_LEN - len) % Self::PNL_HISTORY_LEN;
+        (0..len).map(move |i| {
+            i64::from_le_bytes(self.pnl_history[(start + i) % Self::PNL_HISTORY_LEN])
+        })
+    }
+
+    /// Downside deviation of the booked PnL history, in lamports
+    ///
+    /// `None` until `MIN_DOWNSIDE_SAMPLES` amounts have been booked.
+    pub fn compute_downside_dev_lamports(&self) -> Option<u64> {
+        if (self.pnl_history_len as usize) < Self::MIN_DOWNSIDE_SAMPLES {
+            return None;
+        }
+        math::downside_deviation(self.pnl_history())
+    }
+
+    /// Age (seconds) after which current data counts as stale (0 = never)
+    pub fn stale_after_secs(&self) -> i64 {
+        u32::from_le_bytes(self.stale_after_secs) as i64
+    }
+
+    /// Is the current data older than `stale_after_secs` at `now`?
+    pub fn is_stale(&self, now: i64) -> bool {
+        let stale_after = self.stale_after_secs();
+        stale_after != 0 && now.saturating_sub(self.last_update()) > stale_after
+    }
+
+    pub fn max_apy_bps(&self) -> u16 {
+        match u16::from_le_bytes(self.max_apy_bps) {
+            0 => Self::MAX_APY_BPS,
+            max => max,
+        }
+    }
+
+    pub fn switch_margin_bps(&self) -> u16 {
+        u16::from_le_bytes(self.switch_margin_bps)
+    }
+
+    pub fn peak_value_managed(&self) -> u64 {
+        u64::from_le_bytes(self.peak_value_managed)
+    }
+
+    pub fn max_drawdown_bps(&self) -> u16 {
+        u16::from_le_bytes(self.max_drawdown_bps)
+    }
+
+    pub fn apy_ema_bps(&self) -> u16 {
+        u16::from_le_bytes(self.apy_ema_bps)
+    }
+
+    pub fn bump(&self) -> u8 {
+        self.bump
+    }
+
+    pub fn emergency_delay_secs(&self) -> u32 {
+        u32::from_le_bytes(self.emergency_delay_secs)
+    }
+
+    pub fn emergency_armed_at(&self) -> i64 {
+        i64::from_le_bytes(self.emergency_armed_at)
+    }
+
+    pub fn swaps_count(&self) -> u64 {
+        u64::from_le_bytes(self.swaps_count)
+    }
+
+    pub fn rebalances_count(&self) -> u64 {
+        u64::from_le_bytes(self.rebalances_count)
+    }
+
+    pub fn last_pnl_record_at(&self) -> i64 {
+        i64::from_le_bytes(self.last_pnl_record_at)
+    }
+
+    pub fn drift_threshold_bps(&self) -> u16 {
+        u16::from_le_bytes(self.drift_threshold_bps)
+    }
+
+    pub fn target_allocation_bps(&self) -> [u16; protocol::COUNT] {
+        self.target_allocation_bps.map(u16::from_le_bytes)
+    }
+
+    pub fn max_value_managed(&self) -> u64 {
+        u64::from_le_bytes(self.max_value_managed)
+    }
+
+    pub fn last_nonce(&self) -> u64 {
+        u64::from_le_bytes(self.last_nonce)
+    }
+
+    pub fn protocol_fee_bps(&self) -> [u16; protocol::COUNT] {
+        self.protocol_fee_bps.map(u16::from_le_bytes)
+    }
+
+    pub fn min_liquidity(&self) -> u64 {
+        u64::from_le_bytes(self.min_liquidity)
+    }
+
+    pub fn best_liquidity(&self) -> u64 {
+        u64::from_le_bytes(self.best_liquidity)
+    }
+
+    pub fn net_deposits(&self) -> i64 {
+        i64::from_le_bytes(self.net_deposits)
+    }
+
+    /// Value managed beyond what was deposited, net of withdrawals
+    ///
+    /// Unlike `cumulative_pnl`, which only sums booked results, this is
+    /// unaffected by when capital came in or left. Saturates at the `i64`
+    /// bounds.
+    pub fn true_pnl(&self) -> i64 {
+        let pnl = self.total_value_managed() as i128 - self.net_deposits() as i128;
+        pnl.clamp(i64::MIN as i128, i64::MAX as i128) as i64
+    }
+
+    /// Bitmask of the protocols the oracle may act on, bit i for protocol
+    /// i; all ones when unset
+    pub fn protocol_allowlist(&self) -> u8 {
+        match self.protocol_allowlist {
+            0 => u8::MAX,
+            mask => mask,
+        }
+    }
+
+    /// Fail with `InvalidProtocol` unless the allowlist includes a protocol
+    pub fn require_protocol_allowed(&self, protocol_id: u8) -> Result<(), ProgramError> {
+        let index = protocol_index(protocol_id)?;
+        if self.protocol_allowlist() & (1 << index) == 0 {
+            return Err(OracleError::InvalidProtocol.into());
+        }
+        Ok(())
+    }
+
+    pub fn ema_alpha_bps(&self) -> u16 {
+        match u16::from_le_bytes(self.ema_alpha_bps) {
+            0 => Self::EMA_ALPHA_BPS,
+            alpha => alpha,
+        }
+    }
+
+    /// Drawdown of `total_value_managed` from its peak, in bps
+    pub fn current_drawdown_bps(&self) -> u16 {
+        let peak = self.peak_value_managed();
+        if peak == 0 {
+            return 0;
+        }
+        let drop = peak.saturating_sub(self.total_value_managed());
+        (drop as u128 * 10000 / peak as u128) as u16
+    }
+
+    /// Single 0-100 health number as of `now`, higher is healthier
+    ///
+    /// Blends, by `SCORE_WEIGHTS`:
+    /// - the current risk-adjusted APY, linear up to `SCORE_APY_TARGET_BPS`
+    /// - the current `risk_score` (0 scores full, 100 scores nothing)
+    /// - freshness: full until `stale_after_secs`, then linear down to
+    ///   nothing at twice that age; always full without a window
+    /// - the worst drawdown (`max_drawdown_bps`, 100% scores nothing)
+    pub fn composite_score(&self, now: i64) -> u8 {
+        let [apy_weight, risk_weight, fresh_weight, drawdown_weight] = Self::SCORE_WEIGHTS;
+
+        let adjusted_apy = self.risk_adjusted_apy(
+            self.best_protocol,
+            self.current_apy_bps() as u32,
+            self.risk_score,
+        );
+        let apy_points = adjusted_apy.min(Self::SCORE_APY_TARGET_BPS) * apy_weight
+            / Self::SCORE_APY_TARGET_BPS;
+
+        let risk_points = (100 - self.risk_score.min(100) as u32) * risk_weight / 100;
+
+        let stale_after = self.stale_after_secs();
+        let overdue = now.saturating_sub(self.last_update()).saturating_sub(stale_after);
+        let fresh_points = if stale_after == 0 || overdue <= 0 {
+            fresh_weight
+        } else if overdue >= stale_after {
+            0
+        } else {
+            ((stale_after - overdue) * fresh_weight as i64 / stale_after) as u32
+        };
+
+        let drawdown_points =
+            (10000 - self.max_drawdown_bps().min(10000) as u32) * drawdown_weight / 10000;
+
+        (apy_points + risk_points + fresh_points + drawdown_points).min(100) as u8
+    }
+
+    /// Is `liquidity` at least `min_liquidity`?
+    pub fn meets_min_liquidity(&self, liquidity: u64) -> bool {
+        liquidity >= self.min_liquidity()
+    }
+
+    /// Fail with `ImplausibleApy` if `apy_bps` is above `max_apy_bps`
+    pub fn check_apy_plausible(&self, apy_bps: u16) -> Result<(), OracleError> {
+        if apy_bps > self.max_apy_bps() {
+            return Err(OracleError::ImplausibleApy);
+        }
+        Ok(())
+    }
+
+    /// Time until which routing to a protocol is paused (0 = not paused)
+    pub fn protocol_paused_until(&self, protocol_id: u8) -> Result<i64, ProgramError> {
+        Ok(i64::from_le_bytes(self.paused_until[protocol_index(protocol_id)?]))
+    }
+
+    /// Is routing to a protocol paused at `now`? Pauses lapse on their own.
+    pub fn is_protocol_paused(&self, protocol_id: u8, now: i64) -> Result<bool, ProgramError> {
+        Ok(now < self.protocol_paused_until(protocol_id)?)
+    }
+
+    /// `apy` (in the configured precision's unit) of a protocol, net of its
+    /// `protocol_fee_bps`
+    pub fn net_apy(&self, protocol_id: u8, apy: u32) -> Result<u32, ProgramError> {
+        let fee = u16::from_le_bytes(self.protocol_fee_bps[protocol_index(protocol_id)?]);
+        Ok(apy.saturating_sub(self.apy_precision().from_bps(fee as u32)))
+    }
+
+    /// APY (bps) of a protocol's latest observation, net of its fee
+    pub fn net_apy_bps(&self, protocol_id: u8) -> Result<u16, ProgramError> {
+        let index = protocol_index(protocol_id)?;
+        Ok(u16::from_le_bytes(self.protocol_apy_bps[index])
+            .saturating_sub(u16::from_le_bytes(self.protocol_fee_bps[index])))
+    }
+
+    /// Risk-adjusted APY (bps) of a protocol's latest observation, net of
+    /// its fee
+    pub fn protocol_adjusted_apy_bps(&self, protocol_id: u8) -> Result<u32, ProgramError> {
+        Ok(self.risk_adjusted_apy(
+            protocol_id,
+            self.net_apy_bps(protocol_id)? as u32,
+            self.protocol_risk[protocol_index(protocol_id)?],
+        ))
+    }
+
+    /// Current APY in the configured precision's unit
+    pub fn current_apy(&self) -> u32 {
+        match self.apy_precision() {
+            ApyPrecision::Bps => self.current_apy_bps() as u32,
+            ApyPrecision::CentiBps => self.current_apy_centi_bps(),
+        }
+    }
+
+    /// Check internal accounting is consistent
+    ///
+    /// Catches fields drifting apart as features accumulate; run after every
+    /// instruction with the `strict` feature.
+    pub fn check_invariants(&self) -> Result<(), OracleError> {
+        // The peak can never trail the PnL it tracks
+        if self.peak_pnl() < self.cumulative_pnl() {
+            return Err(OracleError::InvariantViolation);
+        }
+
+        // Allocation can't exceed 100%
+        let allocated: u32 = self.current_allocation_bps().iter().map(|&bps| bps as u32).sum();
+        if allocated > 10000 {
+            return Err(OracleError::InvariantViolation);
+        }
+
+        // Ring buffer bookkeeping stays in range
+        if self.history_head as usize >= Self::HISTORY_LEN
+            || self.history_len as usize > Self::HISTORY_LEN
+            || self.pnl_history_head as usize >= Self::PNL_HISTORY_LEN
+            || self.pnl_history_len as usize > Self::PNL_HISTORY_LEN
+        {
+            return Err(OracleError::InvariantViolation);
+        }
+
+        // Flag bytes are strictly 0 or 1, and only defined status bits set
+        if self.status_flags & !status::ALL != 0
+            || self.config_locked > 1
+            || self.rebalance_phase > 1
+            || self.history_seeded > 1
+            || self.apy_precision > 1
+            || self.risk_curves.iter().any(|&curve| curve > RiskCurve::MAX)
+        {
+            return Err(OracleError::InvariantViolation);
+        }
+
+        // Risk scores are percentages
+        if self.protocol_risk.iter().any(|&risk| risk > 100) {
+            return Err(OracleError::InvariantViolation);
+        }
+
+        if self.fee_bps() > Self::MAX_FEE_BPS
+            || self.max_drawdown_bps() > 10000
+            || self.ema_alpha_bps() > 10000
+        {
+            return Err(OracleError::InvariantViolation);
+        }
+
+        Ok(())
+    }
+
+    /// Seconds until `MonitorYields` accepts another update
+    ///
+    /// Waits out the minimum update interval, which stops applying once the
+    /// current data goes stale, and a decision window already holding
+    /// `max_decisions_per_day` decisions, until it rolls over. Returns 0
+    /// when an update is allowed now.
+    pub fn seconds_until_next_decision(&self, now: i64) -> u64 {
+        let mut interval = self.min_update_interval_secs() as i64;
+        if self.stale_after_secs() != 0 {
+            interval = interval.min(self.stale_after_secs() + 1);
+        }
+        let mut allowed_at = self.last_update().saturating_add(interval);
+
+        let max = self.max_decisions_per_day();
+        if max != 0 && self.decisions_in_window() >= max {
+            // See `roll_decision_window`
+            let rolls_at = self
+                .decisions_window_start()
+                .saturating_add(Self::DECISION_WINDOW_SECS + 1);
+            allowed_at = allowed_at.max(rolls_at);
+        }
+        allowed_at.saturating_sub(now).max(0) as u64
+    }
+
+    /// Fail with `GasPriceTooHigh` if a non-urgent trade paid more than
+    /// the configured priority fee cap
+    pub fn check_priority_fee(&self, fee_lamports: u64, urgent: bool) -> Result<(), ProgramError> {
+        let max = self.max_priority_fee_lamports();
+        if !urgent && max > 0 && fee_lamports > max {
+            return Err(OracleError::GasPriceTooHigh.into());
+        }
+        Ok(())
+    }
+
+    /// Fail with `NotInitialized` unless the oracle is initialized
+    pub fn require_initialized(&self) -> Result<(), ProgramError> {
+        if !self.is_initialized() {
+            return Err(OracleError::NotInitialized.into());
+        }
+        Ok(())
+    }
+
+    /// Check `signer` is the authority of an initialized oracle
+    ///
+    /// Initialization is always checked first, so a zeroed authority can
+    /// never be matched by a zero key.
+    pub fn require_authority(&self, signer: &[u8]) -> Result<(), ProgramError> {
+        self.require_initialized()?;
+        if self.authority != *signer {
+            return Err(OracleError::InvalidAuthority.into());
+        }
+        Ok(())
+    }
+
+    /// Verify `signer` may pause: the authority, or a set guardian
+    ///
+    /// Fails like `require_authority` otherwise.
+    pub fn require_pauser(&self, signer: &[u8]) -> Result<(), ProgramError> {
+        self.require_initialized()?;
+        if self.guardian != [0u8; 32] && self.guardian == *signer {
+            return Ok(());
+        }
+        self.require_authority(signer)
+    }
+
+    /// Fail with `ConfigLocked` while settings are frozen
+    pub fn require_config_unlocked(&self) -> Result<(), ProgramError> {
+        if self.is_config_locked() {
+            return Err(OracleError::ConfigLocked.into());
+        }
+        Ok(())
+    }
+
+    /// Last published ranking as (protocol, apy_bps, risk_score), best first
+    pub fn recommendations(&self) -> impl Iterator<Item = (u8, u16, u8)> + '_ {
+        let len = (self.recommendations_len as usize).min(protocol::COUNT);
+        self.recommendations[..len]
+            .iter()
+            .map(|entry| (entry[0], u16::from_le_bytes([entry[1], entry[2]]), entry[3]))
+    }
+
+    /// Recorded (timestamp, apy_bps) samples, oldest first
+    pub fn apy_history(&self) -> impl Iterator<Item = (i64, u16)> + '_ {
+        let len = self.history_len as usize;
+        let start = (self.history_head as usize + Self::HISTORY_LEN - len) % Self::HISTORY_LEN;
+        (0..len).map(move |i| {
+            let sample = &self.apy_history[(start + i) % Self::HISTORY_LEN];
+            (
+                i64::from_le_bytes(sample[0..8].try_into().unwrap()),
+                u16::from_le_bytes([sample[8], sample[9]]),
+            )
+        })
+    }
+
+    /// Booked PnL since `stats_since` as an annualized return (bps)
+    ///
+    /// `cumulative_pnl / total_value_managed * year / elapsed`, in integer
+    /// math and saturating at the `i64` bounds. 0 with no value managed or
+    /// no time elapsed in the stats epoch.
+    pub fn annualized_return_bps(&self, now: i64) -> i64 {
+        let elapsed = now.saturating_sub(self.stats_since());
+        let tvl = self.total_value_managed();
+        if elapsed <= 0 || tvl == 0 {
+            return 0;
+        }
+
+        let annualized = self.cumulative_pnl() as i128 * 10_000 * Self::SECONDS_PER_YEAR as i128
+            / (tvl as i128 * elapsed as i128);
+        annualized.clamp(i64::MIN as i128, i64::MAX as i128) as i64
+    }
+
+    /// Annualized APY (bps) delivered by booked PnL since `stats_since`
+    ///
+    /// Returns `None` with less than `MIN_REALIZED_APY_WINDOW_SECS` of
+    /// history or no value managed, where the figure would be noise.
+    pub fn compute_realized_apy_bps(&self, now: i64) -> Option<i32> {
+        let elapsed = now.saturating_sub(self.stats_since());
+        if elapsed < Self::MIN_REALIZED_APY_WINDOW_SECS || self.total_value_managed() == 0 {
+            return None;
+        }
+
+        let apy = self.annualized_return_bps(now);
+        Some(apy.clamp(i32::MIN as i64, i32::MAX as i64) as i32)
+    }
+
+    /// Whether the realized APY beats holding at `benchmark_apy_bps`
+    ///
+    /// Ties don't count. Returns `None` when there isn't enough history to
+    /// compute a realized APY (see `compute_realized_apy_bps`).
+    pub fn beats_benchmark(&self, benchmark_apy_bps: u16, now: i64) -> Option<bool> {
+        self.compute_realized_apy_bps(now)
+            .map(|realized| realized > benchmark_apy_bps as i32)
+    }
+
+    /// Fail with `EmergencyModeActive` while the oracle is paused
+    pub fn require_not_paused(&self) -> Result<(), ProgramError> {
+        if self.is_paused() {
+            return Err(OracleError::EmergencyModeActive.into());
+        }
+        Ok(())
+    }
+
+    // ========== Setters ==========
+
+    pub fn set_current_apy_bps(&mut self, apy: u16) {
+        self.current_apy_bps = apy.to_le_bytes();
+        self.current_apy_centi_bps = (apy as u32 * 100).to_le_bytes();
+    }
+
+    /// Store a high-precision APY, keeping the bps field (floored) in step
+    pub fn set_current_apy_centi_bps(&mut self, apy: u32) {
+        self.current_apy_centi_bps = apy.to_le_bytes();
+        self.current_apy_bps = ((apy / 100).min(u16::MAX as u32) as u16).to_le_bytes();
+    }
+
+    /// Fold `apy_bps` into the APY EMA, rounding to the nearest bps
+    ///
+    /// `ema = alpha * new + (1 - alpha) * old`; an unseeded EMA starts at
+    /// `apy_bps` instead of climbing from zero.
+    pub fn update_apy_ema(&mut self, apy_bps: u16) {
+        let old = self.apy_ema_bps();
+        let ema = if old == 0 {
+            apy_bps
+        } else {
+            let alpha = self.ema_alpha_bps().min(10000) as u32;
+            ((alpha * apy_bps as u32 + (10000 - alpha) * old as u32 + 5000) / 10000) as u16
+        };
+        self.apy_ema_bps = ema.to_le_bytes();
+    }
+
+    pub fn set_bump(&mut self, bump: u8) {
+        self.bump = bump;
+    }
+
+    /// Record the authority the oracle PDA is derived from
+    pub fn set_seed_authority(&mut self, authority: &Address) {
+        self.seed_authority.copy_from_slice(authority.as_ref());
+    }
+
+    pub fn set_emergency_delay_secs(&mut self, delay: u32) {
+        self.emergency_delay_secs = delay.to_le_bytes();
+    }
+
+    pub fn set_last_pnl_record_at(&mut self, ts: i64) {
+        self.last_pnl_record_at = ts.to_le_bytes();
+    }
+
+    pub fn set_drift_threshold_bps(&mut self, threshold: u16) {
+        self.drift_threshold_bps = threshold.to_le_bytes();
+    }
+
+    pub fn set_target_allocation_bps(&mut self, target: [u16; protocol::COUNT]) {
+        self.target_allocation_bps = target.map(u16::to_le_bytes);
+    }
+
+    pub fn set_max_value_managed(&mut self, cap: u64) {
+        self.max_value_managed = cap.to_le_bytes();
+    }
+
+    pub fn set_protocol_fee_bps(&mut self, fees: [u16; protocol::COUNT]) {
+        self.protocol_fee_bps = fees.map(u16::to_le_bytes);
+    }
+
+    pub fn set_min_liquidity(&mut self, lamports: u64) {
+        self.min_liquidity = lamports.to_le_bytes();
+    }
+
+    pub fn set_best_liquidity(&mut self, lamports: u64) {
+        self.best_liquidity = lamports.to_le_bytes();
+    }
+
+    pub fn set_net_deposits(&mut self, lamports: i64) {
+        self.net_deposits = lamports.to_le_bytes();
+    }
+
+    /// Replace the published ranking, best first; entries past
+    /// `protocol::COUNT` are dropped
+    pub fn set_recommendations(&mut self, ranked: &[(u8, u16, u8)]) {
+        self.recommendations = [[0u8; 4]; protocol::COUNT];
+        let entries = self.recommendations.iter_mut().zip(ranked);
+        for (entry, &(protocol, apy_bps, risk_score)) in entries {
+            let [apy_lo, apy_hi] = apy_bps.to_le_bytes();
+            *entry = [protocol, apy_lo, apy_hi, risk_score];
+        }
+        self.recommendations_len = ranked.len().min(protocol::COUNT) as u8;
+    }
+
+    /// Arm, or check an armed, emergency withdrawal at `now`
+    ///
+    /// Returns whether funds may move now. Without a delay they always may.
+    /// Otherwise the first call arms the withdrawal and pauses the oracle,
+    /// and a call after `emergency_delay_secs` disarms it and proceeds;
+    /// calls in between fail with `TimelockNotElapsed`.
+    pub fn arm_emergency(&mut self, now: i64) -> Result<bool, ProgramError> {
+        let delay = self.emergency_delay_secs() as i64;
+        if delay == 0 {
+            return Ok(true);
+        }
+
+        let armed_at = self.emergency_armed_at();
+        if !self.is_set(status::EMERGENCY_ARMED) {
+            self.emergency_armed_at = now.to_le_bytes();
+            self.set(status::EMERGENCY_ARMED | status::PAUSED);
+            return Ok(false);
+        }
+
+        if now < armed_at.saturating_add(delay) {
+            return Err(OracleError::TimelockNotElapsed.into());
+        }
+
+        self.emergency_armed_at = [0u8; 8];
+        self.clear(status::EMERGENCY_ARMED);
+        Ok(true)
+    }
+
+    pub fn set_protocol_allowlist(&mut self, mask: u8) {
+        self.protocol_allowlist = mask;
+    }
+
+    pub fn set_ema_alpha_bps(&mut self, alpha: u16) {
+        self.ema_alpha_bps = alpha.to_le_bytes();
+    }
+
+    pub fn set_last_update(&mut self, ts: i64) {
+        self.last_update = ts.to_le_bytes();
+    }
+
+    /// Set the value under management, tracking its peak and worst drawdown
+    pub fn set_total_value_managed(&mut self, val: u64) {
+        self.total_value_managed = val.to_le_bytes();
+
+        if val > self.peak_value_managed() {
+            self.peak_value_managed = val.to_le_bytes();
+        }
+        let drawdown = self.current_drawdown_bps();
+        if drawdown > self.max_drawdown_bps() {
+            self.max_drawdown_bps = drawdown.to_le_bytes();
+        }
+    }
+
+    /// Track `amount` more value under management
+    ///
+    /// Fails with `ValueCapExceeded` if that would go past a nonzero
+    /// `max_value_managed`.
+    pub fn add_value(&mut self, amount: u64) -> Result<(), ProgramError> {
+        let total = self
+            .total_value_managed()
+            .checked_add(amount)
+            .ok_or(OracleError::ValueOverflow)?;
+        let cap = self.max_value_managed();
+        if cap != 0 && total > cap {
+            return Err(OracleError::ValueCapExceeded.into());
+        }
+        self.set_total_value_managed(total);
+        Ok(())
+    }
+
+    /// Track `amount` less value under management
+    pub fn remove_value(&mut self, amount: u64) -> Result<(), ProgramError> {
+        let total = self
+            .total_value_managed()
+            .checked_sub(amount)
+            .ok_or(OracleError::InsufficientFunds)?;
+        self.set_total_value_managed(total);
+        Ok(())
+    }
+
+    /// Track a deposit: `amount` more value managed, and more principal
+    pub fn deposit_value(&mut self, amount: u64) -> Result<(), ProgramError> {
+        self.add_value(amount)?;
+        self.set_net_deposits(self.net_deposits().saturating_add_unsigned(amount));
+        Ok(())
+    }
+
+    /// Track a withdrawal: `amount` less value managed, and less principal
+    pub fn withdraw_value(&mut self, amount: u64) -> Result<(), ProgramError> {
+        self.remove_value(amount)?;
+        self.set_net_deposits(self.net_deposits().saturating_sub_unsigned(amount));
+        Ok(())
+    }
+
+    pub fn set_lot_size_bps(&mut self, lot: u16) {
+        self.lot_size_bps = lot.to_le_bytes();
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        if paused {
+            self.set(status::PAUSED);
+        } else {
+            self.clear(status::PAUSED);
+        }
+    }
+
+    /// Set every bit of `flags` (see `status`)
+    pub fn set(&mut self, flags: u8) {
+        self.status_flags |= flags;
+    }
+
+    /// Clear every bit of `flags` (see `status`)
+    pub fn clear(&mut self, flags: u8) {
+        self.status_flags &= !flags;
+    }
+
+    pub fn set_min_actionable_adjusted_apy_bps(&mut self, floor: u16) {
+        self.min_actionable_adjusted_apy_bps = floor.to_le_bytes();
+    }
+
+    pub fn set_created_at(&mut self, ts: i64) {
+        self.created_at = ts.to_le_bytes();
+    }
+
+    pub fn set_realized_apy_bps(&mut self, apy: i32) {
+        self.realized_apy_bps = apy.to_le_bytes();
+    }
+
+    /// Start a new stats epoch at `now`
+    ///
+    /// Zeroes the counters, booked PnL and its history, and the metrics
+    /// derived from them; the drawdown peak restarts at the value managed,
+    /// and so do net deposits, so `true_pnl` restarts at 0 as well.
+    /// Authority, settings, the current decision, and the value managed are
+    /// kept, as is the daily decision window, so a reset can't lift the cap.
+    pub fn reset_stats(&mut self, now: i64) {
+        let tvl = self.total_value_managed();
+
+        self.decisions_count = [0u8; 8];
+        self.swaps_count = [0u8; 8];
+        self.rebalances_count = [0u8; 8];
+        self.cumulative_pnl = [0u8; 8];
+        self.peak_pnl = [0u8; 8];
+        self.realized_apy_bps = [0u8; 4];
+        self.downside_dev_lamports = [0u8; 8];
+        self.pnl_history = [[0u8; 8]; Self::PNL_HISTORY_LEN];
+        self.pnl_history_head = 0;
+        self.pnl_history_len = 0;
+        self.peak_value_managed = tvl.to_le_bytes();
+        self.max_drawdown_bps = [0u8; 2];
+        self.set_net_deposits(tvl.min(i64::MAX as u64) as i64);
+        self.last_reset_ts = now.to_le_bytes();
+    }
+
+    /// Record an APY sample, overwriting the oldest once full
+    pub fn push_apy_history(&mut self, ts: i64, apy: u16) {
+        let sample = &mut self.apy_history[self.history_head as usize % Self::HISTORY_LEN];
+        sample[0..8].copy_from_slice(&ts.to_le_bytes());
+        sample[8..10].copy_from_slice(&apy.to_le_bytes());
+
+        self.history_head = ((self.history_head as usize + 1) % Self::HISTORY_LEN) as u8;
+        if (self.history_len as usize) < Self::HISTORY_LEN {
+            self.history_len += 1;
+        }
+    }
+
+    /// Finalize a two-step authority handoff signed by `signer`
+    ///
+    /// Fails with `InvalidAuthority` when no handoff is pending or the
+    /// signer isn't the pending authority.
+    pub fn accept_authority(&mut self, signer: &[u8]) -> Result<(), ProgramError> {
+        if self.pending_authority == [0u8; 32] || self.pending_authority != *signer {
+            return Err(OracleError::InvalidAuthority.into());
+        }
+
+        self.authority = self.pending_authority;
+        self.pending_authority = [0u8; 32];
+        Ok(())
+    }
+
+    /// Accept `nonce` if it is above the last one
+    ///
+    /// Fails with `StaleNonce` otherwise, so a re-broadcast update can't be
+    /// applied twice.
+    pub fn advance_nonce(&mut self, nonce: u64) -> Result<(), OracleError> {
+        if nonce <= self.last_nonce() {
+            return Err(OracleError::StaleNonce);
+        }
+        self.last_nonce = nonce.to_le_bytes();
+        Ok(())
+    }
+
+    /// Count a decision against the lifetime total and the current window
+    ///
+    /// Fails with `DecisionLimitReached` once the window holds
+    /// `max_decisions_per_day` decisions; see `roll_decision_window`.
+    pub fn increment_decisions(&mut self) -> Result<(), OracleError> {
+        let max = self.max_decisions_per_day();
+        let in_window = self.decisions_in_window();
+        if max != 0 && in_window >= max {
+            return Err(OracleError::DecisionLimitReached);
+        }
+        self.decisions_in_window = in_window.saturating_add(1).to_le_bytes();
+
+        let count = self.decisions_count().saturating_add(1);
+        self.decisions_count = count.to_le_bytes();
+        Ok(())
+    }
+
+    /// Count a swap, as a decision too (see `increment_decisions`)
+    pub fn increment_swaps(&mut self) -> Result<(), OracleError> {
+        self.increment_decisions()?;
+        self.swaps_count = self.swaps_count().saturating_add(1).to_le_bytes();
+        Ok(())
+    }
+
+    /// Count a rebalance, as a decision too (see `increment_decisions`)
+    pub fn increment_rebalances(&mut self) -> Result<(), OracleError> {
+        self.increment_decisions()?;
+        self.rebalances_count = self.rebalances_count().saturating_add(1).to_le_bytes();
+        Ok(())
+    }
+
+    /// Start a new decision window at `now` (the on-chain clock) once the
+    /// current one is more than `DECISION_WINDOW_SECS` old
+    pub fn roll_decision_window(&mut self, now: i64) {
+        if now.saturating_sub(self.decisions_window_start()) > Self::DECISION_WINDOW_SECS {
+            self.decisions_window_start = now.to_le_bytes();
+            self.decisions_in_window = [0; 2];
+        }
+    }
+
+    pub fn set_max_drawdown_lamports(&mut self, max: u64) {
+        self.max_drawdown_lamports = max.to_le_bytes();
+    }
+
+    /// Store the current allocation
+    ///
+    /// Buckets may sum to less than 10000 when the risk budget holds part of
+    /// the capital in cash, but never more.
+    pub fn set_current_allocation_bps(
+        &mut self,
+        allocation: [u16; protocol::COUNT],
+    ) -> Result<(), ProgramError> {
+        let sum: u32 = allocation.iter().map(|&bps| bps as u32).sum();
+        if sum > 10000 {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        self.current_allocation_bps = allocation.map(u16::to_le_bytes);
+        Ok(())
+    }
+
+    pub fn set_rebalance_phase(&mut self, phase: RebalancePhase) {
+        self.rebalance_phase = phase as u8;
+    }
+
+    pub fn set_rebalance_target(&mut self, target: [u16; protocol::COUNT]) {
+        self.rebalance_target = target.map(u16::to_le_bytes);
+    }
+
+    pub fn set_phase_one_fraction_bps(&mut self, fraction: u16) {
+        self.phase_one_fraction_bps = fraction.min(10000).to_le_bytes();
+    }
+
+    pub fn set_min_update_interval_secs(&mut self, interval: u32) {
+        self.min_update_interval_secs = interval.to_le_bytes();
+    }
+
+    pub fn set_max_acceptable_risk(&mut self, risk: u8) {
+        self.max_acceptable_risk = risk;
+    }
+
+    pub fn set_stale_override_floor_bps(&mut self, floor: u16) {
+        self.stale_override_floor_bps = floor.to_le_bytes();
+    }
+
+    pub fn set_risk_curve(&mut self, category: ProtocolCategory, curve: RiskCurve) {
+        self.risk_curves[category as usize] = curve as u8;
+    }
+
+    pub fn set_max_priority_fee_lamports(&mut self, max: u64) {
+        self.max_priority_fee_lamports = max.to_le_bytes();
+    }
+
+    pub fn set_migrated_at(&mut self, ts: i64) {
+        self.migrated_at = ts.to_le_bytes();
+    }
+
+    pub fn set_last_rebalance_at(&mut self, ts: i64) {
+        self.last_rebalance_at = ts.to_le_bytes();
+    }
+
+    pub fn set_fee_bps(&mut self, fee_bps: u16) {
+        self.fee_bps = fee_bps.to_le_bytes();
+        if fee_bps > 0 {
+            self.set(status::FEE_ENABLED);
+        } else {
+            self.clear(status::FEE_ENABLED);
+        }
+    }
+
+    pub fn set_max_decisions_per_day(&mut self, max: u16) {
+        self.max_decisions_per_day = max.to_le_bytes();
+    }
+
+    pub fn set_max_apy_bps(&mut self, max: u16) {
+        self.max_apy_bps = max.to_le_bytes();
+    }
+
+    pub fn set_switch_margin_bps(&mut self, margin: u16) {
+        self.switch_margin_bps = margin.to_le_bytes();
+    }
+
+    pub fn set_downside_dev_lamports(&mut self, dev: u64) {
+        self.downside_dev_lamports = dev.to_le_bytes();
+    }
+
+    fn push_pnl_history(&mut self, pnl: i64) {
+        self.pnl_history[self.pnl_history_head as usize % Self::PNL_HISTORY_LEN] = pnl.to_le_bytes();
+
+        self.pnl_history_head = ((self.pnl_history_head as usize + 1) % Self::PNL_HISTORY_LEN) as u8;
+        if (self.pnl_history_len as usize) < Self::PNL_HISTORY_LEN {
+            self.pnl_history_len += 1;
+        }
+    }
+
+    pub fn set_stale_after_secs(&mut self, secs: u32) {
+        self.stale_after_secs = secs.to_le_bytes();
+    }
+
+    pub fn set_protocol_paused_until(
+        &mut self,
+        protocol_id: u8,
+        until: i64,
+    ) -> Result<(), ProgramError> {
+        self.paused_until[protocol_index(protocol_id)?] = until.to_le_bytes();
+        Ok(())
+    }
+
+    /// Record the latest observation of a protocol
+    pub fn record_protocol_observation(
+        &mut self,
+        protocol_id: u8,
+        apy_bps: u16,
+        risk_score: u8,
+    ) -> Result<(), ProgramError> {
+        let index = protocol_index(protocol_id)?;
+        self.protocol_apy_bps[index] = apy_bps.to_le_bytes();
+        self.protocol_risk[index] = risk_score;
+        Ok(())
+    }
+
+    pub fn set_apy_precision(&mut self, precision: ApyPrecision) {
+        self.apy_precision = precision as u8;
+    }
+
+    /// Freeze settings, cancelling any pending unlock
+    pub fn lock_config(&mut self) {
+        self.config_locked = 1;
+        self.config_unlock_at = [0u8; 8];
+    }
+
+    /// Propose a new fee destination, acceptable once the timelock elapses
+    ///
+    /// Replaces any earlier proposal and restarts the timelock; all zeros
+    /// cancels.
+    pub fn propose_fee_destination(&mut self, destination: [u8; 32], now: i64) {
+        self.pending_fee_destination = destination;
+        let change_at = if destination == [0u8; 32] {
+            0
+        } else {
+            now.saturating_add(Self::FEE_DESTINATION_TIMELOCK_SECS)
+        };
+        self.fee_destination_change_at = change_at.to_le_bytes();
+    }
+
+    /// Make the pending fee destination current
+    ///
+    /// Fails with `TimelockNotElapsed` before the proposal's timelock is up.
+    pub fn accept_fee_destination(&mut self, now: i64) -> Result<(), ProgramError> {
+        if self.pending_fee_destination == [0u8; 32] {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        if now < self.fee_destination_change_at() {
+            return Err(OracleError::TimelockNotElapsed.into());
+        }
+
+        self.fee_destination = self.pending_fee_destination;
+        self.pending_fee_destination = [0u8; 32];
+        self.fee_destination_change_at = [0u8; 8];
+        Ok(())
+    }
+
+    /// Step the timelocked unlock
+    ///
+    /// The first call schedules the unlock `CONFIG_UNLOCK_DELAY_SECS` out;
+    /// a call once that time has passed clears the lock. Returns whether
+    /// settings are unlocked afterwards.
+    pub fn unlock_config(&mut self, now: i64) -> Result<bool, ProgramError> {
+        if !self.is_config_locked() {
+            return Ok(true);
+        }
+
+        let unlock_at = self.config_unlock_at();
+        if unlock_at == 0 {
+            let unlock_at = now.saturating_add(Self::CONFIG_UNLOCK_DELAY_SECS);
+            self.config_unlock_at = unlock_at.to_le_bytes();
+            return Ok(false);
+        }
+
+        if now < unlock_at {
+            return Err(OracleError::ConfigLocked.into());
+        }
+
+        self.config_locked = 0;
+        self.config_unlock_at = [0u8; 8];
+        Ok(true)
+    }
+
+    /// Carry lifetime metrics over from a prior system
+    ///
+    /// Only allowed once, and only before the oracle has recorded a
+    /// decision of its own; fails with `HistoryAlreadySeeded` otherwise.
+    pub fn seed_history(
+        &mut self,
+        cumulative_pnl: i64,
+        peak_pnl: i64,
+        decisions_count: u64,
+        total_value_managed: u64,
+    ) -> Result<(), ProgramError> {
+        if self.is_history_seeded() || self.decisions_count() != 0 {
+            return Err(OracleError::HistoryAlreadySeeded.into());
+        }
+
+        // The peak can never trail the PnL it tracks
+        if peak_pnl < cumulative_pnl {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        self.cumulative_pnl = cumulative_pnl.to_le_bytes();
+        self.peak_pnl = peak_pnl.to_le_bytes();
+        self.decisions_count = decisions_count.to_le_bytes();
+        self.set_total_value_managed(total_value_managed);
+        self.history_seeded = 1;
+        Ok(())
+    }
+
+    /// Book `pnl` into the cumulative total and its history
+    ///
+    /// Fails with `PnlOverflow`, writing nothing, where the total would
+    /// leave the i64 range; clamping would hide the accounting bug.
+    pub fn add_pnl(&mut self, pnl: i64) -> Result<(), OracleError> {
+        let new_pnl = self.cumulative_pnl().checked_add(pnl).ok_or(OracleError::PnlOverflow)?;
+        self.push_pnl_history(pnl);
+        self.cumulative_pnl = new_pnl.to_le_bytes();
+
+        if new_pnl > self.peak_pnl() {
+            self.peak_pnl = new_pnl.to_le_bytes();
+        }
+        Ok(())
+    }
+
+    /// Value managed once `pnl` is realized on it
+    ///
+    /// Fails with `InsufficientFunds` for a loss larger than the value
+    /// managed, and with `ValueOverflow` past the u64 range.
+    pub fn value_after_pnl(&self, pnl: i64) -> Result<u64, OracleError> {
+        let tvl = self.total_value_managed();
+        if pnl < 0 {
+            tvl.checked_sub(pnl.unsigned_abs()).ok_or(OracleError::InsufficientFunds)
+        } else {
+            tvl.checked_add(pnl as u64).ok_or(OracleError::ValueOverflow)
+        }
+    }
+
+    /// Book `pnl` realized on the value managed, which it moved to `new_tvl`
+    ///
+    /// Principal is untouched, so the value beyond net deposits moves by
+    /// exactly `pnl`; debug builds check the two stay in step.
+    pub fn book_pnl(&mut self, pnl: i64, new_tvl: u64) -> Result<(), OracleError> {
+        let value_over_deposits =
+            |state: &Self| state.total_value_managed() as i128 - state.net_deposits() as i128;
+        let before = value_over_deposits(self);
+
+        self.add_pnl(pnl)?;
+        self.set_total_value_managed(new_tvl);
+
+        debug_assert_eq!(value_over_deposits(self), before + pnl as i128);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_oracle_state_size() {
+        assert_eq!(OracleState::LEN, 862);
+        assert_eq!(core::mem::size_of::<OracleState>(), OracleState::LEN);
+    }
+
+    #[test]
+    fn test_layout_unchanged() {
+        // Pinned so existing accounts keep loading; new fields go at the end
+        let expected = [
+            ("version", 0),
+            ("authority", 1),
+            ("best_protocol", 33),
+            ("current_apy_bps", 34),
+            ("risk_score", 36),
+            ("last_update", 37),
+            ("total_value_managed", 45),
+            ("decisions_count", 53),
+            ("cumulative_pnl", 61),
+            ("lot_size_bps", 69),
+            ("status_flags", 71),
+            ("min_actionable_adjusted_apy_bps", 72),
+            ("pending_authority", 74),
+            ("created_at", 106),
+            ("realized_apy_bps", 114),
+            ("history_head", 118),
+            ("history_len", 119),
+            ("apy_history", 120),
+            ("peak_pnl", 280),
+            ("max_drawdown_lamports", 288),
+            ("config_locked", 296),
+            ("config_unlock_at", 297),
+            ("rebalance_phase", 305),
+            ("phase_one_fraction_bps", 306),
+            ("history_seeded", 308),
+            ("min_update_interval_secs", 309),
+            ("apy_precision", 313),
+            ("current_apy_centi_bps", 314),
+            ("max_acceptable_risk", 318),
+            ("stale_override_floor_bps", 319),
+            ("risk_curves", 321),
+            ("max_priority_fee_lamports", 324),
+            ("protocol_apy_bps", 332),
+            ("protocol_risk", 342),
+            ("paused_until", 347),
+            ("stale_after_secs", 387),
+            ("pnl_history", 391),
+            ("pnl_history_head", 519),
+            ("pnl_history_len", 520),
+            ("downside_dev_lamports", 521),
+            ("fee_destination", 529),
+            ("pending_fee_destination", 561),
+            ("fee_destination_change_at", 593),
+            ("migrated_at", 601),
+            ("last_rebalance_at", 609),
+            ("fee_bps", 617),
+            ("decisions_window_start", 619),
+            ("decisions_in_window", 627),
+            ("max_decisions_per_day", 629),
+            ("max_apy_bps", 631),
+            ("switch_margin_bps", 633),
+            ("peak_value_managed", 635),
+            ("max_drawdown_bps", 643),
+            ("apy_ema_bps", 645),
+            ("ema_alpha_bps", 647),
+            ("bump", 649),
+            ("emergency_delay_secs", 650),
+            ("emergency_armed_at", 654),
+            ("swaps_count", 662),
+            ("rebalances_count", 670),
+            ("last_pnl_record_at", 678),
+            ("drift_threshold_bps", 686),
+            ("target_allocation_bps", 688),
+            ("max_value_managed", 698),
+            ("guardian", 706),
+            ("last_nonce", 738),
+            ("protocol_fee_bps", 746),
+            ("min_liquidity", 756),
+            ("best_liquidity", 764),
+            ("net_deposits", 772),
+            ("recommendations_len", 780),
+            ("recommendations", 781),
+            ("protocol_allowlist", 801),
+            ("last_reset_ts", 802),
+            ("seed_authority", 810),
+            ("current_allocation_bps", 842),
+            ("rebalance_target", 852),
+        ];
+        assert_eq!(OracleState::FIELDS, expected.as_slice());
+    }
+
+    #[test]
+    fn test_fields_cover_layout() {
+        // Every field is listed, in order, with no gap or overlap up to LEN
+        let state = OracleState::zeroed();
+        macro_rules! spans {
+            ($($field:ident),* $(,)?) => {
+                [$((
+                    stringify!($field),
+                    offset_of!(OracleState, $field),
+                    core::mem::size_of_val(&state.$field),
+                )),*]
+            };
+        }
+        let spans = spans![
+            version, authority, best_protocol, current_apy_bps, risk_score, last_update,
+            total_value_managed, decisions_count, cumulative_pnl, lot_size_bps, status_flags,
+            min_actionable_adjusted_apy_bps, pending_authority, created_at, realized_apy_bps,
+            history_head, history_len, apy_history, peak_pnl, max_drawdown_lamports, config_locked,
+            config_unlock_at, rebalance_phase, phase_one_fraction_bps, history_seeded,
+            min_update_interval_secs, apy_precision, current_apy_centi_bps, max_acceptable_risk,
+            stale_override_floor_bps, risk_curves, max_priority_fee_lamports, protocol_apy_bps,
+            protocol_risk, paused_until, stale_after_secs, pnl_history, pnl_history_head,
+            pnl_history_len, downside_dev_lamports, fee_destination, pending_fee_destination,
+            fee_destination_change_at, migrated_at, last_rebalance_at, fee_bps,
+            decisions_window_start, decisions_in_window, max_decisions_per_day, max_apy_bps,
+            switch_margin_bps, peak_value_managed, max_drawdown_bps, apy_ema_bps, ema_alpha_bps,
+            bump, emergency_delay_secs, emergency_armed_at, swaps_count, rebalances_count,
+            last_pnl_record_at, drift_threshold_bps, target_allocation_bps, max_value_managed,
+            guardian, last_nonce, protocol_fee_bps, min_liquidity, best_liquidity, net_deposits,
+            recommendations_len, recommendations, protocol_allowlist, last_reset_ts, seed_authority,
+            current_allocation_bps, rebalance_target,
+        ];
+
+        let mut end = 0;
+        for (&(name, offset, size), &field) in spans.iter().zip(OracleState::FIELDS) {
+            assert_eq!((name, offset), field);
+            assert_eq!(offset, end, "gap or overlap before {name}");
+            end += size;
+        }
+        assert_eq!(spans.len(), OracleState::FIELDS.len());
+        assert_eq!(end, OracleState::LEN);
+    }
+
+    #[test]
+    fn test_fields_round_trip() {
+        let mut data = [0u8; OracleState::LEN];
+        data[0] = OracleState::DISCRIMINATOR;
+        let state = OracleState::from_bytes_mut(&mut data).unwrap();
+
+        state.seed_history(0, 100, 0, 0).unwrap();
+        state.authority = [7u8; 32];
+        state.best_protocol = 4;
+        state.set_current_apy_bps(1500);
+        state.risk_score = 20;
+        state.set_last_update(-5);
+        state.set_total_value_managed(u64::MAX);
+        state.roll_decision_window(1_800_000_002);
+        state.increment_decisions().unwrap();
+        state.add_pnl(-42).unwrap();
+        state.set_lot_size_bps(500);
+        state.set_paused(true);
+        state.set_min_actionable_adjusted_apy_bps(250);
+        state.pending_authority = [8u8; 32];
+        state.set_created_at(1_700_000_000);
+        state.set_realized_apy_bps(-1234);
+        state.push_apy_history(1_000, 900);
+        state.set_max_drawdown_lamports(9_000);
+        state.lock_config();
+        state.set_current_allocation_bps([1000, 2000, 3000, 2000, 2000]).unwrap();
+        state.set_rebalance_phase(RebalancePhase::PhaseOne);
+        state.set_rebalance_target([2000, 2000, 2000, 3000, 1000]);
+        state.set_phase_one_fraction_bps(2500);
+        state.set_min_update_interval_secs(300);
+        state.set_apy_precision(ApyPrecision::CentiBps);
+        state.set_current_apy_centi_bps(150_025);
+        state.set_max_acceptable_risk(60);
+        state.set_stale_override_floor_bps(300);
+        state.set_risk_curve(ProtocolCategory::Lp, RiskCurve::Quadratic);
+        state.set_max_priority_fee_lamports(10_000);
+        state.record_protocol_observation(protocol::JITO, 800, 15).unwrap();
+        state.set_protocol_paused_until(protocol::KAMINO, 2_000).unwrap();
+        state.set_stale_after_secs(600);
+        state.set_downside_dev_lamports(250);
+        state.propose_fee_destination([5u8; 32], 100);
+        state.set_migrated_at(1_800_000_000);
+        state.set_last_rebalance_at(1_800_000_001);
+        state.set_fee_bps(750);
+        state.set_max_decisions_per_day(10);
+        state.set_max_apy_bps(20_000);
+        state.set_switch_margin_bps(25);
+        state.set_ema_alpha_bps(5000);
+        state.update_apy_ema(1200);
+        state.set_bump(254);
+        state.set_emergency_delay_secs(3600);
+        state.arm_emergency(1_800_000_003).unwrap();
+        state.increment_swaps().unwrap();
+        state.increment_rebalances().unwrap();
+        state.set_last_pnl_record_at(1_800_000_004);
+        state.set_drift_threshold_bps(300);
+        state.set_target_allocation_bps([1000, 2000, 3000, 2000, 1000]);
+        state.set_max_value_managed(5_000_000_000);
+        state.guardian = [6u8; 32];
+        state.advance_nonce(42).unwrap();
+        state.set_protocol_fee_bps([30, 0, 10, 5, 5]);
+        state.set_min_liquidity(1_000_000);
+        state.set_best_liquidity(2_000_000);
+        state.set_net_deposits(-3_000_000);
+        state.set_recommendations(&[(4, 1500, 20), (2, 900, 10)]);
+        state.set_protocol_allowlist(0b11000);
+
+        // Survives a reload from the raw bytes
+        let state = OracleState::from_bytes(&data).unwrap();
+        assert_eq!(state.authority, [7u8; 32]);
+        assert_eq!(state.best_protocol, 4);
+        assert_eq!(state.current_apy_bps(), 1500);
+        assert_eq!(state.risk_score, 20);
+        assert_eq!(state.last_update(), -5);
+        assert_eq!(state.total_value_managed(), u64::MAX);
+        assert_eq!(state.decisions_count(), 3);
+        assert_eq!(state.cumulative_pnl(), -42);
+        assert_eq!(state.peak_pnl(), 100);
+        assert_eq!(state.lot_size_bps(), 500);
+        assert!(state.is_paused());
+        assert_eq!(state.min_actionable_adjusted_apy_bps(), 250);
+        assert_eq!(state.pending_authority, [8u8; 32]);
+        assert_eq!(state.created_at(), 1_700_000_000);
+        assert_eq!(state.realized_apy_bps(), -1234);
+        assert_eq!(state.apy_history().collect::<Vec<_>>(), [(1_000, 900)]);
+        assert_eq!(state.max_drawdown_lamports(), 9_000);
+        assert!(state.is_config_locked());
+        assert_eq!(state.current_allocation_bps(), [1000, 2000, 3000, 2000, 2000]);
+        assert_eq!(state.rebalance_phase(), RebalancePhase::PhaseOne);
+        assert_eq!(state.rebalance_target(), [2000, 2000, 2000, 3000, 1000]);
+        assert_eq!(state.phase_one_fraction_bps(), 2500);
+        assert!(state.is_history_seeded());
+        assert_eq!(state.min_update_interval_secs(), 300);
+        assert_eq!(state.apy_precision(), ApyPrecision::CentiBps);
+        assert_eq!(state.current_apy_centi_bps(), 150_025);
+        assert_eq!(state.max_acceptable_risk(), 60);
+        assert_eq!(state.stale_override_floor_bps(), 300);
+        assert_eq!(state.risk_curve(ProtocolCategory::Lp), RiskCurve::Quadratic);
+        assert_eq!(state.risk_curve(ProtocolCategory::Staking), RiskCurve::Hyperbolic);
+        assert_eq!(state.max_priority_fee_lamports(), 10_000);
+        assert_eq!(state.protocol_apy_bps(), [0, 0, 0, 0, 800]);
+        assert_eq!(state.protocol_risk(), [0, 0, 0, 0, 15]);
+        assert_eq!(state.protocol_paused_until(protocol::KAMINO), Ok(2_000));
+        assert_eq!(state.protocol_paused_until(protocol::JITO), Ok(0));
+        assert_eq!(state.stale_after_secs(), 600);
+        assert_eq!(state.pnl_history().collect::<Vec<_>>(), [-42]);
+        assert_eq!(state.downside_dev_lamports(), 250);
+        assert_eq!(state.pending_fee_destination, [5u8; 32]);
+        assert_eq!(state.fee_destination_change_at(), 100 + OracleState::FEE_DESTINATION_TIMELOCK_SECS);
+        assert_eq!(state.migrated_at(), 1_800_000_000);
+        assert_eq!(state.last_rebalance_at(), 1_800_000_001);
+        assert_eq!(state.fee_bps(), 750);
+        assert_eq!(state.decisions_window_start(), 1_800_000_002);
+        assert_eq!(state.decisions_in_window(), 3);
+        assert_eq!(state.max_decisions_per_day(), 10);
+        assert_eq!(state.max_apy_bps(), 20_000);
+        assert_eq!(state.switch_margin_bps(), 25);
+        assert_eq!(state.peak_value_managed(), u64::MAX);
+        assert_eq!(state.max_drawdown_bps(), 0);
+        assert_eq!(state.ema_alpha_bps(), 5000);
+        assert_eq!(state.apy_ema_bps(), 1200);
+        assert_eq!(state.bump(), 254);
+        assert_eq!(state.emergency_delay_secs(), 3600);
+        assert_eq!(state.emergency_armed_at(), 1_800_000_003);
+        assert_eq!(state.swaps_count(), 1);
+        assert_eq!(state.rebalances_count(), 1);
+        assert_eq!(state.last_pnl_record_at(), 1_800_000_004);
+        assert_eq!(state.drift_threshold_bps(), 300);
+        assert_eq!(state.target_allocation_bps(), [1000, 2000, 3000, 2000, 1000]);
+        assert_eq!(state.max_value_managed(), 5_000_000_000);
+        assert_eq!(state.guardian, [6u8; 32]);
+        assert_eq!(state.last_nonce(), 42);
+        assert_eq!(state.protocol_fee_bps(), [30, 0, 10, 5, 5]);
+        assert_eq!(state.min_liquidity(), 1_000_000);
+        assert_eq!(state.best_liquidity(), 2_000_000);
+        assert_eq!(state.net_deposits(), -3_000_000);
+        assert!(state.recommendations().eq([(4, 1500, 20), (2, 900, 10)]));
+        assert_eq!(state.protocol_allowlist(), 0b11000);
+
+        // Original fields sit where the agent client reads them
+        assert_eq!(data[1..33], [7u8; 32]);
+        assert_eq!(data[33], 4);
+        assert_eq!(data[36], 20);
+        assert_eq!(data[37..45], (-5i64).to_le_bytes());
+        assert_eq!(data[61..69], (-42i64).to_le_bytes());
+    }
+
+    #[test]
+    fn test_allocation_lamports() {
+        let state = &mut OracleState::test_state();
+        state.set_total_value_managed(1_000_000_001);
+        state.set_current_allocation_bps([5000, 2500, 1000, 0, 250]).unwrap();
+
+        // 12.5% leaves cash 1250 bps, plus the lamport lost rounding down
+        assert_eq!(
+            state.allocation_lamports(),
+            [500_000_000, 250_000_000, 100_000_000, 0, 25_000_000, 125_000_001]
+        );
+
+        // No overflow at the top of the range
+        state.set_total_value_managed(u64::MAX);
+        state.set_current_allocation_bps([10000, 0, 0, 0, 0]).unwrap();
+        assert_eq!(state.allocation_lamports(), [u64::MAX, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_pause_gate() {
+        let state = &mut OracleState::test_state();
+        assert!(state.require_not_paused().is_ok());
+
+        state.set_paused(true);
+        assert_eq!(
+            state.require_not_paused(),
+            Err(OracleError::EmergencyModeActive.into())
+        );
+
+        state.set_paused(false);
+        assert!(state.require_not_paused().is_ok());
+    }
+
+    #[test]
+    fn test_protocol_pause_expires() {
+        let state = &mut OracleState::test_state();
+        state.set_protocol_paused_until(protocol::RAYDIUM_CPMM, 1_000).unwrap();
+
+        assert_eq!(state.is_protocol_paused(protocol::RAYDIUM_CPMM, 999), Ok(true));
+        assert_eq!(state.is_protocol_paused(protocol::RAYDIUM_CPMM, 1_000), Ok(false));
+        assert_eq!(state.is_protocol_paused(protocol::KAMINO, 999), Ok(false));
+
+        state.set_protocol_paused_until(protocol::RAYDIUM_CPMM, 0).unwrap();
+        assert_eq!(state.is_protocol_paused(protocol::RAYDIUM_CPMM, 0), Ok(false));
+    }
+
+    #[test]
+    fn test_pnl_history_wraps() {
+        let state = &mut OracleState::test_state();
+        assert_eq!(state.compute_downside_dev_lamports(), None);
+
+        for pnl in 0..OracleState::PNL_HISTORY_LEN as i64 + 2 {
+            state.add_pnl(-pnl).unwrap();
+        }
+        let history: Vec<i64> = state.pnl_history().collect();
+        assert_eq!(history.len(), OracleState::PNL_HISTORY_LEN);
+        assert_eq!(history[0], -2);
+        assert_eq!(history.last(), Some(&-(OracleState::PNL_HISTORY_LEN as i64 + 1)));
+        assert!(state.check_invariants().is_ok());
+    }
+
+    #[test]
+    fn test_add_pnl_refuses_to_overflow() {
+        let state = &mut OracleState::test_state();
+
+        state.add_pnl(i64::MAX - 1).unwrap();
+        assert_eq!(state.add_pnl(i64::MAX - 1), Err(OracleError::PnlOverflow));
+        assert_eq!(state.cumulative_pnl(), i64::MAX - 1);
+        assert_eq!(state.pnl_history().count(), 1);
+
+        // Likewise at the bottom
+        state.add_pnl(-(i64::MAX - 1)).unwrap();
+        state.add_pnl(i64::MIN + 1).unwrap();
+        assert_eq!(state.add_pnl(i64::MIN + 1), Err(OracleError::PnlOverflow));
+        assert_eq!(state.cumulative_pnl(), i64::MIN + 1);
+    }
+
+    #[test]
+    fn test_book_pnl_moves_value_with_pnl() {
+        let state = &mut OracleState::test_state();
+        state.deposit_value(1_000_000).unwrap();
+
+        state.book_pnl(-250_000, 750_000).unwrap();
+        assert_eq!(state.cumulative_pnl(), -250_000);
+        assert_eq!(state.true_pnl(), -250_000);
+    }
+
+    #[test]
+    fn test_compute_downside() {
+        let state = &mut OracleState::test_state();
+
+        state.add_pnl(500).unwrap();
+        assert_eq!(state.compute_downside_dev_lamports(), None);
+        state.add_pnl(1_000).unwrap();
+        assert_eq!(state.compute_downside_dev_lamports(), Some(0));
+
+        // sqrt(3000^2 / 3) = 1732
+        state.add_pnl(-3_000).unwrap();
+        assert_eq!(state.compute_downside_dev_lamports(), Some(1_732));
+    }
+
+    #[test]
+    fn test_authority_handoff() {
+        let state = &mut OracleState::test_state();
+        state.authority = [1u8; 32];
+
+        // Nothing pending yet
+        assert!(state.accept_authority(&[0u8; 32]).is_err());
+
+        state.pending_authority = [2u8; 32];
+        assert_eq!(
+            state.accept_authority(&[3u8; 32]),
+            Err(OracleError::InvalidAuthority.into())
+        );
+        assert_eq!(state.authority, [1u8; 32]);
+
+        state.accept_authority(&[2u8; 32]).unwrap();
+        assert_eq!(state.authority, [2u8; 32]);
+        assert_eq!(state.pending_authority, [0u8; 32]);
+    }
+
+    #[test]
+    fn test_value_tracking_bounds() {
+        let state = &mut OracleState::test_state();
+
+        state.add_value(1_000).unwrap();
+        state.remove_value(400).unwrap();
+        assert_eq!(state.total_value_managed(), 600);
+
+        // Withdrawing exactly the total empties it; one more is refused
+        assert_eq!(state.remove_value(601), Err(OracleError::InsufficientFunds.into()));
+        state.remove_value(600).unwrap();
+        assert_eq!(state.total_value_managed(), 0);
+        assert_eq!(state.remove_value(1), Err(OracleError::InsufficientFunds.into()));
+
+        // Filling to u64::MAX is fine; past it is refused, not saturated
+        state.add_value(u64::MAX - 1).unwrap();
+        state.add_value(1).unwrap();
+        assert_eq!(state.total_value_managed(), u64::MAX);
+        assert_eq!(state.add_value(1), Err(OracleError::ValueOverflow.into()));
+        assert_eq!(state.total_value_managed(), u64::MAX);
+    }
+
+    #[test]
+    fn test_advance_nonce() {
+        let state = &mut OracleState::test_state();
+
+        state.advance_nonce(1).unwrap();
+        state.advance_nonce(5).unwrap();
+        assert_eq!(state.last_nonce(), 5);
+
+        // Equal or lower is a replay
+        assert_eq!(state.advance_nonce(5), Err(OracleError::StaleNonce));
+        assert_eq!(state.advance_nonce(4), Err(OracleError::StaleNonce));
+        assert_eq!(state.advance_nonce(0), Err(OracleError::StaleNonce));
+        assert_eq!(state.last_nonce(), 5);
+    }
+
+    #[test]
+    fn test_net_apy() {
+        let state = &mut OracleState::test_state();
+        state.set_protocol_fee_bps([0, 0, 0, 30, 2000]);
+        state.record_protocol_observation(protocol::MARINADE, 800, 0).unwrap();
+        state.record_protocol_observation(protocol::JITO, 800, 0).unwrap();
+
+        assert_eq!(state.net_apy_bps(protocol::MARINADE), Ok(770));
+        assert_eq!(state.protocol_adjusted_apy_bps(protocol::MARINADE), Ok(770));
+        // A fee above the APY leaves nothing rather than wrapping
+        assert_eq!(state.net_apy_bps(protocol::JITO), Ok(0));
+
+        state.set_apy_precision(ApyPrecision::CentiBps);
+        assert_eq!(state.net_apy(protocol::MARINADE, 80_000), Ok(77_000));
+        assert_eq!(state.net_apy(protocol::KAMINO, 80_000), Ok(80_000));
+    }
+
+    #[test]
+    fn test_out_of_range_protocol_is_an_error() {
+        let state = &mut OracleState::test_state();
+
+        assert_eq!(protocol_index(protocol::MAX_PROTOCOL), Ok(protocol::COUNT - 1));
+
+        // Every per-protocol access refuses rather than panics
+        let invalid = Some(ProgramError::from(OracleError::InvalidProtocol));
+        for protocol_id in [protocol::MAX_PROTOCOL + 1, u8::MAX] {
+            assert_eq!(protocol_index(protocol_id).err(), invalid);
+            assert_eq!(state.protocol_paused_until(protocol_id).err(), invalid);
+            assert_eq!(state.is_protocol_paused(protocol_id, 0).err(), invalid);
+            assert_eq!(state.net_apy(protocol_id, 800).err(), invalid);
+            assert_eq!(state.net_apy_bps(protocol_id).err(), invalid);
+            assert_eq!(state.protocol_adjusted_apy_bps(protocol_id).err(), invalid);
+            assert_eq!(state.set_protocol_paused_until(protocol_id, 1_000).err(), invalid);
+            assert_eq!(state.record_protocol_observation(protocol_id, 800, 10).err(), invalid);
+        }
+        assert_eq!(state.protocol_apy_bps(), [0; protocol::COUNT]);
+    }
+
+    #[test]
+    fn test_true_pnl_follows_deposits_and_withdrawals() {
+        let state = &mut OracleState::test_state();
+
+        // 10 SOL in, then 1 SOL gained on it
+        state.deposit_value(10_000_000_000).unwrap();
+        state.set_total_value_managed(11_000_000_000);
+        state.add_pnl(1_000_000_000).unwrap();
+        assert_eq!(state.true_pnl(), 1_000_000_000);
+
+        // Capital added after the gain isn't return
+        state.deposit_value(5_000_000_000).unwrap();
+        assert_eq!(state.net_deposits(), 15_000_000_000);
+        assert_eq!(state.true_pnl(), 1_000_000_000);
+
+        // Nor is capital taken out a loss
+        state.withdraw_value(6_000_000_000).unwrap();
+        assert_eq!(state.total_value_managed(), 10_000_000_000);
+        assert_eq!(state.net_deposits(), 9_000_000_000);
+        assert_eq!(state.true_pnl(), 1_000_000_000);
+
+        // Withdrawing the gain too leaves the principal negative
+        state.withdraw_value(10_000_000_000).unwrap();
+        assert_eq!(state.net_deposits(), -1_000_000_000);
+        assert_eq!(state.true_pnl(), 1_000_000_000);
+        assert_eq!(state.withdraw_value(1), Err(OracleError::InsufficientFunds.into()));
+        assert_eq!(state.net_deposits(), -1_000_000_000);
+    }
+
+    #[test]
+    fn test_value_cap() {
+        let state = &mut OracleState::test_state();
+        state.set_max_value_managed(1_000);
+
+        // Under the cap, then exactly at it
+        state.add_value(600).unwrap();
+        state.add_value(400).unwrap();
+        assert_eq!(state.total_value_managed(), 1_000);
+
+        // Past it is refused and nothing is tracked
+        assert_eq!(state.add_value(1), Err(OracleError::ValueCapExceeded.into()));
+        assert_eq!(state.total_value_managed(), 1_000);
+
+        // 0 lifts the cap
+        state.set_max_value_managed(0);
+        state.add_value(1).unwrap();
+        assert_eq!(state.total_value_managed(), 1_001);
+    }
+
+    #[test]
+    fn test_performance_fee() {
+        let state = &mut OracleState::test_state();
+        state.set_fee_bps(500);
+
+        // No treasury yet
+        assert_eq!(state.performance_fee(1_000_000), 0);
+
+        state.fee_destination = [3u8; 32];
+        assert_eq!(state.performance_fee(1_000_000), 50_000);
+        assert_eq!(state.performance_fee(19), 0);
+        assert_eq!(state.performance_fee(i64::MAX), (i64::MAX as u128 * 500 / 10000) as u64);
+
+        // Losses and break-even pay nothing
+        assert_eq!(state.performance_fee(0), 0);
+        assert_eq!(state.performance_fee(-1_000_000), 0);
+
+        // Capped
+        state.set_fee_bps(OracleState::MAX_FEE_BPS);
+        assert_eq!(state.performance_fee(1_000_000), 100_000);
+        assert!(state.check_invariants().is_ok());
+        state.set_fee_bps(OracleState::MAX_FEE_BPS + 1);
+        assert_eq!(state.check_invariants(), Err(OracleError::InvariantViolation));
+    }
+
+    #[test]
+    fn test_apy_ema() {
+        let state = &mut OracleState::test_state();
+        assert_eq!(state.ema_alpha_bps(), OracleState::EMA_ALPHA_BPS);
+
+        // Seeded to the first spot value, not pulled up from zero
+        state.update_apy_ema(1000);
+        assert_eq!(state.apy_ema_bps(), 1000);
+
+        // 0.2 * 2000 + 0.8 * 1000 = 1200
+        state.update_apy_ema(2000);
+        assert_eq!(state.apy_ema_bps(), 1200);
+        // 0.2 * 1000 + 0.8 * 1200 = 1160
+        state.update_apy_ema(1000);
+        assert_eq!(state.apy_ema_bps(), 1160);
+        // 0.2 * 1163 + 0.8 * 1160 = 1160.6, rounded up
+        state.update_apy_ema(1163);
+        assert_eq!(state.apy_ema_bps(), 1161);
+        // 0.2 * 1158 + 0.8 * 1161 = 1160.4, rounded down
+        state.update_apy_ema(1158);
+        assert_eq!(state.apy_ema_bps(), 1160);
+
+        // Full weight follows the spot value
+        state.set_ema_alpha_bps(10000);
+        state.update_apy_ema(u16::MAX);
+        assert_eq!(state.apy_ema_bps(), u16::MAX);
+    }
+
+    #[test]
+    fn test_emergency_delay() {
+        let state = &mut OracleState::test_state();
+
+        // No delay: funds move on the first call
+        assert_eq!(state.arm_emergency(1_000), Ok(true));
+        assert_eq!(state.emergency_armed_at(), 0);
+        assert!(!state.is_paused());
+
+        // First call only arms, and pauses
+        state.set_emergency_delay_secs(3600);
+        assert_eq!(state.arm_emergency(1_000), Ok(false));
+        assert_eq!(state.emergency_armed_at(), 1_000);
+        assert!(state.is_set(status::EMERGENCY_ARMED | status::PAUSED));
+
+        // Too early
+        assert_eq!(state.arm_emergency(4_599), Err(OracleError::TimelockNotElapsed.into()));
+        assert_eq!(state.emergency_armed_at(), 1_000);
+
+        // After the delay funds move, and the next emergency arms afresh
+        assert_eq!(state.arm_emergency(4_600), Ok(true));
+        assert_eq!(state.emergency_armed_at(), 0);
+        assert!(!state.is_set(status::EMERGENCY_ARMED));
+        assert_eq!(state.arm_emergency(5_000), Ok(false));
+    }
+
+    #[test]
+    fn test_needs_rebalance() {
+        let state = &mut OracleState::test_state();
+        state.set_target_allocation_bps([4000, 3000, 3000, 0, 0]);
+        state.set_current_allocation_bps([4200, 2900, 2900, 0, 0]).unwrap();
+
+        // No threshold, no flag
+        assert!(!state.needs_rebalance());
+
+        // Within the threshold, however many buckets moved
+        state.set_drift_threshold_bps(200);
+        assert!(!state.needs_rebalance());
+
+        // One bucket beyond it, in either direction
+        state.set_current_allocation_bps([4000, 3000, 2700, 300, 0]).unwrap();
+        assert!(state.needs_rebalance());
+        state.set_current_allocation_bps([4201, 2900, 2899, 0, 0]).unwrap();
+        assert!(state.needs_rebalance());
+    }
+
+    #[test]
+    fn test_status_flags() {
+        let state = &mut OracleState::test_state();
+        let flags = [
+            status::PAUSED,
+            status::INITIALIZED,
+            status::EMERGENCY_ARMED,
+            status::FEE_ENABLED,
+            status::LEGACY_OBSERVATIONS,
+        ];
+
+        // Each bit on its own leaves the others alone
+        for flag in flags {
+            state.set(flag);
+            for other in flags {
+                assert_eq!(state.is_set(other), other == flag);
+            }
+            state.clear(flag);
+            assert_eq!(state.status_flags, 0);
+        }
+
+        // Combined, and cleared one at a time
+        state.set(status::ALL);
+        assert!(state.is_set(status::PAUSED | status::FEE_ENABLED));
+        state.clear(status::PAUSED);
+        assert!(!state.is_paused());
+        assert!(!state.is_set(status::PAUSED | status::FEE_ENABLED));
+        assert!(state.is_set(status::INITIALIZED | status::EMERGENCY_ARMED | status::FEE_ENABLED));
+
+        // The fee bit follows the fee rate
+        state.set_fee_bps(0);
+        assert!(!state.is_set(status::FEE_ENABLED));
+        state.set_fee_bps(500);
+        assert!(state.is_set(status::FEE_ENABLED));
+
+        // Paused accounts from before the flags keep their meaning
+        state.status_flags = 1;
+        assert!(state.is_paused());
+    }
+
+    #[test]
+    fn test_composite_score() {
+        let state = &mut OracleState::test_state();
+
+        // Fresh, high yield, low risk, no drawdown:
+        // 40 (2272 adjusted, capped) + 27 + 20 + 10
+        state.set_current_apy_bps(2500);
+        state.risk_score = 10;
+        state.set_last_update(100_000);
+        state.set_stale_after_secs(3600);
+        assert_eq!(state.composite_score(100_000), 97);
+        assert_eq!(state.composite_score(100_000 + 3600), 97);
+
+        // Stale: freshness decays over a further stale_after_secs
+        assert_eq!(state.composite_score(100_000 + 5400), 87);
+        assert_eq!(state.composite_score(100_000 + 7200), 77);
+        assert_eq!(state.composite_score(i64::MAX), 77);
+
+        // Without a window data never goes stale
+        state.set_stale_after_secs(0);
+        assert_eq!(state.composite_score(i64::MAX), 97);
+        state.set_stale_after_secs(3600);
+
+        // Low yield, high risk, halved at worst: 6 (333 adjusted) + 15 + 20 + 5
+        state.set_current_apy_bps(500);
+        state.risk_score = 50;
+        state.max_drawdown_bps = 5000u16.to_le_bytes();
+        assert_eq!(state.composite_score(100_000), 46);
+
+        // Worst case on every axis
+        state.set_current_apy_bps(0);
+        state.risk_score = 100;
+        state.max_drawdown_bps = 10000u16.to_le_bytes();
+        assert_eq!(state.composite_score(i64::MAX), 0);
+    }
+
+    #[test]
+    fn test_drawdown_tracking() {
+        let state = &mut OracleState::test_state();
+        assert_eq!(state.current_drawdown_bps(), 0);
+
+        // Up to a peak of 10 SOL
+        state.add_value(4_000_000_000).unwrap();
+        state.add_value(6_000_000_000).unwrap();
+        assert_eq!(state.peak_value_managed(), 10_000_000_000);
+        assert_eq!(state.max_drawdown_bps(), 0);
+
+        // Down 25% to the trough, then partway back
+        state.remove_value(1_500_000_000).unwrap();
+        assert_eq!(state.current_drawdown_bps(), 1500);
+        state.set_total_value_managed(7_500_000_000);
+        assert_eq!(state.current_drawdown_bps(), 2500);
+        state.add_value(1_500_000_000).unwrap();
+        assert_eq!(state.current_drawdown_bps(), 1000);
+        assert_eq!(state.max_drawdown_bps(), 2500);
+
+        // A new peak clears the current drawdown, not the worst one
+        state.add_value(3_000_000_000).unwrap();
+        assert_eq!(state.peak_value_managed(), 12_000_000_000);
+        assert_eq!(state.current_drawdown_bps(), 0);
+        assert_eq!(state.max_drawdown_bps(), 2500);
+
+        // Emptied out entirely
+        state.set_total_value_managed(0);
+        assert_eq!(state.max_drawdown_bps(), 10000);
+    }
+
+    #[test]
+    fn test_decision_limit() {
+        let state = &mut OracleState::test_state();
+        state.set_max_decisions_per_day(2);
+
+        // The first roll opens a window, as the start is still 0
+        let start = 1_770_000_000;
+        state.roll_decision_window(start);
+        assert_eq!(state.decisions_window_start(), start);
+        state.increment_decisions().unwrap();
+        state.increment_decisions().unwrap();
+        assert_eq!(state.increment_decisions(), Err(OracleError::DecisionLimitReached));
+        assert_eq!(state.decisions_count(), 2);
+
+        // Still the same window at exactly one day
+        state.roll_decision_window(start + OracleState::DECISION_WINDOW_SECS);
+        assert_eq!(state.increment_decisions(), Err(OracleError::DecisionLimitReached));
+
+        // Reset once the day is over
+        state.roll_decision_window(start + 1 + OracleState::DECISION_WINDOW_SECS);
+        assert_eq!(state.decisions_window_start(), start + 1 + OracleState::DECISION_WINDOW_SECS);
+        assert_eq!(state.decisions_in_window(), 0);
+        state.increment_decisions().unwrap();
+        assert_eq!(state.decisions_count(), 3);
+
+        // No cap
+        state.set_max_decisions_per_day(0);
+        for _ in 0..10 {
+            state.increment_decisions().unwrap();
+        }
+        assert_eq!(state.decisions_in_window(), 11);
+    }
+
+    #[test]
+    fn test_fee_destination_timelock() {
+        let state = &mut OracleState::test_state();
+        let unlocks_at = 1_000 + OracleState::FEE_DESTINATION_TIMELOCK_SECS;
+
+        // Nothing proposed
+        assert_eq!(state.accept_fee_destination(1_000), Err(ProgramError::InvalidArgument));
+
+        state.propose_fee_destination([7u8; 32], 1_000);
+        assert_eq!(state.pending_fee_destination, [7u8; 32]);
+        assert_eq!(state.fee_destination_change_at(), unlocks_at);
+        assert_eq!(state.fee_destination, [0u8; 32]);
+
+        // Too early
+        assert_eq!(
+            state.accept_fee_destination(unlocks_at - 1),
+            Err(OracleError::TimelockNotElapsed.into())
+        );
+        assert_eq!(state.fee_destination, [0u8; 32]);
+
+        // On time
+        state.accept_fee_destination(unlocks_at).unwrap();
+        assert_eq!(state.fee_destination, [7u8; 32]);
+        assert_eq!(state.pending_fee_destination, [0u8; 32]);
+        assert_eq!(state.fee_destination_change_at(), 0);
+
+        // A new proposal restarts the clock; zeros cancel it
+        state.propose_fee_destination([8u8; 32], unlocks_at);
+        state.propose_fee_destination([0u8; 32], unlocks_at);
+        assert_eq!(
+            state.accept_fee_destination(unlocks_at + OracleState::FEE_DESTINATION_TIMELOCK_SECS),
+            Err(ProgramError::InvalidArgument)
+        );
+        assert_eq!(state.fee_destination, [7u8; 32]);
+    }
+
+    #[test]
+    fn test_compute_realized_apy() {
+        let state = &mut OracleState::test_state();
+        state.set_created_at(1_000);
+
+        // No value managed yet
+        assert_eq!(state.compute_realized_apy_bps(1_000 + OracleState::SECONDS_PER_YEAR), None);
+
+        // 1 SOL on 10 SOL over half a year = 10%, annualized to 20%
+        state.set_total_value_managed(10_000_000_000);
+        state.add_pnl(1_000_000_000).unwrap();
+        let half_year = 1_000 + OracleState::SECONDS_PER_YEAR / 2;
+        assert_eq!(state.compute_realized_apy_bps(half_year), Some(2000));
+
+        // Losses come out negative
+        state.add_pnl(-2_000_000_000).unwrap();
+        assert_eq!(state.compute_realized_apy_bps(half_year), Some(-2000));
+
+        // Too little history
+        assert_eq!(state.compute_realized_apy_bps(1_000 + 3_600), None);
+    }
+
+    #[test]
+    fn test_annualized_return() {
+        let state = &mut OracleState::test_state();
+        state.set_created_at(1_000);
+        state.add_pnl(500_000_000).unwrap();
+
+        // Nothing managed, or no time elapsed
+        assert_eq!(state.annualized_return_bps(1_000 + OracleState::SECONDS_PER_YEAR), 0);
+        state.set_total_value_managed(10_000_000_000);
+        assert_eq!(state.annualized_return_bps(1_000), 0);
+        assert_eq!(state.annualized_return_bps(999), 0);
+
+        // 0.5 SOL on 10 SOL over a quarter = 5%, annualized to 20%
+        let quarter = 1_000 + OracleState::SECONDS_PER_YEAR / 4;
+        assert_eq!(state.annualized_return_bps(quarter), 2000);
+        assert_eq!(state.annualized_return_bps(1_000 + OracleState::SECONDS_PER_YEAR), 500);
+
+        // Unlike the realized APY there is no minimum window
+        assert_eq!(state.annualized_return_bps(1_000 + 3_600), 4_380_000);
+        assert_eq!(state.compute_realized_apy_bps(1_000 + 3_600), None);
+    }
+
+    #[test]
+    fn test_beats_benchmark() {
+        let state = &mut OracleState::test_state();
+        state.set_created_at(1_000);
+        let half_year = 1_000 + OracleState::SECONDS_PER_YEAR / 2;
+
+        // Not enough data yet
+        assert_eq!(state.beats_benchmark(700, half_year), None);
+
+        // 20% realized
+        state.set_total_value_managed(10_000_000_000);
+        state.add_pnl(1_000_000_000).unwrap();
+        assert_eq!(state.beats_benchmark(700, half_year), Some(true));
+        assert_eq!(state.beats_benchmark(2000, half_year), Some(false));
+        assert_eq!(state.beats_benchmark(2500, half_year), Some(false));
+
+        // Losing money never beats holding
+        state.add_pnl(-2_000_000_000).unwrap();
+        assert_eq!(state.beats_benchmark(0, half_year), Some(false));
+
+        // Too little elapsed time
+        assert_eq!(state.beats_benchmark(0, 1_000 + 3_600), None);
+    }
+
+    #[test]
+    fn test_apy_history_keeps_last_samples() {
+        let state = &mut OracleState::test_state();
+        assert_eq!(state.apy_history().count(), 0);
+
+        for i in 0..20u16 {
+            state.push_apy_history(1_000 + i as i64, 100 + i);
+        }
+
+        let history: Vec<(i64, u16)> = state.apy_history().collect();
+        let expected: Vec<(i64, u16)> = (4..20u16).map(|i| (1_000 + i as i64, 100 + i)).collect();
+        assert_eq!(history, expected);
+    }
+
+    #[test]
+    fn test_risk_budget_shrinks_with_drawdown() {
+        let state = &mut OracleState::test_state();
+
+        // Disabled without a max drawdown
+        state.add_pnl(-5_000).unwrap();
+        assert_eq!(state.risk_budget_bps(), 10000);
+
+        state.add_pnl(5_000 + 1_000_000).unwrap();
+        state.set_max_drawdown_lamports(400_000);
+        assert_eq!(state.peak_pnl(), 1_000_000);
+        assert_eq!(state.risk_budget_bps(), 10000);
+
+        state.add_pnl(-100_000).unwrap();
+        assert_eq!(state.current_drawdown_lamports(), 100_000);
+        assert_eq!(state.risk_budget_bps(), 7500);
+
+        state.add_pnl(-100_000).unwrap();
+        assert_eq!(state.risk_budget_bps(), 5000);
+
+        state.add_pnl(-100_000).unwrap();
+        assert_eq!(state.risk_budget_bps(), 2500);
+
+        state.add_pnl(-500_000).unwrap();
+        assert_eq!(state.risk_budget_bps(), 0);
+    }
+
+    #[test]
+    fn test_config_lock_timelock() {
+        let state = &mut OracleState::test_state();
+        assert!(state.require_config_unlocked().is_ok());
+
+        state.lock_config();
+        assert_eq!(state.require_config_unlocked(), Err(OracleError::ConfigLocked.into()));
+
+        // Requesting the unlock keeps settings frozen
+        assert_eq!(state.unlock_config(1_000), Ok(false));
+        assert!(state.require_config_unlocked().is_err());
+
+        // Too early
+        let unlock_at = 1_000 + OracleState::CONFIG_UNLOCK_DELAY_SECS;
+        assert_eq!(state.unlock_config(unlock_at - 1), Err(OracleError::ConfigLocked.into()));
+        assert!(state.is_config_locked());
+
+        assert_eq!(state.unlock_config(unlock_at), Ok(true));
+        assert!(state.require_config_unlocked().is_ok());
+        assert_eq!(state.config_unlock_at(), 0);
+    }
+
+    #[test]
+    fn test_seconds_until_next_decision() {
+        let state = &mut OracleState::test_state();
+        state.set_last_update(10_000);
+
+        // No throttle configured
+        assert_eq!(state.seconds_until_next_decision(10_000), 0);
+
+        state.set_min_update_interval_secs(300);
+        assert_eq!(state.seconds_until_next_decision(10_000), 300);
+        assert_eq!(state.seconds_until_next_decision(10_200), 100);
+        assert_eq!(state.seconds_until_next_decision(10_300), 0);
+        assert_eq!(state.seconds_until_next_decision(20_000), 0);
+
+        // Staleness caps the wait, unless data never goes stale
+        state.set_min_update_interval_secs(7_200);
+        assert_eq!(state.seconds_until_next_decision(10_000), 7_200);
+        state.set_stale_after_secs(3600);
+        assert_eq!(state.seconds_until_next_decision(10_000), 3601);
+
+        // A full decision window holds until it rolls over, stale or not
+        let start = 1_770_000_000;
+        state.set_max_decisions_per_day(1);
+        state.roll_decision_window(start);
+        state.increment_decisions().unwrap();
+        let rolls_at = start + OracleState::DECISION_WINDOW_SECS + 1;
+        assert_eq!(state.seconds_until_next_decision(start + 100), (rolls_at - start - 100) as u64);
+        assert_eq!(state.seconds_until_next_decision(rolls_at), 0);
+    }
+
+    #[test]
+    fn test_stale_window_boundary() {
+        let state = &mut OracleState::test_state();
+        state.set_last_update(10_000);
+
+        state.set_stale_after_secs(300);
+        assert!(!state.is_stale(10_300));
+        assert!(state.is_stale(10_301));
+
+        state.set_stale_after_secs(86_400);
+        assert!(!state.is_stale(10_000 + 86_400));
+        assert!(state.is_stale(10_000 + 86_401));
+
+        // 0 disables staleness altogether
+        state.set_stale_after_secs(0);
+        assert!(!state.is_stale(i64::MAX));
+    }
+
+    #[test]
+    fn test_priority_fee_cap() {
+        let state = &mut OracleState::test_state();
+
+        // Uncapped by default
+        assert!(state.check_priority_fee(u64::MAX, false).is_ok());
+
+        state.set_max_priority_fee_lamports(10_000);
+        assert!(state.check_priority_fee(10_000, false).is_ok());
+        assert_eq!(
+            state.check_priority_fee(10_001, false),
+            Err(OracleError::GasPriceTooHigh.into())
+        );
+
+        // Urgent exits pay whatever it takes
+        assert!(state.check_priority_fee(1_000_000, true).is_ok());
+    }
+
+    #[test]
+    fn test_check_invariants() {
+        let state = &mut OracleState::test_state();
+        state.add_pnl(500).unwrap();
+        state.add_pnl(-200).unwrap();
+        state.set_current_allocation_bps([2000; 5]).unwrap();
+        state.push_apy_history(1_000, 1500);
+        assert_eq!(state.check_invariants(), Ok(()));
+
+        // Peak behind cumulative PnL
+        state.peak_pnl = 100i64.to_le_bytes();
+        assert_eq!(state.check_invariants(), Err(OracleError::InvariantViolation));
+        state.peak_pnl = 500i64.to_le_bytes();
+
+        // Over-allocated
+        state.current_allocation_bps[4] = 2001u16.to_le_bytes();
+        assert_eq!(state.check_invariants(), Err(OracleError::InvariantViolation));
+        state.current_allocation_bps[4] = 2000u16.to_le_bytes();
+
+        // History head out of range
+        state.history_head = OracleState::HISTORY_LEN as u8;
+        assert_eq!(state.check_invariants(), Err(OracleError::InvariantViolation));
+        state.history_head = 1;
+
+        // Undefined status bit
+        state.status_flags = status::ALL + 1;
+        assert_eq!(state.check_invariants(), Err(OracleError::InvariantViolation));
+        state.status_flags = status::ALL;
+
+        assert_eq!(state.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn test_first_mismatch() {
+        let mut data = [0u8; OracleState::LEN];
+        data[0] = OracleState::DISCRIMINATOR;
+        let expected = data;
+
+        let state = OracleState::from_bytes_mut(&mut data).unwrap();
+        state.set_current_apy_bps(1500);
+        state.risk_score = 20;
+        let snapshot = state.as_bytes();
+        assert_eq!(state.first_mismatch(snapshot), None);
+
+        // Only the risk score differs once the APY is brought back in line
+        let mut stale = expected;
+        stale[34..36].copy_from_slice(&1500u16.to_le_bytes());
+        assert_eq!(state.first_mismatch(&stale), Some("risk_score"));
+
+        assert_eq!(state.first_mismatch(&expected[..10]), Some("length"));
+    }
+
+    #[test]
+    fn test_from_bytes_rejects_wrong_discriminator() {
+        let mut data = [0u8; OracleState::LEN];
+        data[0] = OracleState::VERSION + 1;
+        assert_eq!(OracleState::from_bytes(&data).err(), Some(ProgramError::InvalidAccountData));
+        assert_eq!(
+            OracleState::from_bytes_mut(&mut data).err(),
+            Some(ProgramError::InvalidAccountData)
+        );
+
+        // Fresh, zeroed accounts are not oracles yet either
+        let zeroed = [0u8; OracleState::LEN];
+        assert_eq!(
+            OracleState::from_bytes(&zeroed).err(),
+            Some(OracleError::NotInitialized.into())
+        );
+    }
+
+    /// A version 1 account, as deployed with the original 69-byte layout
+    fn v1_account() -> [u8; 69] {
+        let mut v1 = [0u8; 69];
+        v1[0] = 1; // is_initialized
+        v1[1..33].copy_from_slice(&[7u8; 32]); // authority
+        v1[33] = protocol::MARINADE; // best_protocol
+        v1[34..36].copy_from_slice(&850u16.to_le_bytes()); // current_apy_bps
+        v1[36] = 15; // risk_score
+        v1[37..45].copy_from_slice(&1_770_000_000i64.to_le_bytes()); // last_update
+        v1[45..53].copy_from_slice(&5_000u64.to_le_bytes()); // total_value_managed
+        v1[53..61].copy_from_slice(&42u64.to_le_bytes()); // decisions_count
+        v1[61..69].copy_from_slice(&(-300i64).to_le_bytes()); // cumulative_pnl
+        v1
+    }
+
+    #[test]
+    fn test_older_versions_need_migration() {
+        let v1 = v1_account();
+        assert_eq!(OracleState::layout_len(1), Some(v1.len()));
+        assert_eq!(
+            OracleState::from_bytes(&v1).err(),
+            Some(OracleError::MigrationRequired.into())
+        );
+
+        // Still readable, with newer fields at their defaults
+        let state = OracleState::load_any_version(&v1).unwrap();
+        assert_eq!(state.version, 1);
+        assert_eq!(state.authority, [7u8; 32]);
+        assert_eq!(state.current_apy_bps(), 850);
+        assert_eq!(state.total_value_managed(), 5_000);
+        assert_eq!(state.cumulative_pnl(), -300);
+        assert_eq!(state.migrated_at(), 0);
+        assert!(state.is_initialized());
+        assert!(state.require_authority(&[7u8; 32]).is_ok());
+
+        // Too short for the version it claims
+        assert_eq!(
+            OracleState::from_bytes(&v1[..60]).err(),
+            Some(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn test_migrate_v1_to_current() {
+        // As after the account is resized: old bytes, then whatever follows
+        let mut data = [0xAAu8; OracleState::LEN];
+        data[..69].copy_from_slice(&v1_account());
+
+        assert_eq!(OracleState::migrate(&mut data, 1_900_000_000), Ok(1));
+        let state = OracleState::from_bytes(&data).unwrap();
+        assert_eq!(state.version, OracleState::VERSION);
+
+        // Every original field is kept where it was
+        assert_eq!(state.authority, [7u8; 32]);
+        assert_eq!(state.best_protocol, protocol::MARINADE);
+        assert_eq!(state.current_apy_bps(), 850);
+        assert_eq!(state.risk_score, 15);
+        assert_eq!(state.last_update(), 1_770_000_000);
+        assert_eq!(state.total_value_managed(), 5_000);
+        assert_eq!(state.decisions_count(), 42);
+        assert_eq!(state.cumulative_pnl(), -300);
+
+        // ...and the rest start from their defaults
+        assert_eq!(state.net_deposits(), 5_300);
+        assert_eq!(state.true_pnl(), -300);
+        assert_eq!(state.seed_authority, [7u8; 32]);
+        assert_eq!(state.migrated_at(), 1_900_000_000);
+        assert_eq!(state.last_rebalance_at(), 0);
+        assert_eq!(state.fee_destination, [0u8; 32]);
+        assert_eq!(state.current_allocation_bps(), [0; protocol::COUNT]);
+        assert!(state.is_initialized());
+        assert!(!state.is_paused());
+        assert!(state.is_set(status::LEGACY_OBSERVATIONS));
+        assert_eq!(state.stale_after_secs(), OracleState::STALE_AFTER_SECS);
+        assert!(state.check_invariants().is_ok());
+
+        // Migrating again changes nothing
+        let before = data;
+        assert_eq!(OracleState::migrate(&mut data, 2_000_000_000), Ok(OracleState::VERSION));
+        assert_eq!(data, before);
+
+        // Not resized yet
+        let mut v1 = v1_account();
+        assert_eq!(
+            OracleState::migrate(&mut v1, 0),
+            Err(ProgramError::AccountDataTooSmall)
+        );
+
+        // Never initialized
+        let mut zeroed = [0u8; OracleState::LEN];
+        assert_eq!(
+            OracleState::migrate(&mut zeroed, 0),
+            Err(OracleError::NotInitialized.into())
+        );
+    }
+
+    #[test]
+    fn test_uninitialized_never_reports_invalid_authority() {
+        // Zeroed account signed for by the zero key
+        let mut data = [0u8; OracleState::LEN];
+        assert_eq!(
+            OracleState::from_bytes_mut(&mut data).err(),
+            Some(OracleError::NotInitialized.into())
+        );
+
+        let state = OracleState::from_bytes_mut_unchecked(&mut data).unwrap();
+        assert_eq!(state.require_authority(&[0u8; 32]), Err(OracleError::NotInitialized.into()));
+        assert_eq!(state.require_authority(&[9u8; 32]), Err(OracleError::NotInitialized.into()));
+
+        // The current layout also needs the status bit
+        state.version = OracleState::DISCRIMINATOR;
+        assert_eq!(state.require_authority(&[0u8; 32]), Err(OracleError::NotInitialized.into()));
+        state.set(status::INITIALIZED);
+        assert!(state.require_authority(&[0u8; 32]).is_ok());
+        assert_eq!(state.require_authority(&[9u8; 32]), Err(OracleError::InvalidAuthority.into()));
+    }
+
+    #[test]
+    fn test_guardian_can_only_pause() {
+        let state = &mut OracleState::test_state();
+        state.set(status::INITIALIZED);
+        state.authority = [1u8; 32];
+
+        // Without a guardian only the authority pauses, the zero key included
+        assert!(state.require_pauser(&[1u8; 32]).is_ok());
+        assert_eq!(state.require_pauser(&[0u8; 32]), Err(OracleError::InvalidAuthority.into()));
+        assert_eq!(state.require_pauser(&[2u8; 32]), Err(OracleError::InvalidAuthority.into()));
+
+        state.guardian = [2u8; 32];
+        assert!(state.require_pauser(&[1u8; 32]).is_ok());
+        assert!(state.require_pauser(&[2u8; 32]).is_ok());
+        assert_eq!(state.require_pauser(&[3u8; 32]), Err(OracleError::InvalidAuthority.into()));
+
+        // Withdrawing and transferring authority stay authority-only
+        assert_eq!(state.require_authority(&[2u8; 32]), Err(OracleError::InvalidAuthority.into()));
+
+        // An uninitialized account has no pauser
+        state.clear(status::INITIALIZED);
+        assert_eq!(state.require_pauser(&[2u8; 32]), Err(OracleError::NotInitialized.into()));
+    }
+
+    #[test]
+    fn test_from_bytes_exact_size() {
+        let mut data = [0u8; OracleState::LEN + 8];
+        data[0] = OracleState::DISCRIMINATOR;
+
+        // Exact
+        assert!(OracleState::from_bytes_exact(&data[..OracleState::LEN]).is_ok());
+        assert!(OracleState::from_bytes_mut_unchecked(&mut data[..OracleState::LEN]).is_ok());
+
+        // Oversized: tolerated by from_bytes only
+        assert!(OracleState::from_bytes(&data).is_ok());
+        assert_eq!(
+            OracleState::from_bytes_exact(&data).err(),
+            Some(ProgramError::InvalidAccountData)
+        );
+        assert_eq!(
+            OracleState::from_bytes_mut_unchecked(&mut data).err(),
+            Some(ProgramError::InvalidAccountData)
+        );
+
+        // Undersized
+        let short = &data[..OracleState::LEN - 1];
+        assert_eq!(OracleState::from_bytes(short).err(), Some(ProgramError::InvalidAccountData));
+        assert_eq!(
+            OracleState::from_bytes_exact(short).err(),
+            Some(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn test_from_bytes_accepts_discriminator() {
+        let mut data = [0u8; OracleState::LEN];
+        data[0] = OracleState::DISCRIMINATOR;
+        assert!(OracleState::from_bytes(&data).is_ok());
+        assert!(OracleState::from_bytes_mut(&mut data).is_ok());
+    }
+
+    #[test]
+    fn test_verify_address_rejects_non_pda() {
+        let program_id = Address::new_from_array([7u8; 32]);
+        let authority = Address::new_from_array([9u8; 32]);
+
+        let (pda, bump) = OracleState::find_address(&authority, &program_id);
+        assert_eq!(OracleState::verify_address(&pda, &authority, &program_id), Ok(bump));
+
+        let not_pda = Address::new_from_array([1u8; 32]);
+        assert_eq!(
+            OracleState::verify_address(&not_pda, &authority, &program_id),
+            Err(ProgramError::InvalidSeeds)
+        );
+    }
+
+    #[test]
+    fn test_stored_bump_matches_find_address() {
+        let program_id = Address::new_from_array([7u8; 32]);
+        let authority = Address::new_from_array([9u8; 32]);
+        let (pda, bump) = OracleState::find_address(&authority, &program_id);
+
+        let state = &mut OracleState::test_state();
+        state.authority = [9u8; 32];
+        state.set_seed_authority(&authority);
+        state.set_bump(OracleState::verify_address(&pda, &authority, &program_id).unwrap());
+
+        assert_eq!(state.bump(), bump);
+        assert_eq!(state.verify_bump(&pda, &program_id), Ok(()));
+        assert_eq!(state.signer_seeds().len(), 3);
+
+        // Any other bump derives another address, or none
+        state.set_bump(bump.wrapping_sub(1));
+        assert_eq!(state.verify_bump(&pda, &program_id), Err(ProgramError::InvalidSeeds));
+    }
+
+    #[test]
+    fn test_seeds_survive_authority_handoff() {
+        let program_id = Address::new_from_array([7u8; 32]);
+        let authority = Address::new_from_array([9u8; 32]);
+        let (pda, bump) = OracleState::find_address(&authority, &program_id);
+
+        let state = &mut OracleState::test_state();
+        state.authority = [9u8; 32];
+        state.set_seed_authority(&authority);
+        state.set_bump(bump);
+
+        // TransferAuthority, then AcceptAuthority by the new key
+        state.pending_authority = [3u8; 32];
+        assert_eq!(state.accept_authority(&[3u8; 32]), Ok(()));
+        assert_eq!(state.authority, [3u8; 32]);
+
+        // The PDA still signs with the seeds it was created from
+        assert_eq!(state.verify_bump(&pda, &program_id), Ok(()));
+    }
+
+    #[test]
+    fn test_best_protocol_validated_on_read() {
+        let state = &mut OracleState::test_state();
+
+        state.best_protocol = protocol::JITO;
+        assert_eq!(state.best_protocol(), Ok(ProtocolId::Jito));
+
+        // As an older or buggy writer could have left it
+        state.best_protocol = 9;
+        assert_eq!(state.best_protocol(), Err(OracleError::InvalidProtocol.into()));
     }
 }
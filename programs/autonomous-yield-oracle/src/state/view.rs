@@ -0,0 +1,192 @@
+//! Decoded oracle state for off-chain tooling
+//!
+//! `OracleState` is a byte-for-byte view of the account, with most fields
+//! stored as little-endian byte arrays. `OracleStateView` is the same state
+//! decoded into plain integers, for backtesters and indexers that want named
+//! fields rather than offsets. Only built with the `client` feature, which
+//! also derives `serde::Serialize`; none of it reaches the on-chain program.
+
+use serde::Serialize;
+use solana_program_error::ProgramError;
+
+use super::OracleState;
+use crate::instructions::protocol;
+
+/// Oracle state decoded into native types
+///
+/// Fields mirror the `OracleState` getters of the same name.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct OracleStateView {
+    /// Layout version the account was written with
+    pub version: u8,
+    pub authority: [u8; 32],
+    pub pending_authority: [u8; 32],
+    pub guardian: [u8; 32],
+    pub fee_destination: [u8; 32],
+    /// Raw byte, as stored; not necessarily a valid protocol ID
+    pub best_protocol: u8,
+    pub current_apy_bps: u16,
+    pub current_apy_centi_bps: u32,
+    pub risk_score: u8,
+    pub last_update: i64,
+    pub paused: bool,
+    pub initialized: bool,
+    pub config_locked: bool,
+    pub total_value_managed: u64,
+    pub peak_value_managed: u64,
+    pub max_value_managed: u64,
+    pub cumulative_pnl: i64,
+    pub peak_pnl: i64,
+    pub realized_apy_bps: i32,
+    pub apy_ema_bps: u16,
+    pub decisions_count: u64,
+    pub swaps_count: u64,
+    pub rebalances_count: u64,
+    pub created_at: i64,
+    /// Last `ResetStats` (0 = never)
+    pub last_reset_ts: i64,
+    pub last_rebalance_at: i64,
+    pub last_pnl_record_at: i64,
+    pub last_nonce: u64,
+    pub fee_bps: u16,
+    pub current_allocation_bps: [u16; protocol::COUNT],
+    pub target_allocation_bps: [u16; protocol::COUNT],
+    pub protocol_apy_bps: [u16; protocol::COUNT],
+    pub protocol_risk: [u8; protocol::COUNT],
+    pub protocol_fee_bps: [u16; protocol::COUNT],
+    pub min_liquidity: u64,
+    /// Protocols the oracle may act on, bit i for protocol i
+    pub protocol_allowlist: u8,
+    pub best_liquidity: u64,
+    pub net_deposits: i64,
+    /// Value managed beyond net deposits (see `OracleState::true_pnl`)
+    pub true_pnl: i64,
+    /// Last published ranking as (protocol, apy_bps, risk_score), best
+    /// first
+    pub recommendations: Vec<(u8, u16, u8)>,
+    /// APY history as (timestamp, apy_bps), oldest first
+    pub apy_history: Vec<(i64, u16)>,
+    /// Booked PnL records, oldest first
+    pub pnl_history: Vec<i64>,
+}
+
+impl OracleStateView {
+    /// Decode the data of an oracle account of any known version
+    ///
+    /// Fields an older layout predates decode as zero, as with
+    /// `OracleState::load_any_version`.
+    pub fn from_account_bytes(data: &[u8]) -> Result<Self, ProgramError> {
+        Ok(Self::from(&OracleState::load_any_version(data)?))
+    }
+}
+
+impl From<&OracleState> for OracleStateView {
+    fn from(state: &OracleState) -> Self {
+        Self {
+            version: state.version,
+            authority: state.authority,
+            pending_authority: state.pending_authority,
+            guardian: state.guardian,
+            fee_destination: state.fee_destination,
+            best_protocol: state.best_protocol,
+            current_apy_bps: state.current_apy_bps(),
+            current_apy_centi_bps: state.current_apy_centi_bps(),
+            risk_score: state.risk_score,
+            last_update: state.last_update(),
+            paused: state.is_paused(),
+            initialized: state.is_initialized(),
+            config_locked: state.is_config_locked(),
+            total_value_managed: state.total_value_managed(),
+            peak_value_managed: state.peak_value_managed(),
+            max_value_managed: state.max_value_managed(),
+            cumulative_pnl: state.cumulative_pnl(),
+            peak_pnl: state.peak_pnl(),
+            realized_apy_bps: state.realized_apy_bps(),
+            apy_ema_bps: state.apy_ema_bps(),
+            decisions_count: state.decisions_count(),
+            swaps_count: state.swaps_count(),
+            rebalances_count: state.rebalances_count(),
+            created_at: state.created_at(),
+            last_reset_ts: state.last_reset_ts(),
+            last_rebalance_at: state.last_rebalance_at(),
+            last_pnl_record_at: state.last_pnl_record_at(),
+            last_nonce: state.last_nonce(),
+            fee_bps: state.fee_bps(),
+            current_allocation_bps: state.current_allocation_bps(),
+            target_allocation_bps: state.target_allocation_bps(),
+            protocol_apy_bps: state.protocol_apy_bps(),
+            protocol_risk: state.protocol_risk(),
+            protocol_fee_bps: state.protocol_fee_bps(),
+            min_liquidity: state.min_liquidity(),
+            protocol_allowlist: state.protocol_allowlist(),
+            best_liquidity: state.best_liquidity(),
+            net_deposits: state.net_deposits(),
+            true_pnl: state.true_pnl(),
+            recommendations: state.recommendations().collect(),
+            apy_history: state.apy_history().collect(),
+            pnl_history: state.pnl_history().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::OracleError;
+    use crate::state::status;
+
+    #[test]
+    fn test_decodes_written_state() {
+        let mut data = [0u8; OracleState::LEN];
+        data[0] = OracleState::DISCRIMINATOR;
+        let state = OracleState::from_bytes_mut(&mut data).unwrap();
+        state.set(status::INITIALIZED);
+        state.authority = [7u8; 32];
+        state.guardian = [6u8; 32];
+        state.best_protocol = protocol::JITO;
+        state.set_current_apy_bps(1500);
+        state.risk_score = 20;
+        state.set_last_update(1_800_000_000);
+        state.set_total_value_managed(5_000_000);
+        state.add_pnl(-42).unwrap();
+        state.push_apy_history(1_000, 900);
+        state.push_apy_history(2_000, 1500);
+        state.set_current_allocation_bps([1000, 2000, 3000, 2000, 2000]).unwrap();
+        state.record_protocol_observation(protocol::JITO, 1500, 20).unwrap();
+        state.set_protocol_fee_bps([0, 0, 10, 0, 50]);
+        state.advance_nonce(42).unwrap();
+        state.set_paused(true);
+
+        let view = OracleStateView::from_account_bytes(&data).unwrap();
+        assert_eq!(view.version, OracleState::VERSION);
+        assert_eq!(view.authority, [7u8; 32]);
+        assert_eq!(view.guardian, [6u8; 32]);
+        assert_eq!(view.pending_authority, [0u8; 32]);
+        assert_eq!(view.best_protocol, protocol::JITO);
+        assert_eq!(view.current_apy_bps, 1500);
+        assert_eq!(view.risk_score, 20);
+        assert_eq!(view.last_update, 1_800_000_000);
+        assert!(view.paused);
+        assert!(view.initialized);
+        assert!(!view.config_locked);
+        assert_eq!(view.total_value_managed, 5_000_000);
+        assert_eq!(view.cumulative_pnl, -42);
+        assert_eq!(view.pnl_history, [-42]);
+        assert_eq!(view.apy_history, [(1_000, 900), (2_000, 1500)]);
+        assert_eq!(view.current_allocation_bps, [1000, 2000, 3000, 2000, 2000]);
+        assert_eq!(view.protocol_apy_bps, [0, 0, 0, 0, 1500]);
+        assert_eq!(view.protocol_risk, [0, 0, 0, 0, 20]);
+        assert_eq!(view.protocol_fee_bps, [0, 0, 10, 0, 50]);
+        assert_eq!(view.last_nonce, 42);
+
+        // Anything but an oracle account is refused
+        assert_eq!(
+            OracleStateView::from_account_bytes(&[0u8; OracleState::LEN]).err(),
+            Some(OracleError::NotInitialized.into())
+        );
+        assert_eq!(
+            OracleStateView::from_account_bytes(&data[..100]).err(),
+            Some(ProgramError::InvalidAccountData)
+        );
+    }
+}
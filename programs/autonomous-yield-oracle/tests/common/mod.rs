@@ -0,0 +1,149 @@
+//! Harness shared by the SBF tests
+//!
+//! Starts the program under `solana-program-test` with the payer's oracle
+//! PDA derived, and runs instructions against it. Test files add the
+//! helpers only they need in their own `impl Harness` blocks.
+
+// Each test file is its own crate and uses only part of this
+#![allow(dead_code)]
+
+use solana_program_test::{BanksClient, BanksClientError, ProgramTest};
+use solana_sdk::{
+    account::Account,
+    hash::Hash,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use solana_sdk_ids::system_program;
+
+use autonomous_yield_oracle::error::OracleError;
+use autonomous_yield_oracle::instructions::discriminator;
+use autonomous_yield_oracle::state::OracleState;
+
+pub struct Harness {
+    pub banks_client: BanksClient,
+    pub payer: Keypair,
+    pub blockhash: Hash,
+    pub program_id: Pubkey,
+    pub oracle: Pubkey,
+}
+
+impl Harness {
+    pub async fn start() -> Self {
+        let program_id = Pubkey::new_unique();
+        let mut program_test = ProgramTest::new("autonomous_yield_oracle", program_id, None);
+        program_test.prefer_bpf(true);
+
+        let (banks_client, payer, blockhash) = program_test.start().await;
+        let (oracle, _) =
+            Pubkey::find_program_address(&[b"oracle", payer.pubkey().as_ref()], &program_id);
+
+        Self {
+            banks_client,
+            payer,
+            blockhash,
+            program_id,
+            oracle,
+        }
+    }
+
+    pub fn instruction(&self, data: Vec<u8>, accounts: Vec<AccountMeta>) -> Instruction {
+        Instruction::new_with_bytes(self.program_id, &data, accounts)
+    }
+
+    /// Run one instruction, signed by the payer
+    pub async fn process(
+        &mut self,
+        data: Vec<u8>,
+        accounts: Vec<AccountMeta>,
+    ) -> Result<(), BanksClientError> {
+        let payer = self.payer.insecure_clone();
+        self.process_as(&payer, data, accounts).await
+    }
+
+    /// Run one instruction, signed by the payer and `signer`
+    pub async fn process_as(
+        &mut self,
+        signer: &Keypair,
+        data: Vec<u8>,
+        accounts: Vec<AccountMeta>,
+    ) -> Result<(), BanksClientError> {
+        let tx = Transaction::new_signed_with_payer(
+            &[self.instruction(data, accounts)],
+            Some(&self.payer.pubkey()),
+            &[&self.payer, signer],
+            self.blockhash,
+        );
+        self.banks_client.process_transaction(tx).await
+    }
+
+    /// Oracle and authority, the accounts of most instructions
+    pub fn oracle_accounts(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new(self.oracle, false),
+            AccountMeta::new_readonly(self.payer.pubkey(), true),
+        ]
+    }
+
+    /// Oracle, paying `authority`, and `system_program`: the accounts of
+    /// `Initialize` and `Deposit`
+    pub fn funding_accounts(&self, authority: Pubkey, system_program: Pubkey) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new(self.oracle, false),
+            AccountMeta::new(authority, true),
+            AccountMeta::new_readonly(system_program, false),
+        ]
+    }
+
+    /// `Initialize` the payer's oracle
+    pub async fn initialize(&mut self) {
+        let accounts = self.funding_accounts(self.payer.pubkey(), system_program::ID);
+        self.process(vec![discriminator::INITIALIZE], accounts).await.unwrap();
+    }
+
+    /// `Deposit` `lamports` from the payer
+    pub async fn deposit(&mut self, lamports: u64) {
+        let mut data = vec![discriminator::DEPOSIT];
+        data.extend_from_slice(&lamports.to_le_bytes());
+        let accounts = self.funding_accounts(self.payer.pubkey(), system_program::ID);
+        self.process(data, accounts).await.unwrap();
+    }
+
+    pub async fn set_paused(&mut self, paused: bool) {
+        let accounts = self.oracle_accounts();
+        self.process(vec![discriminator::SET_PAUSED, paused as u8], accounts).await.unwrap();
+    }
+
+    /// `EmergencyWithdraw` lamports only, signed for by `authority`
+    pub async fn emergency_withdraw_as(
+        &mut self,
+        authority: &Keypair,
+        destination: Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let accounts = vec![
+            AccountMeta::new(self.oracle, false),
+            AccountMeta::new_readonly(authority.pubkey(), true),
+            AccountMeta::new(destination, false),
+        ];
+        self.process_as(authority, vec![discriminator::EMERGENCY_WITHDRAW], accounts).await
+    }
+
+    pub async fn account(&mut self, address: Pubkey) -> Option<Account> {
+        self.banks_client.get_account(address).await.unwrap()
+    }
+
+    /// The oracle's state, as stored
+    pub async fn state(&mut self) -> OracleState {
+        let account = self.account(self.oracle).await.unwrap();
+        *OracleState::from_bytes(&account.data).unwrap()
+    }
+}
+
+pub fn assert_custom_error(err: BanksClientError, error: OracleError) {
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+    );
+}
@@ -0,0 +1,68 @@
+//! Compare Benchmark tests
+//!
+//! Runs the read-only `CompareBenchmark` against the SBF build with the
+//! same oracle bytes under this program and under another one: only the
+//! program-owned account may report. Run with `cargo test-sbf`.
+
+#![cfg(feature = "test-sbf")]
+
+use solana_program_test::ProgramTest;
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::Signer,
+    transaction::{Transaction, TransactionError},
+};
+
+use autonomous_yield_oracle::instructions::discriminator;
+use autonomous_yield_oracle::state::{status, OracleState};
+
+/// An initialized oracle account owned by `owner`
+fn oracle_account(owner: Pubkey) -> Account {
+    let mut data = vec![0u8; OracleState::LEN];
+    data[0] = OracleState::DISCRIMINATOR;
+    OracleState::from_bytes_mut(&mut data).unwrap().set(status::INITIALIZED);
+
+    Account {
+        lamports: LAMPORTS_PER_SOL,
+        data,
+        owner,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+#[tokio::test]
+async fn test_foreign_owned_oracle_is_refused() {
+    let program_id = Pubkey::new_unique();
+    let owned = Pubkey::new_unique();
+    let foreign = Pubkey::new_unique();
+
+    let mut program_test = ProgramTest::new("autonomous_yield_oracle", program_id, None);
+    program_test.prefer_bpf(true);
+    program_test.add_account(owned, oracle_account(program_id));
+    program_test.add_account(foreign, oracle_account(Pubkey::new_unique()));
+    let (banks_client, payer, blockhash) = program_test.start().await;
+
+    let compare = |oracle| {
+        let mut data = vec![discriminator::COMPARE_BENCHMARK];
+        data.extend_from_slice(&700u16.to_le_bytes());
+        let ix = Instruction::new_with_bytes(
+            program_id,
+            &data,
+            vec![AccountMeta::new_readonly(oracle, false)],
+        );
+        Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[&payer], blockhash)
+    };
+
+    banks_client.process_transaction(compare(owned)).await.unwrap();
+
+    // Same bytes, another program's account
+    let err = banks_client.process_transaction(compare(foreign)).await.unwrap_err();
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(0, InstructionError::InvalidAccountOwner)
+    );
+}
@@ -0,0 +1,102 @@
+//! Compute budget tests
+//!
+//! Runs the core instructions against the SBF build and fails if any of
+//! them exceeds its budget. Run with `cargo test-sbf`.
+//!
+//! | Instruction     | Budget (CU) |
+//! |-----------------|-------------|
+//! | `Initialize`    | 12,000      |
+//! | `MonitorYields` | 3,000       |
+//! | `Rebalance`     | 5,000       |
+//!
+//! `Initialize` is dominated by the PDA derivation and the `CreateAccount`
+//! CPI. Raise a budget only deliberately, alongside the change that needs it.
+
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    signature::Signer,
+    transaction::Transaction,
+};
+use solana_sdk_ids::{system_program, sysvar};
+
+use autonomous_yield_oracle::instructions::discriminator;
+use common::Harness;
+
+const INITIALIZE_CU_BUDGET: u64 = 12_000;
+const MONITOR_YIELDS_CU_BUDGET: u64 = 3_000;
+const REBALANCE_CU_BUDGET: u64 = 5_000;
+
+impl Harness {
+    /// Simulate `instruction` for its CU usage, then land it
+    async fn units_consumed(&mut self, instruction: Instruction) -> u64 {
+        let tx = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&self.payer.pubkey()),
+            &[&self.payer],
+            self.blockhash,
+        );
+
+        let simulation = self.banks_client.simulate_transaction(tx.clone()).await.unwrap();
+        simulation.result.unwrap().unwrap();
+        let units = simulation.simulation_details.unwrap().units_consumed;
+
+        self.banks_client.process_transaction(tx).await.unwrap();
+        units
+    }
+
+    /// `Initialize`, returning the CU it used
+    async fn initialize_units(&mut self) -> u64 {
+        let accounts = self.funding_accounts(self.payer.pubkey(), system_program::ID);
+        let ix = self.instruction(vec![discriminator::INITIALIZE], accounts);
+        self.units_consumed(ix).await
+    }
+}
+
+#[tokio::test]
+async fn test_initialize_within_budget() {
+    let mut harness = Harness::start().await;
+
+    let units = harness.initialize_units().await;
+    assert!(units <= INITIALIZE_CU_BUDGET, "Initialize used {units} CU (budget {INITIALIZE_CU_BUDGET})");
+}
+
+#[tokio::test]
+async fn test_monitor_yields_within_budget() {
+    let mut harness = Harness::start().await;
+    harness.initialize().await;
+
+    // protocol (1) + apy_bps (2) + risk_score (1) + timestamp (8) +
+    // nonce (8); any timestamp up to the cluster clock and any nonce above
+    // 0 are accepted by a fresh oracle
+    let mut data = vec![discriminator::MONITOR_YIELDS, 4];
+    data.extend_from_slice(&750u16.to_le_bytes());
+    data.push(10);
+    data.extend_from_slice(&0i64.to_le_bytes());
+    data.extend_from_slice(&1u64.to_le_bytes());
+
+    let mut accounts = harness.oracle_accounts();
+    accounts.push(AccountMeta::new_readonly(sysvar::clock::ID, false));
+    let ix = harness.instruction(data, accounts);
+    let units = harness.units_consumed(ix).await;
+    assert!(units <= MONITOR_YIELDS_CU_BUDGET, "MonitorYields used {units} CU (budget {MONITOR_YIELDS_CU_BUDGET})");
+}
+
+#[tokio::test]
+async fn test_rebalance_within_budget() {
+    let mut harness = Harness::start().await;
+    harness.initialize().await;
+
+    // 5 x allocation bps + max_slippage_bps
+    let mut data = vec![discriminator::REBALANCE];
+    for bps in [2000u16, 2000, 2000, 2000, 2000, 50] {
+        data.extend_from_slice(&bps.to_le_bytes());
+    }
+
+    let ix = harness.instruction(data, harness.oracle_accounts());
+    let units = harness.units_consumed(ix).await;
+    assert!(units <= REBALANCE_CU_BUDGET, "Rebalance used {units} CU (budget {REBALANCE_CU_BUDGET})");
+}
@@ -0,0 +1,32 @@
+//! Deposit tests
+//!
+//! Runs `Deposit` against the SBF build. Run with `cargo test-sbf`.
+
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+
+use common::Harness;
+
+impl Harness {
+    /// Lamport balance and `total_value_managed` of the oracle
+    async fn oracle_balances(&mut self) -> (u64, u64) {
+        let lamports = self.account(self.oracle).await.unwrap().lamports;
+        (lamports, self.state().await.total_value_managed())
+    }
+}
+
+#[tokio::test]
+async fn test_deposit_one_sol() {
+    let mut harness = Harness::start().await;
+    harness.initialize().await;
+    let (lamports_before, tvl_before) = harness.oracle_balances().await;
+
+    harness.deposit(LAMPORTS_PER_SOL).await;
+
+    let (lamports_after, tvl_after) = harness.oracle_balances().await;
+    assert_eq!(lamports_after, lamports_before + LAMPORTS_PER_SOL);
+    assert_eq!(tvl_after, tvl_before + LAMPORTS_PER_SOL);
+}
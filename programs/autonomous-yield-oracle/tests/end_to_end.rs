@@ -0,0 +1,133 @@
+//! End-to-end tests
+//!
+//! Drives one oracle through `Initialize`, `Deposit`, `MonitorYields`, and
+//! `EmergencyWithdraw` against the SBF build, reading the account back
+//! between steps, so the entrypoint, account borrows, and state layout are
+//! exercised as on a cluster. Run with `cargo test-sbf`.
+
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use solana_program_test::BanksClientError;
+use solana_sdk::{
+    instruction::AccountMeta,
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+use solana_sdk_ids::sysvar;
+
+use autonomous_yield_oracle::error::OracleError;
+use autonomous_yield_oracle::instructions::{discriminator, protocol};
+use autonomous_yield_oracle::state::OracleState;
+use common::{assert_custom_error, Harness};
+
+impl Harness {
+    /// `MonitorYields` one observation, signed for by `authority`
+    async fn monitor_yields_as(
+        &mut self,
+        authority: &Keypair,
+        protocol: u8,
+        apy_bps: u16,
+        nonce: u64,
+    ) -> Result<(), BanksClientError> {
+        // protocol (1) + apy_bps (2) + risk_score (1) + timestamp (8) +
+        // nonce (8); a timestamp of 0 is never ahead of the cluster clock
+        let mut data = vec![discriminator::MONITOR_YIELDS, protocol];
+        data.extend_from_slice(&apy_bps.to_le_bytes());
+        data.push(10);
+        data.extend_from_slice(&0i64.to_le_bytes());
+        data.extend_from_slice(&nonce.to_le_bytes());
+
+        let accounts = vec![
+            AccountMeta::new(self.oracle, false),
+            AccountMeta::new_readonly(authority.pubkey(), true),
+            AccountMeta::new_readonly(sysvar::clock::ID, false),
+        ];
+        self.process_as(authority, data, accounts).await
+    }
+}
+
+#[tokio::test]
+async fn test_initialize_monitor_and_withdraw() {
+    let mut harness = Harness::start().await;
+    let payer = harness.payer.insecure_clone();
+
+    harness.initialize().await;
+    harness.deposit(LAMPORTS_PER_SOL).await;
+
+    harness.monitor_yields_as(&payer, protocol::JITO, 750, 1).await.unwrap();
+    let state = harness.state().await;
+    assert_eq!(state.best_protocol, protocol::JITO);
+    assert_eq!(state.current_apy_bps(), 750);
+    assert_eq!(state.risk_score, 10);
+    assert_eq!(state.decisions_count(), 1);
+    assert_eq!(state.last_nonce(), 1);
+    assert_eq!(state.total_value_managed(), LAMPORTS_PER_SOL);
+
+    // A better yield elsewhere takes over
+    harness.monitor_yields_as(&payer, protocol::MARINADE, 900, 2).await.unwrap();
+    let state = harness.state().await;
+    assert_eq!(state.best_protocol, protocol::MARINADE);
+    assert_eq!(state.decisions_count(), 2);
+    assert_eq!(state.protocol_apy_bps()[protocol::JITO as usize], 750);
+
+    let destination = Pubkey::new_unique();
+    harness.emergency_withdraw_as(&payer, destination).await.unwrap();
+
+    let rent = harness.banks_client.get_rent().await.unwrap();
+    let account = harness.account(harness.oracle).await.unwrap();
+    assert_eq!(account.lamports, rent.minimum_balance(OracleState::LEN));
+    let state = OracleState::from_bytes(&account.data).unwrap();
+    assert!(state.is_paused());
+    assert_eq!(state.total_value_managed(), 0);
+    assert_eq!(state.net_deposits(), 0);
+
+    let destination = harness.account(destination).await.unwrap();
+    assert_eq!(destination.lamports, LAMPORTS_PER_SOL);
+
+    // Paused, so further observations are refused
+    let err = harness.monitor_yields_as(&payer, protocol::KAMINO, 1200, 3).await.unwrap_err();
+    assert_custom_error(err, OracleError::EmergencyModeActive);
+}
+
+#[tokio::test]
+async fn test_withdraw_at_the_rent_minimum_moves_nothing() {
+    let mut harness = Harness::start().await;
+    let payer = harness.payer.insecure_clone();
+    harness.initialize().await;
+
+    let rent = harness.banks_client.get_rent().await.unwrap();
+    let before = harness.account(harness.oracle).await.unwrap();
+    assert_eq!(before.lamports, rent.minimum_balance(OracleState::LEN));
+
+    let destination = Pubkey::new_unique();
+    harness.emergency_withdraw_as(&payer, destination).await.unwrap();
+
+    // Still paused, but the oracle keeps every lamport
+    let account = harness.account(harness.oracle).await.unwrap();
+    assert_eq!(account.lamports, before.lamports);
+    assert!(harness.state().await.is_paused());
+    assert!(harness.account(destination).await.is_none());
+}
+
+#[tokio::test]
+async fn test_other_signers_are_refused() {
+    let mut harness = Harness::start().await;
+    harness.initialize().await;
+    harness.deposit(LAMPORTS_PER_SOL).await;
+    let before = harness.account(harness.oracle).await.unwrap();
+
+    let impostor = Keypair::new();
+    let err = harness.monitor_yields_as(&impostor, protocol::JITO, 750, 1).await.unwrap_err();
+    assert_custom_error(err, OracleError::InvalidAuthority);
+
+    let destination = Pubkey::new_unique();
+    let err = harness.emergency_withdraw_as(&impostor, destination).await.unwrap_err();
+    assert_custom_error(err, OracleError::InvalidAuthority);
+
+    // Nothing moved
+    assert_eq!(harness.account(harness.oracle).await.unwrap(), before);
+    assert!(harness.account(destination).await.is_none());
+}
@@ -0,0 +1,133 @@
+//! Initialize tests
+//!
+//! Runs `Initialize` and `InitializeWithCreate` against the SBF build, from
+//! a PDA that doesn't exist yet: the account is created and initialized in
+//! the one instruction, so there is no window in which it exists
+//! uninitialized. Run with `cargo test-sbf`.
+
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use solana_program_test::BanksClientError;
+use solana_sdk::{
+    instruction::InstructionError,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::TransactionError,
+};
+use solana_sdk_ids::system_program;
+
+use autonomous_yield_oracle::instructions::discriminator;
+use autonomous_yield_oracle::state::OracleState;
+use common::Harness;
+
+impl Harness {
+    /// `Initialize` the payer's oracle PDA, signed for by `authority`
+    async fn initialize_as(&mut self, authority: &Keypair) -> Result<(), BanksClientError> {
+        self.initialize_with(authority, system_program::ID).await
+    }
+
+    /// `Initialize`, passing `system_program` as the system program
+    async fn initialize_with(
+        &mut self,
+        authority: &Keypair,
+        system_program: Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let accounts = self.funding_accounts(authority.pubkey(), system_program);
+        self.process_as(authority, vec![discriminator::INITIALIZE], accounts).await
+    }
+
+    /// `InitializeWithCreate` the payer's oracle PDA, signed for by `authority`
+    async fn initialize_with_create_as(
+        &mut self,
+        authority: &Keypair,
+    ) -> Result<(), BanksClientError> {
+        let accounts = self.funding_accounts(authority.pubkey(), system_program::ID);
+        self.process_as(authority, vec![discriminator::INITIALIZE_WITH_CREATE], accounts).await
+    }
+}
+
+#[tokio::test]
+async fn test_initialize_creates_and_claims_the_pda() {
+    let mut harness = Harness::start().await;
+    assert!(harness.account(harness.oracle).await.is_none());
+
+    let payer = harness.payer.insecure_clone();
+    harness.initialize_as(&payer).await.unwrap();
+
+    let account = harness.account(harness.oracle).await.unwrap();
+    let rent = harness.banks_client.get_rent().await.unwrap();
+    assert_eq!(account.owner, harness.program_id);
+    assert_eq!(account.data.len(), OracleState::LEN);
+    assert!(account.lamports >= rent.minimum_balance(OracleState::LEN));
+
+    let state = OracleState::from_bytes(&account.data).unwrap();
+    assert_eq!(state.version, OracleState::VERSION);
+    assert_eq!(state.authority, payer.pubkey().to_bytes());
+    assert!(state.is_initialized());
+
+    // Claimed once and for all
+    harness.blockhash = harness.banks_client.get_latest_blockhash().await.unwrap();
+    assert!(harness.initialize_as(&payer).await.is_err());
+}
+
+#[tokio::test]
+async fn test_only_the_derived_authority_can_claim_the_pda() {
+    let mut harness = Harness::start().await;
+
+    // The PDA is derived from the payer, so no one else can create it
+    let squatter = Keypair::new();
+    assert!(harness.initialize_as(&squatter).await.is_err());
+    assert!(harness.account(harness.oracle).await.is_none());
+
+    let payer = harness.payer.insecure_clone();
+    harness.initialize_as(&payer).await.unwrap();
+    assert_eq!(harness.state().await.authority, payer.pubkey().to_bytes());
+}
+
+#[tokio::test]
+async fn test_initialize_rejects_a_bogus_system_program() {
+    let mut harness = Harness::start().await;
+    let payer = harness.payer.insecure_clone();
+
+    let err = harness.initialize_with(&payer, Pubkey::new_unique()).await.unwrap_err();
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(0, InstructionError::IncorrectProgramId)
+    );
+    assert!(harness.account(harness.oracle).await.is_none());
+}
+
+#[tokio::test]
+async fn test_initialize_with_create_end_to_end() {
+    let mut harness = Harness::start().await;
+    let payer = harness.payer.insecure_clone();
+
+    // A squatter can't create the payer's PDA
+    let squatter = Keypair::new();
+    assert!(harness.initialize_with_create_as(&squatter).await.is_err());
+    assert!(harness.account(harness.oracle).await.is_none());
+
+    harness.initialize_with_create_as(&payer).await.unwrap();
+
+    let account = harness.account(harness.oracle).await.unwrap();
+    let rent = harness.banks_client.get_rent().await.unwrap();
+    assert_eq!(account.owner, harness.program_id);
+    assert_eq!(account.data.len(), OracleState::LEN);
+    assert_eq!(account.lamports, rent.minimum_balance(OracleState::LEN));
+
+    let state = OracleState::from_bytes(&account.data).unwrap();
+    assert_eq!(state.version, OracleState::VERSION);
+    assert_eq!(state.authority, payer.pubkey().to_bytes());
+    assert!(state.is_initialized());
+
+    // The account exists now, so neither path claims it again
+    harness.blockhash = harness.banks_client.get_latest_blockhash().await.unwrap();
+    let err = harness.initialize_with_create_as(&payer).await.unwrap_err();
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(0, InstructionError::AccountAlreadyInitialized)
+    );
+    assert!(harness.initialize_as(&payer).await.is_err());
+}
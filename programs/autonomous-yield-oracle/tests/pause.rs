@@ -0,0 +1,77 @@
+//! Pause tests
+//!
+//! Runs `SetPaused` against the SBF build: a paused oracle refuses
+//! `ExecuteSwap` but still lets the authority `EmergencyWithdraw`, so
+//! pausing never locks funds in. Run with `cargo test-sbf`.
+
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use solana_program_test::BanksClientError;
+use solana_sdk::{instruction::AccountMeta, native_token::LAMPORTS_PER_SOL, pubkey::Pubkey};
+
+use autonomous_yield_oracle::error::OracleError;
+use autonomous_yield_oracle::instructions::{discriminator, protocol};
+use autonomous_yield_oracle::state::OracleState;
+use common::{assert_custom_error, Harness};
+
+impl Harness {
+    /// `ExecuteSwap` a Jupiter route executed off-chain, so nothing is
+    /// CPI'd and only the oracle's checks decide the outcome
+    async fn execute_swap(&mut self) -> Result<(), BanksClientError> {
+        // amount_in (8) + min_amount_out (8) + protocol (1)
+        let mut data = vec![discriminator::EXECUTE_SWAP];
+        data.extend_from_slice(&1_000u64.to_le_bytes());
+        data.extend_from_slice(&990u64.to_le_bytes());
+        data.push(protocol::JUPITER_ROUTE);
+
+        // Source and destination token accounts only need to be distinct;
+        // an off-chain swap never reads them
+        let mut accounts = self.oracle_accounts();
+        accounts.push(AccountMeta::new(Pubkey::new_unique(), false));
+        accounts.push(AccountMeta::new(Pubkey::new_unique(), false));
+        self.process(data, accounts).await
+    }
+}
+
+#[tokio::test]
+async fn test_paused_oracle_refuses_swaps_but_allows_emergency_withdraw() {
+    let mut harness = Harness::start().await;
+    harness.initialize().await;
+    harness.deposit(LAMPORTS_PER_SOL).await;
+    harness.set_paused(true).await;
+
+    let before = harness.account(harness.oracle).await.unwrap();
+    let err = harness.execute_swap().await.unwrap_err();
+    assert_custom_error(err, OracleError::EmergencyModeActive);
+    assert_eq!(harness.account(harness.oracle).await.unwrap(), before);
+
+    // Pausing never locks the funds in
+    let destination = Pubkey::new_unique();
+    let payer = harness.payer.insecure_clone();
+    harness.emergency_withdraw_as(&payer, destination).await.unwrap();
+
+    let rent = harness.banks_client.get_rent().await.unwrap();
+    let account = harness.account(harness.oracle).await.unwrap();
+    assert_eq!(account.lamports, rent.minimum_balance(OracleState::LEN));
+    let state = OracleState::from_bytes(&account.data).unwrap();
+    assert!(state.is_paused());
+    assert_eq!(state.total_value_managed(), 0);
+    assert_eq!(harness.account(destination).await.unwrap().lamports, LAMPORTS_PER_SOL);
+}
+
+#[tokio::test]
+async fn test_resumed_oracle_swaps_again() {
+    let mut harness = Harness::start().await;
+    harness.initialize().await;
+    harness.set_paused(true).await;
+    assert!(harness.execute_swap().await.is_err());
+
+    harness.set_paused(false).await;
+    harness.execute_swap().await.unwrap();
+
+    let state = harness.state().await;
+    assert!(!state.is_paused());
+    assert_eq!(state.swaps_count(), 1);
+}
@@ -0,0 +1,53 @@
+//! Rebalance And Settle tests
+//!
+//! Runs `RebalanceAndSettle` against the SBF build. Run with
+//! `cargo test-sbf`. A fresh oracle manages nothing, so no legs are swapped.
+
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use solana_program_test::BanksClientError;
+
+use autonomous_yield_oracle::instructions::discriminator;
+use common::Harness;
+
+impl Harness {
+    async fn rebalance_and_settle(&mut self, target: [u16; 5]) -> Result<(), BanksClientError> {
+        // 5 x allocation bps + max_slippage_bps
+        let mut data = vec![discriminator::REBALANCE_AND_SETTLE];
+        for bps in target.into_iter().chain([50]) {
+            data.extend_from_slice(&bps.to_le_bytes());
+        }
+        let accounts = self.oracle_accounts();
+        self.process(data, accounts).await
+    }
+
+    async fn oracle_data(&mut self) -> Vec<u8> {
+        self.account(self.oracle).await.unwrap().data
+    }
+}
+
+#[tokio::test]
+async fn test_rebalance_and_settle() {
+    let mut harness = Harness::start().await;
+    harness.initialize().await;
+
+    harness.rebalance_and_settle([4000, 3000, 2000, 500, 500]).await.unwrap();
+
+    let state = harness.state().await;
+    assert_eq!(state.current_allocation_bps(), [4000, 3000, 2000, 500, 500]);
+    assert_ne!(state.last_rebalance_at(), 0);
+}
+
+#[tokio::test]
+async fn test_rebalance_and_settle_guard_aborts() {
+    let mut harness = Harness::start().await;
+    harness.initialize().await;
+
+    harness.set_paused(true).await;
+    let before = harness.oracle_data().await;
+
+    assert!(harness.rebalance_and_settle([4000, 3000, 2000, 500, 500]).await.is_err());
+    assert_eq!(harness.oracle_data().await, before);
+}